@@ -1,6 +1,9 @@
+mod snapshot;
+mod theme;
 pub mod ui;
 
 use std::{
+    collections::{HashMap, VecDeque},
     error::Error,
     io,
     sync::{mpsc, Arc, Mutex},
@@ -8,37 +11,328 @@ use std::{
     time::{Duration, Instant},
 };
 
-use crossterm::event::KeyCode;
+use chrono::{DateTime, Utc};
+use crossterm::event::{KeyCode, MouseButton, MouseEvent, MouseEventKind};
+use itertools::Itertools;
 use ratatui::{
     backend::{Backend, CrosstermBackend},
-    style::Stylize,
+    layout::{Position, Rect},
     terminal::{Terminal, Viewport},
-    text::{Span, Text},
-    widgets::{Paragraph, Widget},
     TerminalOptions,
 };
 
-use crate::tracing::message::Message;
+use crate::{
+    executor::Pause,
+    runner::progress::Window,
+    state::{App, ExecutorState},
+    tracing::{
+        message::Message,
+        task_event::{metrics::MetricValue, MetricSetKey},
+    },
+};
 
+pub use theme::Theme;
 use ui::ui;
 
 #[derive(Debug)]
 enum Event {
     Input(crossterm::event::KeyEvent),
+    Mouse(MouseEvent),
     Tick,
     Resize,
     Message(Message),
 }
 
+/// Options of the terminal UI, see [`Runner::tui_config`](crate::runner::Runner::tui_config).
+#[derive(Debug, Clone, Copy)]
+pub struct TuiConfig {
+    refresh_rate: Duration,
+    history: usize,
+    theme: Theme,
+}
+
+impl Default for TuiConfig {
+    fn default() -> Self {
+        Self {
+            refresh_rate: Duration::from_millis(200),
+            history: 300,
+            theme: Theme::from_env(),
+        }
+    }
+}
+
+impl TuiConfig {
+    /// Interval between redraws, 200ms by default. A longer one lowers the cost of drawing
+    /// over slow terminals and SSH sessions.
+    pub fn refresh_rate(mut self, refresh_rate: Duration) -> Self {
+        self.refresh_rate = refresh_rate;
+        self
+    }
+
+    /// Number of updates of every metric kept for the zoomed view of a metric, 300 by default.
+    /// The overview charts show the last [`DEFAULT_HISTORY`](crate::state::DEFAULT_HISTORY).
+    pub fn history(mut self, samples: usize) -> Self {
+        self.history = samples;
+        self
+    }
+
+    /// Colors of the UI, [`Theme::from_env`] by default so that `NO_COLOR` is honored
+    /// unless a theme is picked here.
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub(crate) fn history_len(&self) -> usize {
+        self.history
+    }
+}
+
+/// Period of the samples of the iterations per second sparkline.
+const THROUGHPUT_INTERVAL: Duration = Duration::from_secs(1);
+/// Samples kept for the sparkline, older ones are dropped.
+const THROUGHPUT_SAMPLES: usize = 120;
+
+/// Entries kept for the log pane, older ones are dropped.
+const LOG_CAPACITY: usize = 200;
+
+/// How long a notice replaces the help hint.
+const NOTICE_TIME: Duration = Duration::from_secs(5);
+
+/// Size of the pane at the bottom listing errors and warnings.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum LogPane {
+    Hidden,
+    #[default]
+    Collapsed,
+    Expanded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogKind {
+    Error,
+    Warning,
+    Terminated,
+}
+
+#[derive(Debug)]
+struct LogEntry {
+    time: DateTime<Utc>,
+    kind: LogKind,
+    /// scenario and executor the entry came from
+    source: String,
+    message: String,
+}
+
+/// Recent iterations and bytes per second of an executor.
+#[derive(Debug)]
+struct Throughput {
+    /// Iterations completed at the time of the last sample.
+    iterations: u64,
+    /// Totals of every bytes metric by name at the time of the last sample.
+    bytes: HashMap<&'static str, u64>,
+    time: Instant,
+    samples: VecDeque<u64>,
+    /// Iterations per second between the last two samples.
+    rate: f64,
+    /// Bytes per second of every bytes metric between the last two samples, ordered by name.
+    byte_rates: Vec<(&'static str, f64)>,
+}
+
 #[derive(Debug, Default)]
 struct TuiState {
+    /// Scenario shown while browsing earlier ones, the running one when `None`.
+    viewed_scenario: Option<usize>,
     current_exec_selected: usize,
     execs_len: usize,
+    /// Index of the first metric shown, clamped to the number of metrics on render.
+    metrics_offset: usize,
+    /// Number of metrics that fit on screen on the last render.
+    metrics_shown: usize,
+    log_pane: LogPane,
+    /// Panels collapsed to make room for the others on small terminals.
+    hide_logo: bool,
+    hide_left: bool,
+    hide_metrics: bool,
+    show_help: bool,
+    /// Metric at the top of the panel on the last render, zoomed into with Enter.
+    selected_metric: Option<MetricSetKey>,
+    /// Metric shown full screen instead of the overview.
+    zoomed: Option<MetricSetKey>,
+    /// Table of every executor of the run shown instead of the selected one.
+    show_table: bool,
+    /// Terms narrowing the metrics shown, see [`ui::matches`].
+    filter: String,
+    /// Whether keys are typed into the filter.
+    editing_filter: bool,
+    /// Latest errors and warnings, oldest first.
+    log: VecDeque<LogEntry>,
+    /// Short message shown in place of the help hint, such as where a snapshot was written.
+    notice: Option<(Instant, String)>,
+    pause: Arc<Pause>,
+    theme: Theme,
+    /// Where the list of executors was drawn on the last render, one executor per line
+    /// after the heading.
+    executors_area: Rect,
+    metrics_area: Rect,
+    log_area: Rect,
+    /// Iteration times of executors keyed by scenario and executor index.
+    iteration_times: HashMap<(usize, usize), Window>,
+    /// Throughput of executors keyed by scenario and executor index.
+    throughput: HashMap<(usize, usize), Throughput>,
+}
+
+impl TuiState {
+    /// Index of the scenario shown.
+    fn scenario(&self, app: &App) -> usize {
+        self.viewed_scenario
+            .unwrap_or_else(|| app.current_scenario_id())
+    }
+
+    /// Sample the iterations per second of running executors from the change of their
+    /// iteration count, once every [`THROUGHPUT_INTERVAL`].
+    fn sample_throughput(&mut self, app: &Mutex<App>) {
+        let app = app.lock().unwrap();
+        let scenario_id = app.current_scenario_id();
+        let now = Instant::now();
+        for (id, exec) in app.current_scenario().execs.iter().enumerate() {
+            if !exec.started() || exec.ended() {
+                continue;
+            }
+            let throughput =
+                self.throughput
+                    .entry((scenario_id, id))
+                    .or_insert_with(|| Throughput {
+                        iterations: exec.iterations(),
+                        bytes: bytes_totals(exec),
+                        time: now,
+                        samples: VecDeque::new(),
+                        rate: 0.,
+                        byte_rates: Vec::new(),
+                    });
+            let elapsed = now - throughput.time;
+            if elapsed < THROUGHPUT_INTERVAL {
+                continue;
+            }
+            let iterations = exec.iterations().saturating_sub(throughput.iterations);
+            throughput.rate = iterations as f64 / elapsed.as_secs_f64();
+            if throughput.samples.len() == THROUGHPUT_SAMPLES {
+                throughput.samples.pop_front();
+            }
+            throughput.samples.push_back(throughput.rate.round() as u64);
+            throughput.iterations = exec.iterations();
+
+            let bytes = bytes_totals(exec);
+            throughput.byte_rates = bytes
+                .iter()
+                .map(|(name, total)| {
+                    let previous = throughput.bytes.get(name).copied().unwrap_or_default();
+                    let delta = total.saturating_sub(previous);
+                    (*name, delta as f64 / elapsed.as_secs_f64())
+                })
+                .sorted_by_key(|x| x.0)
+                .collect();
+            throughput.bytes = bytes;
+            throughput.time = now;
+        }
+    }
+
+    /// Click an executor to select it, click the log pane to expand or collapse it and scroll
+    /// the metrics with the wheel.
+    fn mouse(&mut self, event: MouseEvent) {
+        let position = Position {
+            x: event.column,
+            y: event.row,
+        };
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) if self.show_help => self.show_help = false,
+            MouseEventKind::Down(MouseButton::Left) if self.executors_area.contains(position) => {
+                let index = (position.y - self.executors_area.y) as usize;
+                if (1..=self.execs_len).contains(&index) {
+                    self.current_exec_selected = index - 1;
+                    self.metrics_offset = 0;
+                }
+            }
+            MouseEventKind::Down(MouseButton::Left) if self.log_area.contains(position) => {
+                self.log_pane = match self.log_pane {
+                    LogPane::Collapsed => LogPane::Expanded,
+                    _ => LogPane::Collapsed,
+                }
+            }
+            MouseEventKind::ScrollDown if self.metrics_area.contains(position) => {
+                self.metrics_offset += 1
+            }
+            MouseEventKind::ScrollUp if self.metrics_area.contains(position) => {
+                self.metrics_offset = self.metrics_offset.saturating_sub(1)
+            }
+            _ => (),
+        }
+    }
+
+    /// Show the scenario `step` away from the one shown, wrapping around the scenarios that
+    /// started so far when `wrap` and stopping at either end otherwise.
+    fn browse(&mut self, app: &Mutex<App>, step: isize, wrap: bool) {
+        let app = app.lock().unwrap();
+        let started = app.current_scenario_id() + 1;
+        let shown = self.scenario(&app) as isize + step;
+        let shown = match wrap {
+            true => shown.rem_euclid(started as isize) as usize,
+            false => shown.clamp(0, started as isize - 1) as usize,
+        };
+        if shown == self.scenario(&app) {
+            return;
+        }
+        self.viewed_scenario = (shown + 1 != started).then_some(shown);
+        self.current_exec_selected = 0;
+        self.metrics_offset = 0;
+        self.execs_len = app.scenarios()[shown].execs.len();
+    }
+
+    fn log(&mut self, app: &Mutex<App>, kind: LogKind, id: Option<usize>, message: &str) {
+        let app = app.lock().unwrap();
+        let scenario = app.current_scenario();
+        let source = match id.and_then(|id| scenario.execs.get(id)) {
+            Some(exec) => format!("{} / {}", scenario.name, exec.config),
+            None => scenario.name.clone(),
+        };
+        if self.log.len() == LOG_CAPACITY {
+            self.log.pop_front();
+        }
+        self.log.push_back(LogEntry {
+            time: Utc::now(),
+            kind,
+            source,
+            message: message.split_whitespace().collect::<Vec<_>>().join(" "),
+        });
+    }
+}
+
+/// Totals of the bytes metrics of an executor by name, over every set of attributes.
+fn bytes_totals(exec: &ExecutorState) -> HashMap<&'static str, u64> {
+    let mut totals = HashMap::new();
+    for (key, values) in exec.metrics() {
+        if let Some(MetricValue::Bytes((total, _))) = values.back() {
+            *totals.entry(key.name).or_default() += total;
+        }
+    }
+    totals
+}
+
+/// Whether stdout is a terminal the UI can draw on. Output piped to a file or a CI log, or a
+/// terminal declaring itself `dumb`, would be corrupted by the escape sequences of the UI.
+pub fn supported() -> bool {
+    use std::io::IsTerminal;
+
+    io::stdout().is_terminal()
+        && std::env::var_os("TERM").is_none_or(|x| x != "dumb")
+        && crossterm::terminal::size().is_ok_and(|(width, height)| width > 0 && height > 0)
 }
 
 pub fn run(
-    app: Arc<Mutex<super::App>>,
+    app: Arc<Mutex<App>>,
     mut tracing_messages: crate::Receiver<Message>,
+    handle: crate::runner::RunnerHandle,
+    config: TuiConfig,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     crossterm::terminal::enable_raw_mode()?;
     let stdout = io::stdout();
@@ -50,10 +344,11 @@ pub fn run(
         },
     )?;
     let _ = terminal.clear();
+    crossterm::execute!(io::stdout(), crossterm::event::EnableMouseCapture)?;
 
     let (tx, rx) = mpsc::channel();
 
-    input_handling(tx.clone());
+    input_handling(tx.clone(), config.refresh_rate);
 
     let _app = app.clone();
     thread::spawn(move || {
@@ -62,8 +357,10 @@ pub fn run(
                 message,
                 Message::End
                     | Message::Error { .. }
+                    | Message::Warning { .. }
                     | Message::TerminatedError { .. }
                     | Message::ScenarioChanged { .. }
+                    | Message::TaskTime { .. }
             ) {
                 let _ = tx.send(Event::Message(message.clone()));
             }
@@ -71,17 +368,25 @@ pub fn run(
         }
     });
 
-    run_app(&mut terminal, app, rx)?;
+    let result = run_app(&mut terminal, app, rx, handle.pause_gate(), config.theme);
+    // Nothing is left to resume the run once the TUI exits.
+    handle.resume();
+    crossterm::execute!(io::stdout(), crossterm::event::DisableMouseCapture)?;
+    let aborted = result?;
 
     let size = terminal.get_frame().size();
     terminal.set_cursor(size.width, size.height + size.y + 1)?;
     crossterm::terminal::disable_raw_mode()?;
 
+    if aborted {
+        // Ctrl-C in raw mode does not raise SIGINT, exit the way it would have.
+        std::process::exit(130);
+    }
+
     Ok(())
 }
 
-fn input_handling(tx: mpsc::Sender<Event>) -> thread::JoinHandle<()> {
-    let tick_rate = Duration::from_millis(200);
+fn input_handling(tx: mpsc::Sender<Event>, tick_rate: Duration) -> thread::JoinHandle<()> {
     thread::spawn(move || {
         let mut last_tick = Instant::now();
         loop {
@@ -89,6 +394,7 @@ fn input_handling(tx: mpsc::Sender<Event>) -> thread::JoinHandle<()> {
             if crossterm::event::poll(timeout).unwrap() {
                 match crossterm::event::read().unwrap() {
                     crossterm::event::Event::Key(key) => tx.send(Event::Input(key)).unwrap(),
+                    crossterm::event::Event::Mouse(event) => tx.send(Event::Mouse(event)).unwrap(),
                     crossterm::event::Event::Resize(_, _) => tx.send(Event::Resize).unwrap(),
                     _ => {}
                 };
@@ -105,12 +411,17 @@ fn input_handling(tx: mpsc::Sender<Event>) -> thread::JoinHandle<()> {
 
 fn run_app<B: Backend>(
     terminal: &mut Terminal<B>,
-    app: Arc<Mutex<super::App>>,
+    app: Arc<Mutex<App>>,
     rx: mpsc::Receiver<Event>,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
+    pause: Arc<Pause>,
+    theme: Theme,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
     let mut tui_state = TuiState {
+        pause: pause.clone(),
+        theme,
         current_exec_selected: 0,
         execs_len: app.lock().unwrap().current_scenario().execs.len(),
+        ..Default::default()
     };
 
     let mut events: Vec<Event> = Vec::new();
@@ -122,61 +433,137 @@ fn run_app<B: Backend>(
         rx.try_iter().for_each(|x| events.push(x));
         for event in events.drain(..) {
             match event {
-                Event::Input(event) => match event.code {
-                    KeyCode::Char('c')
-                        if event.modifiers == crossterm::event::KeyModifiers::CONTROL =>
-                    {
-                        break 'a;
+                Event::Input(event)
+                    if event.code == KeyCode::Char('c')
+                        && event.modifiers == crossterm::event::KeyModifiers::CONTROL =>
+                {
+                    return Ok(true);
+                }
+                Event::Input(event) if tui_state.editing_filter => {
+                    match event.code {
+                        KeyCode::Char(c) => tui_state.filter.push(c),
+                        KeyCode::Backspace => {
+                            tui_state.filter.pop();
+                        }
+                        KeyCode::Enter => tui_state.editing_filter = false,
+                        KeyCode::Esc => {
+                            tui_state.filter.clear();
+                            tui_state.editing_filter = false;
+                        }
+                        _ => (),
                     }
+                    tui_state.metrics_offset = 0;
+                }
+                Event::Input(event) => match event.code {
+                    KeyCode::Char('/') => tui_state.editing_filter = true,
                     KeyCode::Up => {
                         tui_state.current_exec_selected =
-                            tui_state.current_exec_selected.saturating_sub(1)
+                            tui_state.current_exec_selected.saturating_sub(1);
+                        tui_state.metrics_offset = 0;
                     }
                     KeyCode::Down => {
                         tui_state.current_exec_selected =
-                            (tui_state.current_exec_selected + 1).min(tui_state.execs_len - 1)
+                            (tui_state.current_exec_selected + 1).min(tui_state.execs_len - 1);
+                        tui_state.metrics_offset = 0;
+                    }
+                    KeyCode::PageUp => {
+                        tui_state.metrics_offset = tui_state
+                            .metrics_offset
+                            .saturating_sub(tui_state.metrics_shown.max(1))
+                    }
+                    KeyCode::PageDown => tui_state.metrics_offset += tui_state.metrics_shown.max(1),
+                    KeyCode::Char('k') => {
+                        tui_state.metrics_offset = tui_state.metrics_offset.saturating_sub(1)
+                    }
+                    KeyCode::Char('j') => tui_state.metrics_offset += 1,
+                    KeyCode::Home => tui_state.metrics_offset = 0,
+                    KeyCode::Left => tui_state.browse(&app, -1, false),
+                    KeyCode::Right => tui_state.browse(&app, 1, false),
+                    KeyCode::Tab => tui_state.browse(&app, 1, true),
+                    KeyCode::Char('p') => pause.set(!pause.is_paused()),
+                    KeyCode::Char('q') => pause.stop(),
+                    KeyCode::Char('s') => {
+                        let result = snapshot::write(&app.lock().unwrap());
+                        match result {
+                            Ok(path) => {
+                                let notice = format!("Snapshot written to {}", path.display());
+                                tui_state.notice = Some((Instant::now(), notice));
+                            }
+                            Err(err) => {
+                                let err = format!("failed to write snapshot: {err}");
+                                tui_state.log(&app, LogKind::Error, None, &err);
+                            }
+                        }
+                    }
+                    KeyCode::Char('?') => tui_state.show_help = !tui_state.show_help,
+                    KeyCode::Enter => tui_state.zoomed = tui_state.selected_metric.clone(),
+                    KeyCode::Esc if tui_state.show_help => tui_state.show_help = false,
+                    KeyCode::Esc if tui_state.zoomed.is_some() => tui_state.zoomed = None,
+                    KeyCode::Esc if tui_state.show_table => tui_state.show_table = false,
+                    KeyCode::Esc => tui_state.filter.clear(),
+                    KeyCode::Char('e') => {
+                        tui_state.log_pane = match tui_state.log_pane {
+                            LogPane::Hidden => LogPane::Collapsed,
+                            LogPane::Collapsed => LogPane::Expanded,
+                            LogPane::Expanded => LogPane::Hidden,
+                        }
+                    }
+                    KeyCode::Char('t') => tui_state.show_table = !tui_state.show_table,
+                    KeyCode::Char('o') => tui_state.hide_logo = !tui_state.hide_logo,
+                    KeyCode::Char('1') => tui_state.hide_left = !tui_state.hide_left,
+                    KeyCode::Char('2') => tui_state.hide_metrics = !tui_state.hide_metrics,
+                    KeyCode::Char('3') => {
+                        tui_state.log_pane = match tui_state.log_pane {
+                            LogPane::Hidden => LogPane::Collapsed,
+                            _ => LogPane::Hidden,
+                        }
                     }
                     _ => (),
                 },
+                Event::Mouse(event) => tui_state.mouse(event),
                 Event::Resize => {
                     terminal.autoresize()?;
                 }
                 Event::Tick => {
-                    terminal.draw(|f| ui(f, &app, &tui_state))?;
+                    tui_state.sample_throughput(&app);
+                    terminal.draw(|f| ui(f, &app, &mut tui_state))?;
                 }
                 Event::Message(message) => {
                     match message {
                         Message::End => {
                             // redraw for the last time
-                            terminal.draw(|f| ui(f, &app, &tui_state))?;
+                            terminal.draw(|f| ui(f, &app, &mut tui_state))?;
                             break 'a;
                         }
                         Message::TerminatedError { err } => {
-                            let mut text = Text::from(err.as_str());
-                            if let Some(line) = text.lines.first_mut() {
-                                line.spans
-                                    .insert(0, Span::raw("Termination Err: ").bold().red())
-                            }
-
-                            if let Err(err) = terminal
-                                .insert_before(text.height() as u16, |buf| {
-                                    Paragraph::new(text).render(buf.area, buf);
-                                })
-                                .and_then(|_| terminal.draw(|f| ui(f, &app, &tui_state)))
-                            {
-                                return Err(Box::new(err));
-                            }
+                            tui_state.log(&app, LogKind::Terminated, None, &err);
+                            terminal.draw(|f| ui(f, &app, &mut tui_state))?;
                         }
-                        Message::Error { err } => {
-                            let text = Text::from(err.to_string());
-                            // redraw for the last time
-                            let _ = terminal.insert_before(text.height() as u16, |buf| {
-                                Paragraph::new(text).render(buf.area, buf);
-                            });
+                        Message::Error { execution_id, err } => {
+                            tui_state.log(&app, LogKind::Error, execution_id, &err);
+                        }
+                        Message::Warning {
+                            execution_id,
+                            warning,
+                        } => {
+                            tui_state.log(&app, LogKind::Warning, execution_id, &warning);
                         }
-                        Message::ScenarioChanged { .. } => {
+                        Message::TaskTime {
+                            execution_id,
+                            scenario_id,
+                            duration,
+                        } => {
+                            tui_state
+                                .iteration_times
+                                .entry((scenario_id, execution_id))
+                                .or_default()
+                                .observe(duration);
+                        }
+                        // Keep showing the scenario browsed to, if any.
+                        Message::ScenarioChanged { .. } if tui_state.viewed_scenario.is_none() => {
                             let app = &app.lock().unwrap();
                             tui_state.current_exec_selected = 0;
+                            tui_state.metrics_offset = 0;
                             tui_state.execs_len = app.current_scenario().execs.len()
                         }
                         _ => (),
@@ -185,5 +572,5 @@ fn run_app<B: Backend>(
             }
         }
     }
-    Ok(())
+    Ok(false)
 }
@@ -0,0 +1,174 @@
+//! Plain text dump of the state shown by the terminal UI, written with the `s` key.
+
+use std::{fmt::Write, path::PathBuf};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::{
+    state::{App, ExecutorState},
+    tracing::{sink::fields, task_event::MetricSetKey},
+};
+
+/// Write a snapshot of `app` to a timestamped file in the working directory.
+pub(super) fn write(app: &App) -> std::io::Result<PathBuf> {
+    let time = Utc::now();
+    let path = PathBuf::from(format!(
+        "rusher-snapshot-{}.txt",
+        time.format("%Y%m%dT%H%M%S")
+    ));
+    std::fs::write(&path, render(app, time))?;
+    Ok(path)
+}
+
+fn render(app: &App, time: DateTime<Utc>) -> String {
+    let mut text = String::new();
+    let _ = writeln!(
+        text,
+        "Snapshot at {}",
+        time.to_rfc3339_opts(SecondsFormat::Secs, true)
+    );
+    for (index, scenario) in app.scenarios.iter().enumerate() {
+        if !scenario.execs.iter().any(ExecutorState::started) {
+            continue;
+        }
+        let running = match index == app.current_scenario {
+            true => " (running)",
+            false => "",
+        };
+        let _ = writeln!(text, "\nScenario {}{running}", scenario.name);
+        for exec in scenario.execs.iter().filter(|x| x.started()) {
+            executor(&mut text, exec);
+        }
+    }
+    if !app.thresholds.is_empty() {
+        let _ = writeln!(text, "\nThresholds");
+    }
+    for result in &app.thresholds {
+        let outcome = match result.passed {
+            true => "passed",
+            false => "failed",
+        };
+        let _ = writeln!(text, "  {outcome} {}", result.threshold);
+    }
+    text
+}
+
+fn executor(text: &mut String, exec: &ExecutorState) {
+    let _ = writeln!(text, "\n  Executor {}", exec.config);
+    let (min, mean, max) = exec.iteration_times();
+    let mut progress = vec![
+        format!("users {}/{}", exec.users, exec.max_users),
+        match exec.total_iteration {
+            Some(total) => format!("iterations {}/{total}", exec.iterations),
+            None => format!("iterations {}", exec.iterations),
+        },
+        format!("errors {}", exec.errors),
+        match exec.total_duration {
+            Some(total) => format!("duration {:?}/{total:?}", exec.duration()),
+            None => format!("duration {:?}", exec.duration()),
+        },
+    ];
+    if let (Some(stage), Some(stages)) = (exec.stage, exec.stages) {
+        progress.push(format!("stage {}/{stages}", stage + 1));
+    }
+    if exec.ended {
+        progress.push("ended".to_string());
+    }
+    let _ = writeln!(text, "    {}", progress.join(", "));
+    let _ = writeln!(
+        text,
+        "    iteration time min {min:?}, mean {mean:?}, max {max:?}"
+    );
+
+    let mut metrics: Vec<_> = exec
+        .metrics
+        .iter()
+        .filter_map(|(key, values)| Some((name(key), values.back()?)))
+        .collect();
+    metrics.sort_by(|x, y| x.0.cmp(&y.0));
+    for (name, value) in metrics {
+        let fields: Vec<_> = fields(value)
+            .into_iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
+        let _ = writeln!(text, "    {name} {}", fields.join(" "));
+    }
+}
+
+/// Name of a metric followed by its attributes, `latency{route=/login}`.
+fn name(key: &MetricSetKey) -> String {
+    if key.attributes.is_empty() {
+        return key.name.to_string();
+    }
+    let attributes: Vec<_> = key
+        .attributes
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect();
+    format!("{}{{{}}}", key.name, attributes.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, time::Duration};
+
+    use super::*;
+    use crate::{
+        data::RuntimeDataStore,
+        logical::{Execution, Executor, Scenario},
+        tracing::{
+            message::Message,
+            task_event::{
+                metrics::{MetricType, MetricValue},
+                Value,
+            },
+        },
+        user::User,
+        UserResult,
+    };
+
+    async fn user_builder(_: &RuntimeDataStore) -> impl User {
+        || async { UserResult::Ok(()) }
+    }
+
+    #[test]
+    fn render_started_executor() {
+        let execution = Execution::builder()
+            .with_user_builder(user_builder)
+            .with_executor(Executor::Once);
+        let mut app = App::new(&[Scenario::new("checkout", execution)]);
+        let time = DateTime::from_timestamp(0, 0).unwrap();
+        app.handle_message(Message::ExecutorStart {
+            id: 0,
+            start_time: time,
+            prior_executor_duration: Duration::ZERO,
+        });
+        app.handle_message(Message::TaskTime {
+            execution_id: 0,
+            scenario_id: 0,
+            duration: Duration::from_millis(5),
+        });
+        let key = MetricSetKey {
+            name: "requests",
+            metric_type: MetricType::Counter,
+            attributes: vec![("route", Value::String("/login".to_string()))],
+        };
+        let exec = &mut app.scenarios[0].execs[0];
+        exec.metrics
+            .insert(key, VecDeque::from([MetricValue::Counter(3)]));
+        exec.ended = true;
+        exec.start_time = None;
+        exec.prior_duration = Duration::from_secs(1);
+
+        assert_eq!(
+            render(&app, time),
+            "Snapshot at 1970-01-01T00:00:00Z\n\
+            \n\
+            Scenario checkout (running)\n\
+            \n  Executor Once\n\
+            \x20   users 0/0, iterations 1, errors 0, duration 1s, ended\n\
+            \x20   iteration time min 5ms, mean 5ms, max 5ms\n\
+            \x20   requests{route=/login} value=3\n"
+        );
+    }
+}
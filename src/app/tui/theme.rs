@@ -0,0 +1,108 @@
+use ratatui::style::{Color, Style, Stylize};
+
+/// Colors of the terminal UI, see [`TuiConfig::theme`](super::TuiConfig::theme).
+///
+/// The default theme is [`dark`](Self::dark), or [`monochrome`](Self::monochrome) when the
+/// `NO_COLOR` environment variable is set to a non empty value.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    /// Names in `name=value` pairs.
+    pub(super) label: Style,
+    /// Values of the executor summary.
+    pub(super) value: Style,
+    /// Selected executor, metric and the throughput sparkline.
+    pub(super) selected: Style,
+    /// Hints and secondary text.
+    pub(super) muted: Style,
+    pub(super) warning: Style,
+    pub(super) error: Style,
+    /// Keys in the help and the filter prompt.
+    pub(super) key: Style,
+    pub(super) gauge: Style,
+    pub(super) bar: Style,
+    /// Values written over histogram bars.
+    pub(super) bar_value: Style,
+    pub(super) banner: Style,
+    /// Lines of the zoomed chart of a metric.
+    pub(super) series: [Style; 4],
+}
+
+impl Theme {
+    /// Light text on a dark background.
+    pub fn dark() -> Self {
+        Self {
+            label: Style::new().green(),
+            value: Style::new().light_blue(),
+            selected: Style::new().light_green(),
+            muted: Style::new().dark_gray(),
+            warning: Style::new().yellow(),
+            error: Style::new().red(),
+            key: Style::new().cyan(),
+            gauge: Style::new().fg(Color::Green).bg(Color::Gray),
+            bar: Style::new().green(),
+            bar_value: Style::new().black(),
+            banner: Style::new().black().on_yellow(),
+            series: [
+                Style::new().green(),
+                Style::new().yellow(),
+                Style::new().magenta(),
+                Style::new().red(),
+            ],
+        }
+    }
+
+    /// Dark text on a light background, using only the 8 basic colors.
+    pub fn light() -> Self {
+        Self {
+            label: Style::new().green(),
+            value: Style::new().blue(),
+            selected: Style::new().blue(),
+            muted: Style::new().dark_gray(),
+            warning: Style::new().magenta(),
+            error: Style::new().red(),
+            key: Style::new().blue(),
+            gauge: Style::new().fg(Color::Blue).bg(Color::White),
+            bar: Style::new().blue(),
+            bar_value: Style::new().white(),
+            banner: Style::new().white().on_blue(),
+            series: [
+                Style::new().blue(),
+                Style::new().green(),
+                Style::new().magenta(),
+                Style::new().red(),
+            ],
+        }
+    }
+
+    /// No colors at all, emphasis is conveyed with bold, dim and reversed text.
+    pub fn monochrome() -> Self {
+        Self {
+            label: Style::new(),
+            value: Style::new(),
+            selected: Style::new().bold(),
+            muted: Style::new().dim(),
+            warning: Style::new().bold(),
+            error: Style::new().bold(),
+            key: Style::new().bold(),
+            gauge: Style::new(),
+            bar: Style::new(),
+            bar_value: Style::new().reversed(),
+            banner: Style::new().reversed(),
+            series: [Style::new(); 4],
+        }
+    }
+
+    /// [`monochrome`](Self::monochrome) when `NO_COLOR` is set, [`dark`](Self::dark) otherwise.
+    pub fn from_env() -> Self {
+        match std::env::var_os("NO_COLOR") {
+            Some(x) if !x.is_empty() => Self::monochrome(),
+            _ => Self::dark(),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
@@ -1,28 +1,29 @@
-use std::{collections::VecDeque, fmt::Debug, sync::Mutex, time::Duration};
+use std::{borrow::Cow, collections::VecDeque, fmt::Debug, sync::Mutex, time::Duration};
 
 use itertools::Itertools;
 use ordered_float::OrderedFloat;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Flex, Layout, Margin, Rect},
-    style::{Color, Style, Stylize},
+    style::Stylize,
     symbols,
     text::{Line, Span, Text},
     widgets::{
-        block::Title, Axis, Bar, BarChart, BarGroup, Block, Borders, Chart, Dataset, Gauge,
-        GraphType, Padding, Paragraph,
+        block::Title, Axis, Bar, BarChart, BarGroup, Block, Borders, Chart, Clear, Dataset, Gauge,
+        GraphType, Padding, Paragraph, Row, Sparkline, Table,
     },
     Frame,
 };
 
 use crate::{
-    app::{App, ExecutorState},
+    logical::Executor,
+    state::{App, ExecutorState, DEFAULT_HISTORY},
     tracing::task_event::{
-        metrics::{MetricType, MetricValue},
+        metrics::{format_bytes, ratio, MetricType, MetricValue, TrendValue, WINDOW},
         MetricSetKey,
     },
 };
 
-use super::TuiState;
+use super::{LogKind, LogPane, Theme, Throughput, TuiState, NOTICE_TIME};
 
 const LOGO: &str = "\
 ╔═══╗╔╗ ╔╗╔═══╗╔╗ ╔╗╔═══╗╔═══╗
@@ -34,6 +35,42 @@ const LOGO: &str = "\
 ";
 const INFO_CELL_SIZE: usize = 13;
 
+/// Keys listed by the help overlay, along with what they do.
+const KEYS: &[(&str, &str)] = &[
+    ("Left / Right", "browse the scenarios that ran so far"),
+    (
+        "Tab",
+        "show the next scenario, back to the first after the running one",
+    ),
+    ("Up / Down", "select the previous or next executor"),
+    ("PgUp / PgDn", "scroll the metrics by a page"),
+    ("k / j", "scroll the metrics by one"),
+    ("Home", "scroll back to the first metric"),
+    (
+        "e",
+        "cycle the error log between collapsed, expanded and hidden",
+    ),
+    (
+        "Click",
+        "select an executor, expand or collapse the error log",
+    ),
+    ("Wheel", "scroll the metrics"),
+    ("s", "write a snapshot of every executor to a text file"),
+    ("t", "show or hide a table of every executor of the run"),
+    ("o", "show or hide the logo"),
+    (
+        "1 / 2 / 3",
+        "show or hide the left panel, the metrics or the error log",
+    ),
+    ("?", "show or hide this help"),
+    (
+        "Esc",
+        "close this help, leave the zoomed metric or clear the filter",
+    ),
+    ("q", "stop gracefully, letting iterations in flight finish"),
+    ("Ctrl-C", "abort the run immediately"),
+];
+
 struct Size {
     height: u16,
     width: u16,
@@ -53,8 +90,30 @@ fn logo() -> (Size, fn(&mut Frame, Rect)) {
     )
 }
 
-fn scenario_text(name: &str) -> (Size, impl FnOnce(&mut Frame, Rect) + '_) {
-    let scenario_text = Line::from(vec!["Scenario - ".to_string().bold(), name.into()]);
+fn scenario_text<'a>(
+    name: &'a str,
+    index: usize,
+    count: usize,
+    ended: bool,
+    eta: Option<Duration>,
+    theme: &Theme,
+) -> (Size, impl FnOnce(&mut Frame, Rect) + 'a) {
+    let mut scenario_text = Line::from(vec!["Scenario - ".to_string().bold(), name.into()]);
+    if count > 1 {
+        scenario_text.push_span(Span::styled(
+            format!(" [{}/{count}]", index + 1),
+            theme.muted,
+        ));
+    }
+    if ended {
+        scenario_text.push_span(Span::styled(" (ended)", theme.warning));
+    }
+    if let Some(eta) = eta.filter(|x| !x.is_zero()) {
+        scenario_text.push_span(Span::styled(
+            format!(" - run ETA {}", duration_text(eta)),
+            theme.muted,
+        ));
+    }
     let width = scenario_text.width() as u16;
     let f = move |f: &mut Frame, rect: Rect| {
         f.render_widget(scenario_text, rect);
@@ -66,6 +125,7 @@ fn scenario_text(name: &str) -> (Size, impl FnOnce(&mut Frame, Rect) + '_) {
 fn executor_text<'a>(
     current_exec: usize,
     exec_names: impl Iterator<Item = String>,
+    theme: &Theme,
 ) -> (Size, impl FnOnce(&mut Frame, Rect) + 'a) {
     let mut executors_text = Text::from(Line::from("Executors: ".to_string().bold()));
     for (index, exec) in exec_names.enumerate() {
@@ -80,7 +140,7 @@ fn executor_text<'a>(
         ]);
 
         if index == current_exec {
-            line = line.light_green();
+            line = line.patch_style(theme.selected);
         }
 
         executors_text.push_line(line)
@@ -96,21 +156,25 @@ fn executor_text<'a>(
     (Size { height, width }, f)
 }
 
-fn progress_bar(current: &ExecutorState) -> (Size, impl FnOnce(&mut Frame, Rect)) {
+fn progress_bar(current: &ExecutorState, theme: &Theme) -> (Size, impl FnOnce(&mut Frame, Rect)) {
+    let eta = match current.eta() {
+        Some(eta) if !current.ended => format!(" - ETA {}", duration_text(eta)),
+        _ => String::new(),
+    };
     let progress = if let Some(total_duration) = current.total_duration {
         let duration = current.duration();
         Gauge::default()
-            .label(format!("{duration:.2?}/{total_duration:.2?}"))
+            .label(format!("{duration:.2?}/{total_duration:.2?}{eta}"))
             .ratio((duration.as_secs_f64() / total_duration.as_secs_f64()).min(1f64))
     } else if let Some(total_iteration) = current.total_iteration {
         let iteration = current.iterations;
         Gauge::default()
-            .label(format!("{iteration}/{total_iteration}"))
+            .label(format!("{iteration}/{total_iteration}{eta}"))
             .ratio((iteration as f64 / total_iteration as f64).min(1f64))
     } else {
         Gauge::default().label("?/???")
     }
-    .gauge_style(Style::default().fg(Color::Green).bg(Color::Gray));
+    .gauge_style(theme.gauge);
 
     let f = move |f: &mut Frame, rect: Rect| {
         f.render_widget(progress, rect);
@@ -125,7 +189,193 @@ fn progress_bar(current: &ExecutorState) -> (Size, impl FnOnce(&mut Frame, Rect)
     )
 }
 
-fn other_info(current: &ExecutorState) -> (Size, impl FnOnce(&mut Frame, Rect) + '_) {
+/// Target and length of every stage of a ramping executor. A constant arrival rate runs as a
+/// single stage of a ramping one.
+fn stages(config: &Executor) -> Vec<(String, Duration)> {
+    match config {
+        Executor::ConstantArrivalRate { rate, duration, .. } => {
+            vec![(format!("{rate}"), *duration)]
+        }
+        Executor::RampingUser { stages, .. } => stages
+            .iter()
+            .map(|(users, duration)| (format!("{users} users"), *duration))
+            .collect(),
+        Executor::RampingArrivalRate { stages, .. } => stages
+            .iter()
+            .map(|(rate, duration)| (format!("{rate}"), *duration))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Current stage of a ramping executor with the time left in it, above a timeline of every
+/// stage sized by their length. Nothing for other executors.
+fn stage_timeline(current: &ExecutorState, theme: &Theme) -> (Size, impl FnOnce(&mut Frame, Rect)) {
+    let stages = stages(&current.config);
+    // Stages are numbered from 1 in updates.
+    let index = current
+        .stage
+        .filter(|_| current.started() && !stages.is_empty())
+        .map(|x| x.saturating_sub(1).min(stages.len().saturating_sub(1)));
+    let start: Duration = stages.iter().take(index.unwrap_or(0)).map(|x| x.1).sum();
+    let total: Duration = stages.iter().map(|x| x.1).sum();
+    let elapsed = current.duration();
+
+    let mut title = Line::from("Stage".bold());
+    match index.filter(|_| !current.ended) {
+        Some(index) => {
+            let (target, duration) = &stages[index];
+            let left = (start + *duration).saturating_sub(elapsed);
+            title.push_span(Span::raw(format!(" {}/{} ", index + 1, stages.len())));
+            title.push_span(Span::styled(target.clone(), theme.value));
+            title.push_span(Span::styled(
+                format!(", {} left", duration_text(left)),
+                theme.muted,
+            ));
+        }
+        None => title.push_span(Span::styled(
+            format!(" {} stages", stages.len()),
+            theme.muted,
+        )),
+    }
+    let next = index.map_or(0, |x| x + 1);
+    let upcoming = stages[next.min(stages.len())..]
+        .iter()
+        .take(3)
+        .map(|(target, duration)| format!("{target} for {}", duration_text(*duration)))
+        .join(", ");
+    let upcoming = match upcoming.is_empty() || current.ended {
+        true => Line::default(),
+        false => Line::styled(format!("next: {upcoming}"), theme.muted),
+    };
+
+    let height = match stages.is_empty() {
+        true => 0,
+        false => 3,
+    };
+    let width = title.width().max(upcoming.width()) as u16;
+    let selected = theme.selected;
+    let muted = theme.muted;
+    let f = move |f: &mut Frame, rect: Rect| {
+        let [title_area, timeline_area, upcoming_area] =
+            Layout::vertical([Constraint::Length(1); 3]).areas(rect);
+        f.render_widget(title, title_area);
+        f.render_widget(upcoming, upcoming_area);
+
+        // One cell per slice of the total length, the elapsed part of the current stage bold.
+        let width = timeline_area.width as usize;
+        let mut timeline = Line::default();
+        let mut stage_end = Duration::ZERO;
+        let mut cells = 0;
+        for (i, (_, duration)) in stages.iter().enumerate() {
+            stage_end += *duration;
+            let end = match i + 1 == stages.len() {
+                true => width,
+                false => (stage_end.as_secs_f64() / total.as_secs_f64().max(1e-9) * width as f64)
+                    .round() as usize,
+            };
+            let end = end.max(cells + 1).min(width);
+            for cell in cells..end {
+                let time = total.mul_f64((cell as f64 + 0.5) / width as f64);
+                let symbol = match cell == cells {
+                    true => "┃",
+                    false => "━",
+                };
+                let style = match (Some(i) == index, time <= elapsed) {
+                    (true, true) => selected.bold(),
+                    (true, false) => selected,
+                    (false, true) => selected.dim(),
+                    (false, false) => muted,
+                };
+                timeline.push_span(Span::styled(symbol, style));
+            }
+            cells = end;
+        }
+        f.render_widget(timeline, timeline_area);
+    };
+
+    (Size { height, width }, f)
+}
+
+/// Active users over the run time of the executor, against its most users.
+fn users_chart<'a>(
+    current: &'a ExecutorState,
+    theme: &Theme,
+) -> (Size, impl FnOnce(&mut Frame, Rect) + 'a) {
+    let mut title = Line::from("Users".bold());
+    if let Some(now) = current.users_history.back() {
+        title.push_span(Span::styled(
+            format!(" now {now}, max {}", current.max_users),
+            theme.muted,
+        ));
+    }
+    let width = title.width() as u16;
+    let style = theme.selected;
+    let f = move |f: &mut Frame, rect: Rect| {
+        let [title_area, chart_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(rect);
+        f.render_widget(title, title_area);
+        let users = &current.users_history;
+        let seconds = sample_seconds(&current.sample_times, users.len());
+        let points = seconds
+            .iter()
+            .zip(users)
+            .map(|(&x, &y)| (x, y as f64))
+            .collect_vec();
+        let max = users.iter().copied().max().unwrap_or_default();
+        let max = max.max(current.max_users).max(1) as f64;
+        let dataset = Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(style)
+            .data(&points);
+        let chart = Chart::new(vec![dataset])
+            .x_axis(time_axis(&seconds))
+            .y_axis(
+                Axis::default()
+                    .bounds([0., max])
+                    .labels(vec!["0".into(), format!("{max}").into()]),
+            );
+        f.render_widget(chart, chart_area);
+    };
+
+    (Size { height: 7, width }, f)
+}
+
+/// Iterations per second of the last samples that fit, newest on the right.
+fn throughput<'a>(
+    samples: &'a VecDeque<u64>,
+    theme: &Theme,
+) -> (Size, impl FnOnce(&mut Frame, Rect) + 'a) {
+    let mut title = Line::from("Iterations/sec".bold());
+    if let (Some(now), Some(peak)) = (samples.back(), samples.iter().max()) {
+        title.push_span(Span::styled(
+            format!(" now {now}, peak {peak}"),
+            theme.muted,
+        ));
+    }
+    let width = title.width() as u16;
+    let style = theme.selected;
+    let f = move |f: &mut Frame, rect: Rect| {
+        let [title_area, sparkline_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(rect);
+        f.render_widget(title, title_area);
+        let skip = samples.len().saturating_sub(sparkline_area.width as usize);
+        let data = samples.iter().skip(skip).copied().collect_vec();
+        f.render_widget(
+            Sparkline::default().data(&data).style(style),
+            sparkline_area,
+        );
+    };
+
+    (Size { height: 3, width }, f)
+}
+
+fn other_info<'a>(
+    current: &'a ExecutorState,
+    throughput: Option<&Throughput>,
+    theme: &Theme,
+) -> (Size, impl FnOnce(&mut Frame, Rect) + 'a) {
     let average_time = current
         .task_total_time
         .checked_div(current.iterations as u32)
@@ -137,58 +387,109 @@ fn other_info(current: &ExecutorState) -> (Size, impl FnOnce(&mut Frame, Rect) +
     let max_time_formatted = format!("{:.2?}", current.task_max_time);
     let min_time_formatted = format!("{:.2?}", current.task_min_time);
     let total_iterations_completed_formatted = current.iterations.to_string();
-    let iteration_per_sec_formatted = format!(
-        "{:.2} iter/sec",
-        current.iterations as f64 / current.duration().as_secs_f64()
-    );
-
-    let stages_formatted = current.stages.map(|x| x.to_string());
-    let stage_formatted = current.stage.map(|x| x.to_string());
-    let stage_duration_formatted = current
-        .stage_duration
-        .map(|duration| format!("{:.2?}", duration));
+    // Over the last second once sampled, spikes are lost in the average over the whole run.
+    let iteration_per_sec = match throughput {
+        Some(throughput) if !throughput.samples.is_empty() => throughput.rate,
+        _ => current.iterations as f64 / current.duration().as_secs_f64().max(1e-9),
+    };
+    let iteration_per_sec_formatted = format!("{iteration_per_sec:.2} iter/sec");
 
     let mut info_render = Vec::default();
 
-    if let Some(stages) = stages_formatted {
-        let line = if let Some((stage, duration)) = stage_formatted.zip(stage_duration_formatted) {
-            Line::from_iter(
-                value_span(stage)
-                    .into_iter()
-                    .chain(key_value_span("total", stages))
-                    .chain(key_value_span("duration", duration)),
-            )
-        } else {
-            Line::from_iter(key_value_span("total", stages))
-        };
-        info_render.push(("current_stage", line))
-    }
-
     info_render.extend([
-        ("users", Line::from_iter(value_span(total_users_formatted))),
+        (
+            "users",
+            Line::from_iter(value_span(theme, total_users_formatted)),
+        ),
         (
             "max_users",
-            Line::from_iter(value_span(total_max_users_formatted)),
+            Line::from_iter(value_span(theme, total_max_users_formatted)),
         ),
         (
             "iteration_time",
             Line::from_iter(
-                key_value_span("avg", average_time_formatted)
+                key_value_span(theme, "avg", average_time_formatted)
                     .into_iter()
-                    .chain(key_value_span("max", max_time_formatted))
-                    .chain(key_value_span("min", min_time_formatted)),
+                    .chain(key_value_span(theme, "max", max_time_formatted))
+                    .chain(key_value_span(theme, "min", min_time_formatted)),
             ),
         ),
         (
             "iterations",
             Line::from_iter(
-                key_value_span("total", total_iterations_completed_formatted)
+                key_value_span(theme, "total", total_iterations_completed_formatted)
                     .into_iter()
-                    .chain(value_span(iteration_per_sec_formatted)),
+                    .chain(value_span(theme, iteration_per_sec_formatted)),
             ),
         ),
     ]);
 
+    if let Some(throughput) = throughput.filter(|x| !x.byte_rates.is_empty()) {
+        info_render.push((
+            "bytes_per_sec",
+            Line::from_iter(throughput.byte_rates.iter().flat_map(|(name, rate)| {
+                key_value_span(theme, *name, format!("{}/s", format_bytes(*rate)))
+            })),
+        ));
+    }
+
+    let (ok, failed) = current.iteration_results();
+    if ok + failed > 0 {
+        let error_rate = current.error_rate() * 100.;
+        let style = match failed {
+            0 => theme.value,
+            _ => theme.error,
+        };
+        info_render.push((
+            "errors",
+            Line::from_iter(
+                value_span(theme, failed.to_string())
+                    .into_iter()
+                    .map(|x| x.patch_style(style))
+                    .chain(key_value_span(theme, "rate", format!("{error_rate:.2}%")))
+                    .chain(key_value_span(
+                        theme,
+                        "success",
+                        format!("{:.2}%", 100. - error_rate),
+                    )),
+            ),
+        ));
+    }
+
+    let checks = current.checks();
+    if !checks.is_empty() {
+        let passes: u64 = checks.iter().map(|x| x.passes).sum();
+        let total: u64 = checks.iter().map(|x| x.passes + x.fails).sum();
+        info_render.push((
+            "checks",
+            Line::from_iter(
+                value_span(
+                    theme,
+                    format!("{:.2}%", passes as f64 * 100. / total as f64),
+                )
+                .into_iter()
+                .chain(key_value_span(theme, "passed", passes.to_string()))
+                .chain(key_value_span(
+                    theme,
+                    "failed",
+                    (total - passes).to_string(),
+                )),
+            ),
+        ));
+    }
+
+    let status_classes = current.status_classes();
+    if !status_classes.is_empty() {
+        info_render.push((
+            "http_status",
+            Line::from_iter(
+                status_classes
+                    .into_iter()
+                    .flat_map(|(class, count)| key_value_span(theme, class, count.to_string())),
+            ),
+        ));
+    }
+
     let key_size = info_render.iter().map(|(k, _)| k.len()).max().unwrap() + 2;
 
     let mut paragraph = Text::default();
@@ -215,7 +516,14 @@ fn other_info(current: &ExecutorState) -> (Size, impl FnOnce(&mut Frame, Rect) +
     (size, f)
 }
 
-fn render_gauge(key: &MetricSetKey, value: &VecDeque<MetricValue>, f: &mut Frame, area: Rect) {
+fn render_gauge(
+    key: &MetricSetKey,
+    value: &[MetricValue],
+    seconds: &[f64],
+    theme: &Theme,
+    f: &mut Frame,
+    area: Rect,
+) {
     let Some(min_value) = value.iter().reduce(|x, y| x.min_gauge(y)) else {
         return;
     };
@@ -226,8 +534,8 @@ fn render_gauge(key: &MetricSetKey, value: &VecDeque<MetricValue>, f: &mut Frame
 
     let data_points: Vec<(f64, f64)> = value
         .iter()
-        .enumerate()
-        .map(|(x, &y)| {
+        .zip(seconds)
+        .map(|(&y, &x)| {
             let y = match y {
                 MetricValue::GaugeF64(x) => x,
                 MetricValue::GaugeI64(x) => x as f64,
@@ -236,7 +544,7 @@ fn render_gauge(key: &MetricSetKey, value: &VecDeque<MetricValue>, f: &mut Frame
                 _ => 0.,
             };
 
-            (x as f64, y)
+            (x, y)
         })
         .collect();
 
@@ -246,10 +554,7 @@ fn render_gauge(key: &MetricSetKey, value: &VecDeque<MetricValue>, f: &mut Frame
         .graph_type(GraphType::Line)
         .data(&data_points);
 
-    // Create the X axis and define its properties
-    let x_axis = Axis::default()
-        .bounds([0.0, data_points.len() as f64])
-        .labels(vec!["0".into(), data_points.len().to_string().into()]);
+    let x_axis = time_axis(seconds);
 
     // Create the Y axis and define its properties
     let min = data_points
@@ -287,7 +592,7 @@ fn render_gauge(key: &MetricSetKey, value: &VecDeque<MetricValue>, f: &mut Frame
     let chart = Chart::new(vec![data])
         .block(
             Block::new()
-                .title(title(key))
+                .title(title(key, theme))
                 .title_alignment(Alignment::Right),
         )
         .x_axis(x_axis)
@@ -299,6 +604,8 @@ fn render_gauge(key: &MetricSetKey, value: &VecDeque<MetricValue>, f: &mut Frame
 fn render_histogram<'a>(
     key: &MetricSetKey,
     value: impl Iterator<Item = &'a MetricValue>,
+    window: Option<&MetricValue>,
+    theme: &Theme,
     f: &mut Frame,
     area: Rect,
 ) {
@@ -317,33 +624,68 @@ fn render_histogram<'a>(
             .label(name.into())
     }
 
-    let (bars, sum): (BarGroup, &dyn Debug) = match value {
-        MetricValue::Histogram(((p50, p90, p95, p99), sum)) => (
+    let (bars, summary): (BarGroup, Vec<(&str, &dyn Debug)>) = match value {
+        MetricValue::Histogram(((p50, p90, p95, p99), sum, (min, max))) => (
             BarGroup::default().bars(&[
                 bar("p50", p50, p99, norm_f64),
                 bar("p90", p90, p99, norm_f64),
                 bar("p95", p95, p99, norm_f64),
                 bar("p99", p99, p99, norm_f64),
             ]),
-            sum,
+            vec![("sum", sum), ("min", min), ("max", max)],
         ),
-        MetricValue::DurationHistogram(((p50, p90, p95, p99), sum)) => (
+        MetricValue::DurationHistogram(((p50, p90, p95, p99), sum, (min, max))) => (
             BarGroup::default().bars(&[
                 bar("p50", p50, p99, norm_duration),
                 bar("p90", p90, p99, norm_duration),
                 bar("p95", p95, p99, norm_duration),
                 bar("p99", p99, p99, norm_duration),
             ]),
-            sum,
+            vec![("sum", sum), ("min", min), ("max", max)],
         ),
+        MetricValue::Trend(trend) => {
+            let (p50, p90, p95, p99) = &trend.percentiles;
+            (
+                BarGroup::default().bars(&[
+                    bar("p50", p50, p99, norm_f64),
+                    bar("p90", p90, p99, norm_f64),
+                    bar("p95", p95, p99, norm_f64),
+                    bar("p99", p99, p99, norm_f64),
+                ]),
+                trend_summary(trend),
+            )
+        }
+        MetricValue::DurationTrend(trend) => {
+            let (p50, p90, p95, p99) = &trend.percentiles;
+            (
+                BarGroup::default().bars(&[
+                    bar("p50", p50, p99, norm_duration),
+                    bar("p90", p90, p99, norm_duration),
+                    bar("p95", p95, p99, norm_duration),
+                    bar("p99", p99, p99, norm_duration),
+                ]),
+                trend_summary(trend),
+            )
+        }
         _ => unreachable!(),
     };
 
-    let mut title = title(key);
-    title
-        .content
-        .spans
-        .extend([Span::raw("sum=").green(), Span::raw(format!("{:.2?}", sum))]);
+    let mut title = title(key, theme);
+    for (name, value) in summary {
+        title.content.spans.extend([
+            Span::styled(format!("{name}="), theme.label),
+            Span::raw(format!("{:.2?} ", value)),
+        ]);
+    }
+    if let Some((p50, p99)) = window.and_then(window_percentiles) {
+        title.content.spans.extend([
+            Span::styled(format!("last {:?} ", WINDOW), theme.label),
+            Span::styled("p50=", theme.label),
+            Span::raw(format!("{p50} ")),
+            Span::styled("p99=", theme.label),
+            Span::raw(p99),
+        ]);
+    }
 
     let barchart = BarChart::default()
         .block(
@@ -355,17 +697,50 @@ fn render_histogram<'a>(
         .direction(Direction::Horizontal)
         .bar_width(1)
         .bar_gap(0)
-        .bar_style(Style::new().green())
-        .value_style(Style::new().black())
+        .bar_style(theme.bar)
+        .value_style(theme.bar_value)
         .data(bars)
         .max(100);
 
     f.render_widget(barchart, area)
 }
 
+fn trend_summary<T: Debug>(trend: &TrendValue<T>) -> Vec<(&'static str, &dyn Debug)> {
+    vec![
+        ("count", &trend.count),
+        ("mean", &trend.mean),
+        ("stddev", &trend.stddev),
+        ("min", &trend.min),
+        ("max", &trend.max),
+    ]
+}
+
+/// p50 and p99 of a windowed histogram or trend value.
+fn window_percentiles(value: &MetricValue) -> Option<(String, String)> {
+    match value {
+        MetricValue::Histogram(((p50, _, _, p99), ..)) => {
+            Some((format!("{:.2?}", p50), format!("{:.2?}", p99)))
+        }
+        MetricValue::DurationHistogram(((p50, _, _, p99), ..)) => {
+            Some((format!("{:.2?}", p50), format!("{:.2?}", p99)))
+        }
+        MetricValue::Trend(x) => Some((
+            format!("{:.2?}", x.percentiles.0),
+            format!("{:.2?}", x.percentiles.3),
+        )),
+        MetricValue::DurationTrend(x) => Some((
+            format!("{:.2?}", x.percentiles.0),
+            format!("{:.2?}", x.percentiles.3),
+        )),
+        _ => None,
+    }
+}
+
 fn render_counter<'a>(
     key: &MetricSetKey,
     values: impl Iterator<Item = &'a MetricValue>,
+    window: Option<&MetricValue>,
+    theme: &Theme,
     f: &mut Frame,
     rect: Rect,
 ) {
@@ -374,33 +749,208 @@ fn render_counter<'a>(
         unreachable!()
     };
 
-    let mut line = title(key).content;
+    let mut line = title(key, theme).content;
     line.spans
         .extend([Span::raw(" - "), Span::raw(value.to_string())]);
+    if let Some(MetricValue::Counter(window)) = window {
+        line.spans.extend([
+            Span::styled(format!(" last {:?}=", WINDOW), theme.label),
+            Span::raw(format!("+{window}")),
+        ]);
+    }
+    line.alignment = Some(Alignment::Left);
+    f.render_widget(line, rect);
+}
+
+fn render_rate<'a>(
+    key: &MetricSetKey,
+    values: impl Iterator<Item = &'a MetricValue>,
+    theme: &Theme,
+    f: &mut Frame,
+    rect: Rect,
+) {
+    let value = values.last().unwrap();
+    let MetricValue::Rate((total, window)) = value else {
+        unreachable!()
+    };
+
+    let mut line = title(key, theme).content;
+    line.spans.extend([
+        Span::raw(" - "),
+        Span::raw(format!("{:.2}/s ", total)),
+        Span::styled(format!("last {:?}=", WINDOW), theme.label),
+        Span::raw(format!("{:.2}/s", window)),
+    ]);
+    line.alignment = Some(Alignment::Left);
+    f.render_widget(line, rect);
+}
+
+fn render_bytes<'a>(
+    key: &MetricSetKey,
+    values: impl Iterator<Item = &'a MetricValue>,
+    window: Option<&MetricValue>,
+    theme: &Theme,
+    f: &mut Frame,
+    rect: Rect,
+) {
+    let value = values.last().unwrap();
+    let MetricValue::Bytes((total, (rate, window_rate))) = value else {
+        unreachable!()
+    };
+
+    let mut line = title(key, theme).content;
+    line.spans.extend([
+        Span::raw(" - "),
+        Span::raw(format!("{} ", format_bytes(*total as f64))),
+        Span::raw(format!("{}/s ", format_bytes(*rate))),
+        Span::styled(format!("last {:?}=", WINDOW), theme.label),
+    ]);
+    if let Some(MetricValue::Bytes((window, _))) = window {
+        line.spans
+            .push(Span::raw(format!("+{} ", format_bytes(*window as f64))));
+    }
+    line.spans
+        .push(Span::raw(format!("{}/s", format_bytes(*window_rate))));
+    line.alignment = Some(Alignment::Left);
+    f.render_widget(line, rect);
+}
+
+fn render_ratio<'a>(
+    key: &MetricSetKey,
+    values: impl Iterator<Item = &'a MetricValue>,
+    window: Option<&MetricValue>,
+    theme: &Theme,
+    f: &mut Frame,
+    rect: Rect,
+) {
+    let value = values.last().unwrap();
+    let MetricValue::Ratio((passed, total)) = value else {
+        unreachable!()
+    };
+
+    let mut line = title(key, theme).content;
+    line.spans.extend([
+        Span::raw(" - "),
+        Span::raw(format!(
+            "{:.2}% ({passed}/{total})",
+            ratio(*passed, *total) * 100.
+        )),
+    ]);
+    if let Some(MetricValue::Ratio((passed, total))) = window {
+        line.spans.extend([
+            Span::styled(format!(" last {:?}=", WINDOW), theme.label),
+            Span::raw(format!("{:.2}%", ratio(*passed, *total) * 100.)),
+        ]);
+    }
     line.alignment = Some(Alignment::Left);
     f.render_widget(line, rect);
 }
 
-fn render_metrics(metrics: &[(&MetricSetKey, &VecDeque<MetricValue>)], rect: Rect, f: &mut Frame) {
-    let layout = Layout::vertical(metrics.iter().map(|(key, _)| match key.metric_type {
-        MetricType::Counter => Constraint::Length(2),
-        MetricType::Gauge => Constraint::Length(10),
-        MetricType::Histogram => Constraint::Length(7),
-    }))
+fn metric_height(metric_type: MetricType) -> u16 {
+    match metric_type {
+        MetricType::Counter => 2,
+        MetricType::Gauge => 10,
+        MetricType::Histogram => 7,
+        MetricType::Rate => 2,
+        MetricType::Trend => 7,
+        MetricType::Bytes => 2,
+        MetricType::Ratio => 2,
+    }
+}
+
+/// Render the metrics that fit in `rect` starting from the one at `offset`, followed by a line
+/// counting hidden metrics if any. Returns the number of metrics rendered.
+fn render_metrics(
+    metrics: &[(&MetricSetKey, &VecDeque<MetricValue>, Option<&MetricValue>)],
+    times: &VecDeque<Duration>,
+    offset: usize,
+    theme: &Theme,
+    rect: Rect,
+    f: &mut Frame,
+) -> usize {
+    let fits = |rect: Rect| {
+        let mut height = 0;
+        metrics[offset..]
+            .iter()
+            .take_while(|(key, ..)| {
+                // Metrics are separated by a blank line.
+                height += metric_height(key.metric_type) + 1;
+                height <= rect.height + 1
+            })
+            .count()
+    };
+    let total = metrics.len();
+    let mut shown = fits(rect);
+    let mut area = rect;
+    let hidden = offset > 0 || offset + shown < total;
+    if hidden {
+        // Leave the last line for the indicator, after a blank one.
+        area.height = area.height.saturating_sub(2);
+        shown = fits(area).max(1).min(total - offset);
+    }
+    let metrics = &metrics[offset..offset + shown];
+
+    let layout = Layout::vertical(
+        metrics
+            .iter()
+            .map(|(key, ..)| Constraint::Length(metric_height(key.metric_type))),
+    )
     .spacing(1)
-    .split(rect);
+    .split(area);
 
-    for (metric, &rect) in metrics.iter().zip(layout.iter()) {
+    for (index, (metric, &rect)) in metrics.iter().zip(layout.iter()).enumerate() {
         let rect = rect.inner(&Margin {
             horizontal: 2,
             vertical: 0,
         });
+        if index == 0 {
+            // Marks the metric zoomed into with Enter.
+            let marker = Rect {
+                x: rect.x.saturating_sub(2),
+                width: 1,
+                height: rect.height.min(1),
+                ..rect
+            };
+            f.render_widget(Span::styled("▸", theme.selected), marker);
+        }
         match metric.0.metric_type {
-            MetricType::Gauge => render_gauge(metric.0, metric.1, f, rect),
-            MetricType::Histogram => render_histogram(metric.0, metric.1.iter(), f, rect),
-            MetricType::Counter => render_counter(metric.0, metric.1.iter(), f, rect),
+            MetricType::Gauge => {
+                let start = metric.1.len().saturating_sub(DEFAULT_HISTORY);
+                let recent = metric.1.range(start..).copied().collect_vec();
+                let seconds = sample_seconds(times, recent.len());
+                render_gauge(metric.0, &recent, &seconds, theme, f, rect)
+            }
+            MetricType::Histogram | MetricType::Trend => {
+                render_histogram(metric.0, metric.1.iter(), metric.2, theme, f, rect)
+            }
+            MetricType::Counter => {
+                render_counter(metric.0, metric.1.iter(), metric.2, theme, f, rect)
+            }
+            MetricType::Rate => render_rate(metric.0, metric.1.iter(), theme, f, rect),
+            MetricType::Bytes => render_bytes(metric.0, metric.1.iter(), metric.2, theme, f, rect),
+            MetricType::Ratio => render_ratio(metric.0, metric.1.iter(), metric.2, theme, f, rect),
         }
     }
+
+    if hidden {
+        let below = total - offset - shown;
+        let indicator = Line::from(vec![
+            Span::raw(format!(
+                "metrics {}-{} of {total}, {offset} above, {below} below",
+                offset + 1,
+                offset + shown,
+            )),
+            Span::styled(" (PgUp/PgDn or j/k to scroll)", theme.muted),
+        ])
+        .alignment(Alignment::Right);
+        let indicator_area = Rect {
+            y: rect.y + rect.height.saturating_sub(1),
+            height: 1,
+            ..rect
+        };
+        f.render_widget(indicator, indicator_area);
+    }
+    shown
 }
 
 fn margin(rect: Rect, h: u16, v: u16) -> Rect {
@@ -410,12 +960,12 @@ fn margin(rect: Rect, h: u16, v: u16) -> Rect {
     })
 }
 
-fn title(key: &MetricSetKey) -> Title {
+fn title<'a>(key: &'a MetricSetKey, theme: &Theme) -> Title<'a> {
     let mut title: Title = Title::from(format!("{}_{} ", key.name, key.metric_type.to_string()));
     for attr in &key.attributes {
         title.content.spans.extend([
-            Span::raw(attr.0).green(),
-            Span::raw("=").green(),
+            Span::styled(attr.0, theme.label),
+            Span::styled("=", theme.label),
             Span::raw(attr.1.to_string()),
         ]);
         title.content.push_span(Span::raw(" "));
@@ -423,115 +973,650 @@ fn title(key: &MetricSetKey) -> Title {
     title
 }
 
-pub(super) fn ui(f: &mut Frame, app: &Mutex<App>, state: &TuiState) {
+pub(super) fn ui(f: &mut Frame, app: &Mutex<App>, state: &mut TuiState) {
     let area = f.size();
     let app = app.lock().unwrap();
+    let theme = state.theme;
+    let theme = &theme;
+
+    let log_height = match state.log_pane {
+        LogPane::Hidden => 0,
+        // Nothing else left to show.
+        _ if state.hide_left && state.hide_metrics => area.height,
+        LogPane::Collapsed => 6,
+        LogPane::Expanded => area.height / 2,
+    };
+    let [area, log_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(log_height)]).areas(area);
+    state.log_area = log_area;
+    if state.log_pane != LogPane::Hidden {
+        render_log(state, theme, log_area, f);
+    }
+    // Nothing to click or scroll unless drawn below.
+    state.executors_area = Rect::default();
+    state.metrics_area = Rect::default();
 
     let (logo_size, logo_render) = logo();
-    let (scenario_size, scenario_render) = scenario_text(&app.current_scenario().name);
-    let (executor_size, executor_render) = executor_text(
-        state.current_exec_selected,
-        app.current_scenario().exec_names(),
+    let scenario_id = state.scenario(&app);
+    let scenario = &app.scenarios()[scenario_id];
+    let (scenario_size, scenario_render) = scenario_text(
+        &scenario.name,
+        scenario_id,
+        app.scenarios().len(),
+        state.viewed_scenario.is_some(),
+        app.eta(),
+        theme,
     );
+    let (executor_size, executor_render) =
+        executor_text(state.current_exec_selected, scenario.exec_names(), theme);
+    let (stage_size, stage_render) =
+        stage_timeline(&scenario.execs[state.current_exec_selected], theme);
     let (progress_size, progress_render) =
-        progress_bar(&app.current_scenario().execs[state.current_exec_selected]);
-    let (info_size, info_render) =
-        other_info(&app.current_scenario().execs[state.current_exec_selected]);
+        progress_bar(&scenario.execs[state.current_exec_selected], theme);
+    let (info_size, info_render) = other_info(
+        &scenario.execs[state.current_exec_selected],
+        state
+            .throughput
+            .get(&(scenario_id, state.current_exec_selected)),
+        theme,
+    );
+    // Copied so that the state can be changed while rendering.
+    let samples = state
+        .throughput
+        .get(&(scenario_id, state.current_exec_selected))
+        .map(|x| x.samples.clone())
+        .unwrap_or_default();
+    let (throughput_size, throughput_render) = throughput(&samples, theme);
+    let (users_size, users_render) =
+        users_chart(&scenario.execs[state.current_exec_selected], theme);
 
     let left_width = logo_size
         .width
         .max(scenario_size.width)
         .max(executor_size.width)
         .max(progress_size.width)
+        .max(stage_size.width)
+        .max(throughput_size.width)
+        .max(users_size.width)
         .max(info_size.width)
         + 4;
 
     // No margins here. Margins are applied by children of the main area
-    let [left_area, metric_area] =
-        Layout::horizontal([Constraint::Length(left_width), Constraint::Min(0)]).areas(area);
+    let constraints = match (state.hide_left, state.hide_metrics) {
+        (true, _) => [Constraint::Length(0), Constraint::Min(0)],
+        (false, true) => [Constraint::Min(0), Constraint::Length(0)],
+        (false, false) => [Constraint::Length(left_width), Constraint::Min(0)],
+    };
+    let [left_area, metric_area] = Layout::horizontal(constraints).areas(area);
 
     // Draw borders
-    f.render_widget(Block::bordered().borders(Borders::RIGHT), left_area);
+    if !state.hide_left && !state.hide_metrics {
+        f.render_widget(Block::bordered().borders(Borders::RIGHT), left_area);
+    }
 
+    let logo_height = match state.hide_logo {
+        true => 0,
+        // Logo followed by a border.
+        false => logo_size.height + 1,
+    };
+    // Only ramping executors have stages.
+    let stage_gap = u16::from(stage_size.height > 0);
     let left_height = 1
-        + logo_size.height
-        + 1
+        + logo_height
         + scenario_size.height
         + executor_size.height
         + 1
         + progress_size.height
+        + stage_gap
+        + stage_size.height
+        + 1
+        + throughput_size.height
+        + 1
+        + users_size.height
         + 1
         + info_size.height
         + 1;
 
-    if left_height > left_area.height {
+    if state.hide_left {
+        // Collapsed, the metrics take the whole width.
+    } else if left_height > left_area.height {
         // cant render the whole thing
         f.render_widget(
-            Text::raw("Too Small").red().bold().centered(),
+            Text::styled("Too Small", theme.error.bold()).centered(),
             Layout::vertical([Constraint::Length(1)])
                 .flex(Flex::Center)
                 .split(left_area)[0],
         )
     } else {
         // Left Area
-        let [logo_area, scenario_area, executors_area, _, progress_area, _, info_area] =
+        let [logo_area, scenario_area, executors_area, _, progress_area, _, stage_area, _, throughput_area, _, users_area, _, info_area] =
             Layout::vertical([
-                Constraint::Length(logo_size.height + 1),
+                Constraint::Length(logo_height),
                 Constraint::Length(scenario_size.height),
                 Constraint::Length(executor_size.height),
                 Constraint::Length(1),
                 Constraint::Length(progress_size.height),
+                Constraint::Length(stage_gap),
+                Constraint::Length(stage_size.height),
+                Constraint::Length(1),
+                Constraint::Length(throughput_size.height),
+                Constraint::Length(1),
+                Constraint::Length(users_size.height),
                 Constraint::Length(1),
                 Constraint::Min(0),
             ])
             .vertical_margin(1)
             .areas(left_area);
 
-        f.render_widget(Block::bordered().borders(Borders::BOTTOM), logo_area);
-        f.render_widget(
-            Span::raw("┤"),
-            Rect {
-                x: logo_area.width - 1,
-                y: logo_area.height,
-                width: 1,
-                height: 1,
-            },
-        );
-
-        logo_render(f, margin(logo_area, 2, 0));
+        if !state.hide_logo {
+            f.render_widget(Block::bordered().borders(Borders::BOTTOM), logo_area);
+            if !state.hide_metrics {
+                f.render_widget(
+                    Span::raw("┤"),
+                    Rect {
+                        x: logo_area.width - 1,
+                        y: logo_area.height,
+                        width: 1,
+                        height: 1,
+                    },
+                );
+            }
+            logo_render(f, margin(logo_area, 2, 0));
+        }
         scenario_render(f, margin(scenario_area, 2, 0));
         progress_render(f, margin(progress_area, 2, 0));
-        executor_render(f, margin(executors_area, 2, 0));
+        stage_render(f, margin(stage_area, 2, 0));
+        throughput_render(f, margin(throughput_area, 2, 0));
+        users_render(f, margin(users_area, 2, 0));
+        state.executors_area = margin(executors_area, 2, 0);
+        executor_render(f, state.executors_area);
         info_render(f, margin(info_area, 2, 0));
+    }
 
+    if !state.hide_metrics {
         let metric_area = margin(metric_area, 1, 1);
-        let metrics = app.current_scenario().execs[state.current_exec_selected]
+        state.metrics_area = metric_area;
+        let exec = &scenario.execs[state.current_exec_selected];
+        let metrics = exec
             .metrics
             .iter()
+            .filter(|(key, _)| matches(key, &state.filter))
             .sorted_by_key(|(x, _)| x.name)
+            .map(|(key, values)| (key, values, exec.window_metrics().get(key)))
             .collect_vec();
-        render_metrics(&metrics, metric_area, f)
+        state.metrics_offset = state.metrics_offset.min(metrics.len().saturating_sub(1));
+        state.selected_metric = metrics.get(state.metrics_offset).map(|x| x.0.clone());
+        state.metrics_shown = render_metrics(
+            &metrics,
+            &exec.sample_times,
+            state.metrics_offset,
+            theme,
+            metric_area,
+            f,
+        );
+        if metrics.is_empty() && !exec.metrics.is_empty() {
+            f.render_widget(
+                Text::styled("No metrics match the filter", theme.muted),
+                margin(metric_area, 2, 0),
+            );
+        }
+    }
+
+    let hint_area = Rect {
+        y: area.bottom().saturating_sub(1),
+        height: area.height.min(1),
+        ..margin(metric_area, 1, 0)
+    };
+    let hint = match &state.notice {
+        Some((time, notice)) if time.elapsed() < NOTICE_TIME => notice.as_str(),
+        _ => "? for help",
+    };
+    f.render_widget(Text::styled(hint, theme.muted).right_aligned(), hint_area);
+    if state.editing_filter || !state.filter.is_empty() {
+        let mut filter = Line::from(vec![
+            Span::styled("/", theme.key.bold()),
+            Span::raw(state.filter.as_str()),
+        ]);
+        match state.editing_filter {
+            true => filter.push_span(Span::raw("_").slow_blink()),
+            false => filter.push_span(Span::styled("  (/ to edit, Esc to clear)", theme.muted)),
+        }
+        f.render_widget(filter, hint_area);
+    }
+    let banner = match (state.pause.is_stopped(), state.pause.is_paused()) {
+        (true, _) => Some(" STOPPING - waiting for iterations in flight "),
+        (false, true) => Some(" PAUSED - p to resume "),
+        (false, false) => None,
+    };
+    if let Some(banner) = banner {
+        let banner = Line::styled(banner, theme.banner.bold());
+        let [rect] = Layout::horizontal([Constraint::Length(banner.width() as u16)])
+            .flex(Flex::Center)
+            .areas(Rect {
+                height: area.height.min(1),
+                ..area
+            });
+        f.render_widget(banner, rect);
+    }
+    if let Some(key) = &state.zoomed {
+        let exec = &scenario.execs[state.current_exec_selected];
+        f.render_widget(Clear, area);
+        render_zoom(
+            key,
+            exec.metrics.get(key),
+            &exec.sample_times,
+            exec.window_metrics().get(key),
+            theme,
+            f,
+            area,
+        );
+    }
+    if state.show_table {
+        f.render_widget(Clear, area);
+        render_table(&app, state, theme, area, f);
+    }
+    if state.show_help {
+        render_help(theme, area, f);
+    }
+}
+
+/// Named values plotted for a sample of a metric, along with their unit.
+fn series(value: &MetricValue) -> (Unit, Vec<(&'static str, f64)>) {
+    let percentiles = |(p50, p90, p95, p99): (f64, f64, f64, f64)| {
+        vec![("p50", p50), ("p90", p90), ("p95", p95), ("p99", p99)]
+    };
+    let ms = |x: Duration| x.as_secs_f64() * 1000.;
+    let percentiles_ms = |(p50, p90, p95, p99): (Duration, Duration, Duration, Duration)| {
+        percentiles((ms(p50), ms(p90), ms(p95), ms(p99)))
+    };
+    match *value {
+        MetricValue::Counter(x) => (Unit::None, vec![("count", x as f64)]),
+        MetricValue::GaugeF64(x) => (Unit::None, vec![("value", x)]),
+        MetricValue::GaugeI64(x) => (Unit::None, vec![("value", x as f64)]),
+        MetricValue::GaugeU64(x) => (Unit::None, vec![("value", x as f64)]),
+        MetricValue::GaugeDuration(x) => (Unit::Millis, vec![("value", ms(x))]),
+        MetricValue::Histogram((p, ..)) => (Unit::None, percentiles(p)),
+        MetricValue::DurationHistogram((p, ..)) => (Unit::Millis, percentiles_ms(p)),
+        MetricValue::Trend(x) => (Unit::None, percentiles(x.percentiles)),
+        MetricValue::DurationTrend(x) => (Unit::Millis, percentiles_ms(x.percentiles)),
+        MetricValue::Rate((run, window)) => (Unit::None, vec![("rate", run), ("window", window)]),
+        MetricValue::Bytes((_, (run, window))) => {
+            (Unit::Bytes, vec![("per sec", run), ("window", window)])
+        }
+        MetricValue::Ratio((trues, total)) => (Unit::None, vec![("ratio", ratio(trues, total))]),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Unit {
+    None,
+    Millis,
+    /// Bytes per second.
+    Bytes,
+}
+
+impl Unit {
+    fn format(self, x: f64) -> String {
+        match self {
+            Unit::None => format!("{x:.2}"),
+            Unit::Millis => format!("{x:.2}ms"),
+            Unit::Bytes => format!("{}/s", format_bytes(x)),
+        }
     }
 }
 
+/// Full screen chart of the history of a metric, with the latest, lowest and highest value of
+/// every series above it.
+fn render_zoom(
+    key: &MetricSetKey,
+    values: Option<&VecDeque<MetricValue>>,
+    times: &VecDeque<Duration>,
+    window: Option<&MetricValue>,
+    theme: &Theme,
+    f: &mut Frame,
+    area: Rect,
+) {
+    let series_style = |index: usize| theme.series[index % theme.series.len()];
+    let mut title = title(key, theme);
+    title
+        .content
+        .spans
+        .push(Span::styled("- Esc to return ", theme.muted));
+    let block = Block::bordered()
+        .title(title)
+        .padding(Padding::horizontal(1));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let Some(last) = values.and_then(|x| x.back()) else {
+        f.render_widget(
+            Text::styled("No samples of this metric", theme.muted),
+            inner,
+        );
+        return;
+    };
+    let values = values.unwrap();
+    let (unit, names) = series(last);
+    let names = names.into_iter().map(|(name, _)| name).collect_vec();
+    let seconds = sample_seconds(times, values.len());
+    let points: Vec<Vec<(f64, f64)>> = (0..names.len())
+        .map(|index| {
+            values
+                .iter()
+                .zip(&seconds)
+                .filter_map(|(value, &x)| Some((x, series(value).1.get(index)?.1)))
+                .collect()
+        })
+        .collect();
+
+    let mut readouts = Text::default();
+    for (index, (name, points)) in names.iter().zip(&points).enumerate() {
+        let (min, max) = points
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(min, max), (_, y)| {
+                (min.min(*y), max.max(*y))
+            });
+        let now = points.last().map_or(0., |x| x.1);
+        readouts.push_line(Line::from(vec![
+            Span::styled(format!("{name:<8}"), series_style(index)),
+            Span::styled("now=", theme.label),
+            Span::raw(format!("{:<14}", unit.format(now))),
+            Span::styled("min=", theme.label),
+            Span::raw(format!("{:<14}", unit.format(min))),
+            Span::styled("max=", theme.label),
+            Span::raw(unit.format(max)),
+        ]));
+    }
+    let mut summary = Line::default();
+    let mut field = |name: &str, value: String| {
+        summary.push_span(Span::styled(format!("{name}="), theme.label));
+        summary.push_span(Span::raw(format!("{value} ")));
+    };
+    match *last {
+        MetricValue::Histogram((_, sum, (min, max))) => {
+            field("sum", format!("{sum:.2}"));
+            field("min", format!("{min:.2}"));
+            field("max", format!("{max:.2}"));
+        }
+        MetricValue::DurationHistogram((_, sum, (min, max))) => {
+            field("sum", format!("{sum:.2?}"));
+            field("min", format!("{min:.2?}"));
+            field("max", format!("{max:.2?}"));
+        }
+        MetricValue::Trend(x) => field("summary", x.to_string()),
+        MetricValue::DurationTrend(x) => field("summary", x.to_string()),
+        _ => (),
+    }
+    if let Some((p50, p99)) = window.and_then(window_percentiles) {
+        field(&format!("last {WINDOW:?} p50"), p50);
+        field("p99", p99);
+    }
+    if !summary.spans.is_empty() {
+        readouts.push_line(summary);
+    }
+
+    let [readouts_area, _, chart_area] = Layout::vertical([
+        Constraint::Length(readouts.height() as u16),
+        Constraint::Length(1),
+        Constraint::Min(0),
+    ])
+    .areas(inner);
+    f.render_widget(readouts, readouts_area);
+
+    let (min, max) = points
+        .iter()
+        .flatten()
+        .fold((f64::MAX, f64::MIN), |(min, max), (_, y)| {
+            (min.min(*y), max.max(*y))
+        });
+    let (min, max) = match max - min {
+        x if x > 0. => (min - x * 0.1, max + x * 0.1),
+        _ => (min - 1., max + 1.),
+    };
+    let datasets = names
+        .iter()
+        .zip(&points)
+        .enumerate()
+        .map(|(index, (name, points))| {
+            Dataset::default()
+                .name(*name)
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(series_style(index))
+                .data(points)
+        })
+        .collect_vec();
+    let chart = Chart::new(datasets)
+        .x_axis(time_axis(&seconds).title("run time"))
+        .y_axis(Axis::default().bounds([min, max]).labels(vec![
+            unit.format(min).into(),
+            unit.format((min + max) / 2.).into(),
+            unit.format(max).into(),
+        ]));
+    f.render_widget(chart, chart_area);
+}
+
+/// Whether `key` matches every whitespace separated term of `filter`. Terms of the form
+/// `name=value` match attributes named `name` whose value contains `value`, others match
+/// metrics whose name contains them.
+pub(super) fn matches(key: &MetricSetKey, filter: &str) -> bool {
+    filter
+        .split_whitespace()
+        .all(|term| match term.split_once('=') {
+            Some((name, value)) => key
+                .attributes
+                .iter()
+                .any(|(x, y)| *x == name && y.to_string().contains(value)),
+            None => key.name.contains(term),
+        })
+}
+
+/// One row per executor of the run, the selected one highlighted.
+fn render_table(app: &App, state: &mut TuiState, theme: &Theme, area: Rect, f: &mut Frame) {
+    let header = Row::new([
+        "Scenario",
+        "Executor",
+        "Users",
+        "Iterations",
+        "Iter/sec",
+        "p95",
+        "Errors",
+    ])
+    .style(theme.label.bold());
+    let selected = (state.scenario(app), state.current_exec_selected);
+    let mut rows = Vec::new();
+    for (scenario_id, scenario) in app.scenarios().iter().enumerate() {
+        for (id, exec) in scenario.execs.iter().enumerate() {
+            if !exec.started() {
+                rows.push(
+                    Row::new([
+                        scenario.name.clone(),
+                        exec.config.to_string(),
+                        "-".to_string(),
+                        "-".to_string(),
+                        "-".to_string(),
+                        "-".to_string(),
+                        "-".to_string(),
+                    ])
+                    .style(theme.muted),
+                );
+                continue;
+            }
+            // Latest sample while running, the average over the whole run once ended.
+            let rate = match state.throughput.get(&(scenario_id, id)) {
+                Some(throughput) if !exec.ended => {
+                    throughput.samples.back().copied().unwrap_or_default() as f64
+                }
+                _ => exec.iterations as f64 / exec.duration().as_secs_f64().max(1e-9),
+            };
+            let p95 = state
+                .iteration_times
+                .get_mut(&(scenario_id, id))
+                .map(|x| format!("{:.2?}", x.quantile(0.95)))
+                .unwrap_or_else(|| "-".to_string());
+            let error_rate = exec.error_rate() * 100.;
+            let errors = match error_rate > 0. {
+                true => Span::styled(format!("{error_rate:.2}%"), theme.error),
+                false => Span::raw(format!("{error_rate:.2}%")),
+            };
+            let mut row = Row::new([
+                Span::raw(scenario.name.clone()),
+                Span::raw(exec.config.to_string()),
+                Span::raw(format!("{}/{}", exec.users, exec.max_users)),
+                Span::raw(exec.iterations.to_string()),
+                Span::raw(format!("{rate:.1}")),
+                Span::raw(p95),
+                errors,
+            ]);
+            if (scenario_id, id) == selected {
+                row = row.style(theme.selected);
+            }
+            rows.push(row);
+        }
+    }
+    let widths = [
+        Constraint::Fill(2),
+        Constraint::Fill(3),
+        Constraint::Length(11),
+        Constraint::Length(11),
+        Constraint::Length(10),
+        Constraint::Length(11),
+        Constraint::Length(8),
+    ];
+    let table = Table::new(rows, widths).header(header).block(
+        Block::bordered()
+            .title(" All executors - t to return ")
+            .padding(Padding::horizontal(1)),
+    );
+    f.render_widget(table, area);
+}
+
+/// Box listing the keys of [`KEYS`], centered over the rest of the screen.
+fn render_help(theme: &Theme, area: Rect, f: &mut Frame) {
+    let key_width = KEYS.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    let lines: Vec<_> = KEYS
+        .iter()
+        .map(|(key, action)| {
+            Line::from(vec![
+                Span::styled(format!("{key:<key_width$}  "), theme.key.bold()),
+                Span::raw(*action),
+            ])
+        })
+        .collect();
+    let text = Text::from(lines);
+    let width = (text.width() as u16 + 4).min(area.width);
+    let height = (text.height() as u16 + 2).min(area.height);
+    let [rect] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [rect] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(rect);
+
+    let block = Block::bordered()
+        .title(" Keys ")
+        .padding(Padding::horizontal(1));
+    f.render_widget(Clear, rect);
+    f.render_widget(Paragraph::new(text).block(block), rect);
+}
+
+/// Latest errors and warnings that fit in `rect`, newest last.
+fn render_log(state: &TuiState, theme: &Theme, rect: Rect, f: &mut Frame) {
+    let errors = state
+        .log
+        .iter()
+        .filter(|x| x.kind != LogKind::Warning)
+        .count();
+    let title = format!(
+        " Errors ({errors}) and warnings ({}) - e to {} ",
+        state.log.len() - errors,
+        match state.log_pane {
+            LogPane::Expanded => "hide",
+            _ => "expand",
+        }
+    );
+    let block = Block::bordered().borders(Borders::TOP).title(title);
+    let inner = margin(block.inner(rect), 1, 0);
+    f.render_widget(block, rect);
+
+    let skip = state.log.len().saturating_sub(inner.height as usize);
+    let lines: Vec<_> = state
+        .log
+        .iter()
+        .skip(skip)
+        .map(|entry| {
+            let kind = match entry.kind {
+                LogKind::Error => Span::styled("error     ", theme.error),
+                LogKind::Warning => Span::styled("warning   ", theme.warning),
+                LogKind::Terminated => Span::styled("terminated", theme.error.bold()),
+            };
+            Line::from(vec![
+                Span::styled(entry.time.format("%H:%M:%S ").to_string(), theme.muted),
+                kind,
+                Span::styled(format!(" {} ", entry.source), theme.label),
+                Span::raw(entry.message.as_str()),
+            ])
+        })
+        .collect();
+    if lines.is_empty() {
+        f.render_widget(Text::styled("No errors", theme.muted), inner);
+    } else {
+        f.render_widget(Text::from(lines), inner);
+    }
+}
+
+/// Whole seconds of a duration rounded up, such as `1h 02m 05s` or `42s`.
+fn duration_text(duration: Duration) -> String {
+    let seconds = duration.as_secs_f64().ceil() as u64;
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m {s:02}s"),
+        (h, m, s) => format!("{h}h {m:02}m {s:02}s"),
+    }
+}
+
+/// Seconds since the run started of the last `count` samples of a metric, see
+/// [`ExecutorState::sample_times`]. Falls back to the index of samples without enough times.
+fn sample_seconds(times: &VecDeque<Duration>, count: usize) -> Vec<f64> {
+    match times.len().checked_sub(count) {
+        Some(start) => times.range(start..).map(Duration::as_secs_f64).collect(),
+        None => (0..count).map(|x| x as f64).collect(),
+    }
+}
+
+/// X axis of a chart spanning the run time of its samples.
+fn time_axis<'a>(seconds: &[f64]) -> Axis<'a> {
+    let first = seconds.first().copied().unwrap_or_default();
+    let last = seconds.last().copied().unwrap_or_default().max(first + 1.);
+    let label = |x: f64| duration_text(Duration::from_secs_f64(x)).into();
+    Axis::default()
+        .bounds([first, last])
+        .labels(vec![label(first), label(last)])
+}
+
 fn padding(n: usize) -> String {
-    String::from_iter(std::iter::repeat(' ').take(n))
+    String::from_iter(std::iter::repeat_n(' ', n))
 }
 
-fn key_value_span(key: &'static str, value: String) -> [Span<'static>; 4] {
+fn key_value_span(
+    theme: &Theme,
+    key: impl Into<Cow<'static, str>>,
+    value: String,
+) -> [Span<'static>; 4] {
+    let key = key.into();
     let size = 1 + key.len() + value.len();
     [
-        Span::raw(key).green(),
-        Span::raw("=").green(),
+        Span::styled(key, theme.label),
+        Span::styled("=", theme.label),
         Span::raw(value),
         Span::raw(padding(INFO_CELL_SIZE.saturating_sub(size).max(1))),
     ]
 }
 
-fn value_span(value: String) -> [Span<'static>; 2] {
+fn value_span(theme: &Theme, value: String) -> [Span<'static>; 2] {
     let size = value.len();
     [
-        Span::raw(value).light_blue(),
+        Span::styled(value, theme.value),
         Span::raw(padding(INFO_CELL_SIZE.saturating_sub(size).max(1))),
     ]
 }
@@ -543,11 +1628,104 @@ fn norm_duration(x: &Duration, max: &Duration) -> u64 {
     x_norm as u64
 }
 
+/// Percentage of `max`, clamped to the range of the chart since histograms of custom metrics
+/// may hold negative values and a p99 of zero.
 fn norm_f64(x: &f64, max: &f64) -> u64 {
     let x_norm = (x / max) * 100.;
     if x_norm.is_nan() {
         return 0;
     }
-    let x_norm: u64 = unsafe { x_norm.to_int_unchecked() };
-    x_norm
+    x_norm.clamp(0., 100.) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::Utc;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::{duration_text, matches, norm_f64, sample_seconds, stage_timeline, Theme};
+    use crate::{
+        data::RuntimeDataStore,
+        logical::{Execution, Executor, Rate, Scenario},
+        state::App,
+        tracing::{
+            message::Message,
+            task_event::{metrics::MetricType, MetricSetKey, Value},
+        },
+        user::User,
+        UserResult,
+    };
+
+    async fn user_builder(_: &RuntimeDataStore) -> impl User {
+        || async { UserResult::Ok(()) }
+    }
+
+    #[test]
+    fn filter_by_name_and_attribute() {
+        let key = MetricSetKey {
+            name: "http_latency",
+            metric_type: MetricType::Histogram,
+            attributes: vec![("route", Value::String("/checkout/pay".to_string()))],
+        };
+        assert!(matches(&key, ""));
+        assert!(matches(&key, "latency"));
+        assert!(matches(&key, "route=/checkout"));
+        assert!(matches(&key, "http route=pay"));
+        assert!(!matches(&key, "latency route=/login"));
+        assert!(!matches(&key, "method=GET"));
+    }
+
+    #[test]
+    fn normalize_bar_values() {
+        assert_eq!(norm_f64(&5., &10.), 50);
+        assert_eq!(norm_f64(&5., &0.), 100);
+        assert_eq!(norm_f64(&-5., &10.), 0);
+        assert_eq!(norm_f64(&0., &0.), 0);
+    }
+
+    #[test]
+    fn format_duration() {
+        assert_eq!(duration_text(Duration::from_millis(41_200)), "42s");
+        assert_eq!(duration_text(Duration::from_secs(125)), "2m 05s");
+        assert_eq!(duration_text(Duration::from_secs(3725)), "1h 02m 05s");
+    }
+
+    #[test]
+    fn render_constant_arrival_rate_stage() {
+        let execution = Execution::builder()
+            .with_user_builder(user_builder)
+            .with_executor(Executor::ConstantArrivalRate {
+                pre_allocate_users: 1,
+                rate: Rate(10, Duration::from_secs(1)),
+                max_users: 1,
+                duration: Duration::from_secs(60),
+            });
+        let mut app = App::new(&[Scenario::new("scenario", execution)]);
+        app.handle_message(Message::ExecutorStart {
+            id: 0,
+            start_time: Utc::now(),
+            prior_executor_duration: Duration::ZERO,
+        });
+        // Constant arrival rate executors report the single stage they run.
+        app.scenarios[0].execs[0].stage = Some(1);
+        app.scenarios[0].execs[0].stages = Some(1);
+
+        let (size, render) = stage_timeline(&app.scenarios[0].execs[0], &Theme::default());
+        assert_eq!(size.height, 3);
+        let mut terminal = Terminal::new(TestBackend::new(60, 3)).unwrap();
+        terminal.draw(|f| render(f, f.size())).unwrap();
+        let title: String = (0..60)
+            .map(|x| terminal.backend().buffer().get(x, 0).symbol())
+            .collect();
+        assert!(title.starts_with("Stage 1/1 10/1s"), "{title}");
+    }
+
+    #[test]
+    fn align_sample_times() {
+        let times = (1..=4).map(Duration::from_secs).collect();
+        assert_eq!(sample_seconds(&times, 2), [3., 4.]);
+        assert_eq!(sample_seconds(&times, 5), [0., 1., 2., 3., 4.]);
+    }
 }
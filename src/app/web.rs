@@ -1,117 +1,161 @@
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::{sse::Event, Html, IntoResponse},
-    routing::{get, Router},
+    extract::{
+        ws::{self, WebSocket, WebSocketUpgrade},
+        Path, Query, Request, State,
+    },
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{sse::Event, Html, IntoResponse, Response},
+    routing::{get, post, Router},
     Json,
 };
+use chrono::{DateTime, Utc};
 use futures::Future;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::{
     error::Error,
+    net::SocketAddr,
+    path::PathBuf,
     sync::{Arc, Mutex},
     time::Duration,
 };
+use tokio::sync::broadcast;
 
-use crate::tracing::message::Message;
+use crate::{
+    runner::{report::html, report::json, summary::Summary, RunReport, RunnerHandle, ScaleTarget},
+    tracing::message::Message,
+};
+
+use subscription::{Subscription, SubscriptionParams};
 
-use super::App;
+mod prometheus;
+mod subscription;
+
+/// Where and how the web server listens, set through the `web_*` options of
+/// [`Runner`](crate::runner::Runner).
+#[derive(Debug, Clone)]
+pub(crate) struct WebConfig {
+    pub addr: SocketAddr,
+    pub auth_token: Option<String>,
+    /// Directory the reports of finished runs are saved to.
+    pub history: Option<PathBuf>,
+    /// Certificate chain and private key in PEM files.
+    #[cfg(feature = "web-tls")]
+    pub tls: Option<(PathBuf, PathBuf)>,
+}
 
-pub fn run(
-    app: Arc<Mutex<App>>,
+impl Default for WebConfig {
+    fn default() -> Self {
+        Self {
+            addr: SocketAddr::from(([0, 0, 0, 0], 3000)),
+            auth_token: None,
+            history: None,
+            #[cfg(feature = "web-tls")]
+            tls: None,
+        }
+    }
+}
+
+pub(crate) fn run(
+    summary: Arc<Mutex<Summary>>,
     mut rx: crate::Receiver<Message>,
+    handle: RunnerHandle,
+    config: WebConfig,
 ) -> impl Future<Output = Result<(), Box<dyn Error + Send + Sync + 'static>>> + Send + 'static {
+    // Messages waiting for slow sockets, older ones are dropped for them.
+    let (messages, _) = broadcast::channel(1024);
+    let started = Utc::now();
+    let history = Arc::new(History {
+        dir: config.history.clone(),
+        id: started.format("%Y%m%dT%H%M%S%3fZ").to_string(),
+        started,
+        summary: summary.clone(),
+        handle: handle.clone(),
+    });
     let router = Router::new()
         .route("/updates", get(stream_messages))
-        .with_state(app.clone())
-        .route("/commands", axum::routing::post(commands))
+        .with_state(summary.clone())
+        .route(
+            "/ws",
+            get(websocket).with_state((messages.clone(), summary.clone())),
+        )
+        .route("/report.json", get(report_json))
+        .route("/report.html", get(report_html))
+        .route("/status", get(status))
+        .with_state((summary.clone(), handle.clone()))
+        .route("/metrics", get(metrics).with_state(handle.clone()))
+        .route("/runs", get(runs))
+        .route("/runs/:id/report.json", get(run_report_json))
+        .route("/runs/:id/report.html", get(run_report_html))
+        .with_state(history.clone())
+        .merge(
+            Router::new()
+                .route("/control/scale", post(scale))
+                .route("/control/:action", post(control))
+                .with_state(handle)
+                .route_layer(middleware::from_fn_with_state(
+                    config.auth_token.map(Arc::<str>::from),
+                    authorize,
+                )),
+        )
         .fallback(get(index))
         .layer(tower_http::cors::CorsLayer::very_permissive());
 
     async move {
         tokio::spawn(async move {
             while let Some(message) = rx.recv().await {
-                let end = matches!(message, Message::End);
-                app.lock().unwrap().handle_message(message.clone());
-                if end {
+                // Sent for every iteration, too many to push to browsers.
+                if !matches!(message, Message::TaskTime { .. }) {
+                    let _ = messages.send(message.clone());
+                }
+                let mut summary = summary.lock().unwrap();
+                summary.handle_message(message);
+                if summary.ended() {
                     break;
                 }
             }
+            if let Err(err) = history.save().await {
+                tracing::warn!("failed to save the report of the run: {err}");
+            }
         });
-        let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
+        #[cfg(feature = "web-tls")]
+        if let Some((cert, key)) = config.tls {
+            let tls = axum_server::tls_rustls::RustlsConfig::from_pem_file(cert, key).await?;
+            axum_server::bind_rustls(config.addr, tls)
+                .serve(router.into_make_service())
+                .await?;
+            return Ok(());
+        }
+        let listener = tokio::net::TcpListener::bind(config.addr).await?;
         axum::serve(listener, router.into_make_service()).await?;
         Ok(())
     }
 }
 
+/// Dashboard charting every executor, see [`Runner::enable_web`](crate::runner::Runner::enable_web).
 async fn index() -> Html<&'static str> {
-    Html(
-        r#"<!DOCTYPE html>
-<html lang="en">
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>SSE Updates</title>
-    <style>
-        body {
-            font-family: Arial, sans-serif;
-        }
-        #updates {
-            margin-top: 20px;
-        }
-        .update {
-            padding: 10px;
-            border-bottom: 1px solid #ccc;
-        }
-    </style>
-</head>
-<body>
-    <h1>Real-Time Updates</h1>
-    <div id="updates">
-        <!-- Updates will be appended here -->
-    </div>
-
-    <script>
-        // Function to create a new update element
-        function createUpdateElement(data) {
-            const updateElement = document.createElement('div');
-            updateElement.className = 'update';
-            updateElement.textContent = data;
-            return updateElement;
-        }
-
-        // Initialize the EventSource
-        const eventSource = new EventSource('/updates');
-
-        // Event listener for incoming messages
-        eventSource.onmessage = function(event) {
-            const updatesContainer = document.getElementById('updates');
-            const updateElement = createUpdateElement(event.data);
-            updatesContainer.appendChild(updateElement);
-        };
-
-        // Event listener for errors
-        eventSource.onerror = function(event) {
-            console.error('EventSource failed:', event);
-        };
-    </script>
-</body>
-</html>"#,
-    )
+    Html(include_str!("web/index.html"))
 }
 
-async fn stream_messages(State(app): State<Arc<Mutex<App>>>) -> impl IntoResponse {
-    let app = app.clone();
+/// State of the run every 200ms until every executor ended, limited to the subscription of
+/// the query, see [`subscription`].
+async fn stream_messages(
+    State(summary): State<Arc<Mutex<Summary>>>,
+    Query(params): Query<SubscriptionParams>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let subscription = Subscription::new(params, summary.lock().unwrap().app())
+        .map_err(|err| (StatusCode::BAD_REQUEST, err))?;
 
     let messages = async_stream::stream! {
         loop {
             let (event, ended) = {
-                 let app = app.lock().unwrap();
+                 let summary = summary.lock().unwrap();
+                 let app = summary.app();
                  let ended = app
                      .scenarios
                      .iter()
                      .all(|s| s.execs.iter().all(|exec| exec.ended));
-                 let event =  Event::default().json_data(&*app);
+                 let event =  Event::default().json_data(subscription.app(app));
                 (event, ended)
             };
             yield event;
@@ -122,16 +166,349 @@ async fn stream_messages(State(app): State<Arc<Mutex<App>>>) -> impl IntoRespons
         }
     };
 
-    axum::response::Sse::new(messages)
+    Ok(axum::response::Sse::new(messages))
+}
+
+/// Aggregated results of the run so far, and whether they are final.
+fn report(summary: &Mutex<Summary>, handle: &RunnerHandle) -> (RunReport, bool) {
+    let mut summary = summary.lock().unwrap();
+    let thresholds = summary.app().thresholds.clone();
+    let report = RunReport::new(&mut summary, &handle.registry(), thresholds);
+    (report, summary.ended())
+}
+
+/// Results of the run in JSON, partial until the run ends as told by the `final` field.
+async fn report_json(
+    State((summary, handle)): State<(Arc<Mutex<Summary>>, RunnerHandle)>,
+) -> Json<serde_json::Value> {
+    let (report, ended) = report(&summary, &handle);
+    Json(json::document(&report, ended))
+}
+
+/// Results of the run as the HTML report, partial until the run ends.
+async fn report_html(
+    State((summary, handle)): State<(Arc<Mutex<Summary>>, RunnerHandle)>,
+) -> Html<String> {
+    let (report, _) = report(&summary, &handle);
+    Html(html::render(&report))
+}
+
+/// Reports of the runs saved to the directory set with
+/// [`Runner::web_history`](crate::runner::Runner::web_history), and the current run.
+struct History {
+    dir: Option<PathBuf>,
+    /// Start time of the current run, which names its reports.
+    id: String,
+    started: DateTime<Utc>,
+    summary: Arc<Mutex<Summary>>,
+    handle: RunnerHandle,
+}
+
+impl History {
+    /// Report of the current run, along with its id and start time.
+    fn document(&self) -> serde_json::Value {
+        let (report, ended) = report(&self.summary, &self.handle);
+        let mut document = json::document(&report, ended);
+        document["id"] = self.id.clone().into();
+        document["started"] = self.started.to_rfc3339().into();
+        document
+    }
+
+    /// Write the JSON and HTML reports of the current run to the directory, if any.
+    async fn save(&self) -> std::io::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let document = self.document();
+        let (report, _) = report(&self.summary, &self.handle);
+        let html = html::render(&report);
+        tokio::fs::create_dir_all(dir).await?;
+        tokio::fs::write(dir.join(format!("{}.json", self.id)), document.to_string()).await?;
+        tokio::fs::write(dir.join(format!("{}.html", self.id)), html).await
+    }
+
+    /// Reports saved to the directory, unreadable ones left out.
+    async fn saved(&self) -> Vec<serde_json::Value> {
+        let mut documents = Vec::new();
+        let Some(dir) = &self.dir else {
+            return documents;
+        };
+        let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+            return documents;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.path().extension().is_some_and(|x| x == "json") {
+                let document = tokio::fs::read(entry.path()).await.ok();
+                documents.extend(document.and_then(|x| serde_json::from_slice(&x).ok()));
+            }
+        }
+        documents
+    }
+
+    /// Path of the report of run `id` with extension `ext`, unless `id` is not the name
+    /// of a report.
+    fn path(&self, id: &str, ext: &str) -> Option<PathBuf> {
+        let valid = !id.is_empty() && id.chars().all(|x| x.is_ascii_alphanumeric());
+        let dir = self.dir.as_ref().filter(|_| valid)?;
+        Some(dir.join(format!("{id}.{ext}")))
+    }
+}
+
+/// Outcome of the current run and of every saved one, oldest first, without the results
+/// of executors, to compare runs while tuning.
+async fn runs(State(history): State<Arc<History>>) -> Json<Vec<serde_json::Value>> {
+    let mut runs = history.saved().await;
+    if !runs.iter().any(|x| x["id"] == history.id.as_str()) {
+        runs.push(history.document());
+    }
+    for run in &mut runs {
+        if let Some(run) = run.as_object_mut() {
+            for key in ["scenarios", "metrics", "error_messages", "slowest"] {
+                run.remove(key);
+            }
+        }
+    }
+    runs.sort_by(|x, y| x["id"].as_str().cmp(&y["id"].as_str()));
+    Json(runs)
+}
+
+/// Report of run `id` in JSON, the current one until it is saved.
+async fn run_report_json(
+    State(history): State<Arc<History>>,
+    Path(id): Path<String>,
+) -> Result<Response, StatusCode> {
+    let saved = match history.path(&id, "json") {
+        Some(path) => tokio::fs::read(path).await.ok(),
+        None => None,
+    };
+    match saved {
+        Some(document) => {
+            Ok(([(header::CONTENT_TYPE, "application/json")], document).into_response())
+        }
+        None if id == history.id => Ok(Json(history.document()).into_response()),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Report of run `id` in HTML, the current one until it is saved.
+async fn run_report_html(
+    State(history): State<Arc<History>>,
+    Path(id): Path<String>,
+) -> Result<Html<String>, StatusCode> {
+    let saved = match history.path(&id, "html") {
+        Some(path) => tokio::fs::read_to_string(path).await.ok(),
+        None => None,
+    };
+    match saved {
+        Some(html) => Ok(Html(html)),
+        None if id == history.id => {
+            let (report, _) = report(&history.summary, &history.handle);
+            Ok(Html(html::render(&report)))
+        }
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Phase of the run reported by `/status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum RunState {
+    /// No executor started yet.
+    Pending,
+    Running,
+    Paused,
+    /// Stopped, iterations in flight are finishing.
+    Draining,
+    /// Ended with every threshold passed.
+    Finished,
+    /// Ended by a user or with a failed threshold.
+    Failed,
+}
+
+impl RunState {
+    fn new(summary: &Summary, handle: &RunnerHandle) -> Self {
+        let app = summary.app();
+        let failed = summary.terminated().is_some() || app.thresholds().iter().any(|x| !x.passed);
+        let started = app
+            .scenarios()
+            .iter()
+            .any(|x| x.execs().iter().any(|x| x.started()));
+        match () {
+            _ if summary.ended() && failed => Self::Failed,
+            _ if summary.ended() => Self::Finished,
+            _ if handle.is_stopped() => Self::Draining,
+            _ if handle.is_paused() => Self::Paused,
+            _ if started => Self::Running,
+            _ => Self::Pending,
+        }
+    }
+}
+
+/// State of the run for orchestrators polling for readiness and completion.
+async fn status(
+    State((summary, handle)): State<(Arc<Mutex<Summary>>, RunnerHandle)>,
+) -> Json<serde_json::Value> {
+    let summary = summary.lock().unwrap();
+    let app = summary.app();
+    let scenario = app.current_scenario();
+    let executors: Vec<_> = scenario
+        .execs()
+        .iter()
+        .filter(|x| x.started() && !x.ended())
+        .map(|x| x.config().to_string())
+        .collect();
+    // Longest executor of every scenario added up, as in the report.
+    let elapsed: Duration = app
+        .scenarios()
+        .iter()
+        .filter_map(|x| x.execs().iter().map(|x| x.duration()).max())
+        .sum();
+    Json(serde_json::json!({
+        "state": RunState::new(&summary, &handle),
+        "scenario": {
+            "id": app.current_scenario_id(),
+            "name": scenario.name(),
+        },
+        "executors": executors,
+        "elapsed_secs": elapsed.as_secs_f64(),
+        "version": env!("CARGO_PKG_VERSION"),
+    }))
+}
+
+/// Current metrics of every executor for Prometheus to scrape.
+async fn metrics(State(handle): State<RunnerHandle>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        prometheus::render(&handle.metrics().snapshot()),
+    )
+}
+
+/// Push every message of the run as JSON text frames as soon as it is received,
+/// closing the socket after [`Message::End`]. Only the subscription of the query is pushed,
+/// see [`subscription`].
+async fn websocket(
+    State((messages, summary)): State<(broadcast::Sender<Message>, Arc<Mutex<Summary>>)>,
+    Query(params): Query<SubscriptionParams>,
+    upgrade: WebSocketUpgrade,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let rx = messages.subscribe();
+    let (subscription, scenario) = {
+        let summary = summary.lock().unwrap();
+        let subscription = Subscription::new(params, summary.app())
+            .map_err(|err| (StatusCode::BAD_REQUEST, err))?;
+        (subscription, summary.app().current_scenario_id())
+    };
+    Ok(upgrade.on_upgrade(move |socket| forward_messages(socket, rx, subscription, scenario)))
+}
+
+async fn forward_messages(
+    mut socket: WebSocket,
+    mut rx: broadcast::Receiver<Message>,
+    subscription: Subscription,
+    mut scenario: usize,
+) {
+    loop {
+        let message = match rx.recv().await {
+            Ok(message) => message,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        if let Message::ScenarioChanged { scenario_id } = message {
+            scenario = scenario_id;
+        }
+        let end = matches!(message, Message::End);
+        let Some(message) = subscription.message(scenario, &message) else {
+            continue;
+        };
+        let Ok(text) = serde_json::to_string(&message) else {
+            continue;
+        };
+        if socket.send(ws::Message::Text(text)).await.is_err() {
+            return;
+        }
+        if end {
+            break;
+        }
+    }
+    let _ = socket.send(ws::Message::Close(None)).await;
+}
+
+#[derive(Debug, Deserialize)]
+struct StopParams {
+    #[serde(default = "graceful")]
+    graceful: bool,
+}
+
+fn graceful() -> bool {
+    true
+}
+
+/// Refuse requests that do not present the token set with
+/// [`Runner::web_auth_token`](crate::runner::Runner::web_auth_token), and every request
+/// when none is set.
+async fn authorize(
+    State(token): State<Option<Arc<str>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(token) = token else {
+        return StatusCode::FORBIDDEN.into_response();
+    };
+    let bearer = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match bearer.is_some_and(|bearer| same(bearer.as_bytes(), token.as_bytes())) {
+        true => next.run(request).await,
+        false => StatusCode::UNAUTHORIZED.into_response(),
+    }
+}
+
+/// Compare in time independent of where the values differ, not to leak the token.
+fn same(x: &[u8], y: &[u8]) -> bool {
+    x.len() == y.len() && x.iter().zip(y).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Pause, resume or stop the run.
+async fn control(
+    State(handle): State<RunnerHandle>,
+    Path(action): Path<String>,
+    Query(params): Query<StopParams>,
+) -> StatusCode {
+    match action.as_str() {
+        "pause" => handle.pause(),
+        "resume" => handle.resume(),
+        "stop" if params.graceful => handle.stop(),
+        "stop" => handle.abort(),
+        _ => return StatusCode::NOT_FOUND,
+    }
+    StatusCode::NO_CONTENT
 }
 
 #[derive(Debug, Deserialize)]
-struct Command {
-    action: String,
-    value: String,
+struct ScaleParams {
+    scenario: usize,
+    executor: usize,
+    users: Option<usize>,
+    rate: Option<usize>,
 }
 
-async fn commands(Json(command): Json<Command>) -> impl IntoResponse {
-    println!("Received command: {} {}", command.action, command.value);
-    StatusCode::OK
+/// Change the users or the rate of a running executor, see [`RunnerHandle::scale`].
+async fn scale(
+    State(handle): State<RunnerHandle>,
+    Json(params): Json<ScaleParams>,
+) -> (StatusCode, String) {
+    let target = match (params.users, params.rate) {
+        (Some(users), None) => ScaleTarget::Users(users),
+        (None, Some(rate)) => ScaleTarget::Rate(rate),
+        _ => {
+            let err = "expected exactly one of `users` or `rate`".to_string();
+            return (StatusCode::BAD_REQUEST, err);
+        }
+    };
+    match handle.scale(params.scenario, params.executor, target) {
+        Ok(()) => (StatusCode::NO_CONTENT, String::new()),
+        Err(err) => (StatusCode::BAD_REQUEST, err.to_string()),
+    }
 }
@@ -0,0 +1,200 @@
+//! Current metrics of every executor in the Prometheus text format, served at `/metrics`.
+//!
+//! Every field of a metric becomes a sample named after the metric and the field, such as
+//! `rusher_http_req_duration_max`, labelled with the scenario, the executor and the attributes
+//! of the metric. Percentiles share one summary family with a `quantile` label, and values
+//! of gauges and counters are samples of the metric name itself.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::{
+    runner::ExecutorMetrics,
+    tracing::{
+        sink::{fields, Field},
+        task_event::metrics::{MetricType, MetricValue},
+    },
+};
+
+/// Samples of a metric family along with its type, if it has one in the text format.
+#[derive(Default)]
+struct Family {
+    kind: Option<&'static str>,
+    samples: Vec<String>,
+}
+
+pub(super) fn render(executors: &[ExecutorMetrics]) -> String {
+    let mut families: BTreeMap<String, Family> = BTreeMap::new();
+    for exec in executors {
+        for (key, value) in &exec.metrics {
+            let mut labels = vec![
+                ("scenario".to_string(), exec.scenario.clone()),
+                ("executor".to_string(), exec.executor.clone()),
+            ];
+            labels.extend(
+                key.attributes
+                    .iter()
+                    .map(|(name, value)| (sanitize(name), value.to_string())),
+            );
+            let name = format!("{}_{}", crate::CRATE_NAME, sanitize(key.name));
+            for (field, number) in fields(value) {
+                let (family, kind, sample, quantile) = match field {
+                    "value" => {
+                        let kind = match key.metric_type {
+                            MetricType::Counter => "counter",
+                            _ => "gauge",
+                        };
+                        (name.clone(), Some(kind), name.clone(), None)
+                    }
+                    "p50" | "p90" | "p95" | "p99" => {
+                        let quantile = match field {
+                            "p50" => "0.5",
+                            "p90" => "0.9",
+                            "p95" => "0.95",
+                            _ => "0.99",
+                        };
+                        let quantile = Some(quantile.to_string());
+                        (name.clone(), Some("summary"), name.clone(), quantile)
+                    }
+                    "sum" | "count" if summary(value) => {
+                        let sample = format!("{name}_{field}");
+                        (name.clone(), Some("summary"), sample, None)
+                    }
+                    _ => {
+                        let sample = format!("{name}_{field}");
+                        (sample.clone(), None, sample, None)
+                    }
+                };
+                let family = families.entry(family).or_default();
+                family.kind = kind;
+                let mut labels = labels.clone();
+                labels.extend(quantile.map(|x| ("quantile".to_string(), x)));
+                family.samples.push(format!(
+                    "{sample}{} {}",
+                    format_labels(&labels),
+                    text(number)
+                ));
+            }
+        }
+    }
+
+    let mut text = String::new();
+    for (name, family) in families {
+        if let Some(kind) = family.kind {
+            let _ = writeln!(text, "# TYPE {name} {kind}");
+        }
+        for sample in family.samples {
+            let _ = writeln!(text, "{sample}");
+        }
+    }
+    text
+}
+
+/// Whether the percentiles of `value` are exposed as a summary.
+fn summary(value: &MetricValue) -> bool {
+    matches!(
+        value,
+        MetricValue::Histogram(_)
+            | MetricValue::DurationHistogram(_)
+            | MetricValue::Trend(_)
+            | MetricValue::DurationTrend(_)
+    )
+}
+
+/// Name made of the characters allowed by Prometheus, others replaced by `_`.
+fn sanitize(name: &str) -> String {
+    let mut name: String = name
+        .chars()
+        .map(|x| match x.is_ascii_alphanumeric() {
+            true => x,
+            false => '_',
+        })
+        .collect();
+    if name.starts_with(|x: char| x.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+fn format_labels(labels: &[(String, String)]) -> String {
+    let labels: Vec<_> = labels
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', r"\\")
+                .replace('"', r#"\""#)
+                .replace('\n', r"\n");
+            format!("{name}=\"{value}\"")
+        })
+        .collect();
+    format!("{{{}}}", labels.join(","))
+}
+
+fn text(number: Field) -> String {
+    match number {
+        Field::Float(x) if x.is_infinite() => match x > 0. {
+            true => "+Inf".to_string(),
+            false => "-Inf".to_string(),
+        },
+        number => number.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::tracing::task_event::{MetricSetKey, Value};
+
+    #[test]
+    fn render_families() {
+        let exec = ExecutorMetrics {
+            scenario: "checkout".to_string(),
+            executor: "Once".to_string(),
+            metrics: vec![
+                (
+                    MetricSetKey {
+                        name: "http.requests",
+                        metric_type: MetricType::Counter,
+                        attributes: vec![("route", Value::String("/a\"b".to_string()))],
+                    },
+                    MetricValue::Counter(3),
+                ),
+                (
+                    MetricSetKey {
+                        name: "latency",
+                        metric_type: MetricType::Histogram,
+                        attributes: vec![],
+                    },
+                    MetricValue::DurationHistogram((
+                        (
+                            Duration::from_millis(1),
+                            Duration::from_millis(2),
+                            Duration::from_millis(3),
+                            Duration::from_millis(4),
+                        ),
+                        Duration::from_millis(10),
+                        (Duration::from_millis(1), Duration::from_millis(5)),
+                    )),
+                ),
+            ],
+        };
+
+        let labels = r#"scenario="checkout",executor="Once""#;
+        assert_eq!(
+            render(&[exec]),
+            format!(
+                "# TYPE rusher_http_requests counter\n\
+                rusher_http_requests{{{labels},route=\"/a\\\"b\"}} 3\n\
+                # TYPE rusher_latency summary\n\
+                rusher_latency{{{labels},quantile=\"0.5\"}} 1\n\
+                rusher_latency{{{labels},quantile=\"0.9\"}} 2\n\
+                rusher_latency{{{labels},quantile=\"0.95\"}} 3\n\
+                rusher_latency{{{labels},quantile=\"0.99\"}} 4\n\
+                rusher_latency_sum{{{labels}}} 10\n\
+                rusher_latency_max{{{labels}}} 5\n\
+                rusher_latency_min{{{labels}}} 1\n"
+            )
+        );
+    }
+}
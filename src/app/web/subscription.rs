@@ -0,0 +1,223 @@
+//! Subset of the live stream a client of `/ws` or `/updates` subscribes to with query
+//! parameters, such as `?scenario=checkout&executor=0,1&metric=http_req_duration`, so that a
+//! dashboard tab of a high cardinality run only receives what it charts.
+//!
+//! `scenario` is the name or the index of a scenario, `executor` and `metric` are comma
+//! separated indices of executors and names of metrics. Every parameter left out matches
+//! everything.
+
+use std::borrow::Cow;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{state::App, tracing::message::Message};
+
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct SubscriptionParams {
+    scenario: Option<String>,
+    executor: Option<String>,
+    metric: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct Subscription {
+    scenario: Option<usize>,
+    executors: Option<Vec<usize>>,
+    metrics: Option<Vec<String>>,
+}
+
+impl Subscription {
+    pub(super) fn new(params: SubscriptionParams, app: &App) -> Result<Self, String> {
+        let scenario = match params.scenario {
+            Some(scenario) => {
+                let names = app.scenarios().iter().map(|x| x.name());
+                let id = names.clone().position(|x| x == scenario);
+                let id = id.or_else(|| scenario.parse().ok().filter(|x| *x < names.len()));
+                Some(id.ok_or_else(|| format!("no scenario `{scenario}`"))?)
+            }
+            None => None,
+        };
+        let executors = match params.executor {
+            Some(executors) => Some(
+                executors
+                    .split(',')
+                    .map(|x| x.trim().parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| format!("invalid executors `{executors}`"))?,
+            ),
+            None => None,
+        };
+        let metrics = params
+            .metric
+            .map(|x| x.split(',').map(|x| x.trim().to_string()).collect());
+        Ok(Self {
+            scenario,
+            executors,
+            metrics,
+        })
+    }
+
+    fn scenario(&self, scenario: usize) -> bool {
+        self.scenario.is_none_or(|x| x == scenario)
+    }
+
+    fn executor(&self, scenario: usize, executor: usize) -> bool {
+        self.scenario(scenario)
+            && self
+                .executors
+                .as_ref()
+                .is_none_or(|x| x.contains(&executor))
+    }
+
+    fn metric(&self, name: &str) -> bool {
+        self.metrics
+            .as_ref()
+            .is_none_or(|x| x.iter().any(|x| x == name))
+    }
+
+    /// `message` as the client receives it while `scenario` runs, `None` when it is not
+    /// subscribed to. Messages about the whole run are always received.
+    pub(super) fn message<'a>(
+        &self,
+        scenario: usize,
+        message: &'a Message,
+    ) -> Option<Cow<'a, Message>> {
+        let subscribed = match message {
+            Message::ExecutorStart { id, .. }
+            | Message::ExecutorUpdate { id, .. }
+            | Message::ExecutorEnd { id } => self.executor(scenario, *id),
+            Message::ScenarioUpdate { id, .. } => self.scenario(*id),
+            Message::Error { execution_id, .. } | Message::Warning { execution_id, .. } => {
+                match execution_id {
+                    Some(id) => self.executor(scenario, *id),
+                    None => self.scenario(scenario),
+                }
+            }
+            Message::Iteration(record) => self.executor(record.scenario_id, record.execution_id),
+            _ => true,
+        };
+        if !subscribed {
+            return None;
+        }
+        if self.metrics.is_none() {
+            return Some(Cow::Borrowed(message));
+        }
+        let mut message = message.clone();
+        match &mut message {
+            Message::ExecutorUpdate {
+                metrics,
+                window_metrics,
+                deltas,
+                ..
+            } => {
+                metrics.retain(|(key, _)| self.metric(key.name));
+                window_metrics.retain(|(key, _)| self.metric(key.name));
+                deltas.retain(|(key, _)| self.metric(key.name));
+            }
+            Message::ScenarioUpdate { metrics, .. } => {
+                metrics.retain(|(key, _)| self.metric(key.name))
+            }
+            _ => (),
+        }
+        Some(Cow::Owned(message))
+    }
+
+    /// State of the run as JSON with only the subscribed metrics. Scenarios and executors not
+    /// subscribed to are `null`, which keeps the index of the others.
+    pub(super) fn app(&self, app: &App) -> Value {
+        let mut value = serde_json::to_value(app).unwrap_or_default();
+        let Some(scenarios) = value["scenarios"].as_array_mut() else {
+            return value;
+        };
+        for (id, scenario) in scenarios.iter_mut().enumerate() {
+            if !self.scenario(id) {
+                *scenario = Value::Null;
+                continue;
+            }
+            self.retain(&mut scenario["metrics"]);
+            let Some(execs) = scenario["execs"].as_array_mut() else {
+                continue;
+            };
+            for (exec_id, exec) in execs.iter_mut().enumerate() {
+                if !self.executor(id, exec_id) {
+                    *exec = Value::Null;
+                    continue;
+                }
+                for metrics in ["metrics", "windowMetrics", "deltas"] {
+                    self.retain(&mut exec[metrics]);
+                }
+            }
+        }
+        value
+    }
+
+    /// Keep the subscribed entries of serialized metrics, pairs of a key and a value.
+    fn retain(&self, metrics: &mut Value) {
+        if let (Some(_), Some(metrics)) = (&self.metrics, metrics.as_array_mut()) {
+            metrics.retain(|x| x[0]["name"].as_str().is_some_and(|x| self.metric(x)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracing::task_event::{
+        metrics::{MetricType, MetricValue},
+        MetricSetKey,
+    };
+
+    fn update(id: usize, names: &[&'static str]) -> Message {
+        let metrics: Vec<_> = names
+            .iter()
+            .map(|name| {
+                let key = MetricSetKey {
+                    name,
+                    metric_type: MetricType::Counter,
+                    attributes: vec![],
+                };
+                (key, MetricValue::Counter(1))
+            })
+            .collect();
+        Message::ExecutorUpdate {
+            id,
+            users: 1,
+            max_users: 1,
+            total_iteration: None,
+            total_duration: None,
+            stage: None,
+            stage_duration: None,
+            stages: None,
+            metrics: metrics.clone(),
+            window_metrics: metrics,
+            interval: Default::default(),
+            deltas: vec![],
+        }
+    }
+
+    #[test]
+    fn filter_messages() {
+        let subscription = Subscription {
+            scenario: Some(1),
+            executors: Some(vec![0]),
+            metrics: Some(vec!["requests".to_string()]),
+        };
+        let message = update(0, &["requests", "bytes"]);
+        assert!(subscription.message(0, &message).is_none());
+        assert!(subscription.message(1, &update(1, &[])).is_none());
+        match subscription.message(1, &message).as_deref() {
+            Some(Message::ExecutorUpdate {
+                metrics,
+                window_metrics,
+                ..
+            }) => {
+                assert_eq!(metrics.len(), 1);
+                assert_eq!(metrics[0].0.name, "requests");
+                assert_eq!(window_metrics.len(), 1);
+            }
+            x => panic!("unexpected {x:?}"),
+        }
+        assert!(subscription.message(0, &Message::End).is_some());
+    }
+}
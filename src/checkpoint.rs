@@ -0,0 +1,276 @@
+//! Checkpoints of run progress, written periodically to disk so that a long run
+//! interrupted by a crash or a deploy can continue instead of starting from zero.
+
+use std::time::Duration;
+
+use crate::logical;
+
+#[cfg(feature = "serde")]
+use crate::{
+    state::App,
+    tracing::{
+        message::Message,
+        task_event::{
+            metrics::{MetricState, MetricType},
+            MetricSet, MetricSetKey, MetricsConfig, Value,
+        },
+        MetricRegistry,
+    },
+};
+
+/// Progress of every executor of every scenario at the time of the checkpoint.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Checkpoint {
+    scenarios: Vec<ScenarioCheckpoint>,
+}
+
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct ScenarioCheckpoint {
+    #[cfg(feature = "serde")]
+    name: String,
+    executors: Vec<ExecutorCheckpoint>,
+}
+
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct ExecutorCheckpoint {
+    ended: bool,
+    iterations: u64,
+    elapsed: Duration,
+    #[cfg(feature = "serde")]
+    metrics: Vec<(MetricKey, MetricState)>,
+}
+
+/// Owned version of [`MetricSetKey`] without the metric type, which follows from the state.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct MetricKey {
+    name: String,
+    attributes: Vec<(String, AttributeValue)>,
+}
+
+/// Externally tagged version of [`Value`] so it can be deserialized back without ambiguity.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+enum AttributeValue {
+    String(String),
+    Number(i64),
+    UnsignedNumber(u64),
+    Bytes(u64),
+    Float(f64),
+    Duration(Duration),
+    Bool(bool),
+}
+
+impl Checkpoint {
+    /// Work left for an executor given the progress recorded in this checkpoint.
+    pub fn remaining(
+        &self,
+        scenario: usize,
+        executor: usize,
+        config: &logical::Executor,
+    ) -> Option<logical::Executor> {
+        let Some(progress) = self
+            .scenarios
+            .get(scenario)
+            .and_then(|x| x.executors.get(executor))
+        else {
+            return Some(config.clone());
+        };
+
+        if progress.ended {
+            return None;
+        }
+        config.remaining(progress.iterations, progress.elapsed)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Checkpoint {
+    /// Build a checkpoint from live app state and metrics.
+    /// Progress made before resuming from `base` is carried over.
+    fn new(app: &App, registry: &MetricRegistry, base: Option<&Checkpoint>) -> Self {
+        let scenarios = app
+            .scenarios()
+            .iter()
+            .enumerate()
+            .map(|(scenario_id, scenario)| {
+                let executors = scenario
+                    .execs()
+                    .iter()
+                    .enumerate()
+                    .map(|(executor_id, exec)| {
+                        let prior = base
+                            .and_then(|x| x.scenarios.get(scenario_id))
+                            .and_then(|x| x.executors.get(executor_id));
+
+                        let mut checkpoint = ExecutorCheckpoint {
+                            ended: exec.ended() || prior.is_some_and(|x| x.ended),
+                            iterations: exec.iterations()
+                                + prior.map(|x| x.iterations).unwrap_or_default(),
+                            elapsed: exec.duration() + prior.map(|x| x.elapsed).unwrap_or_default(),
+                            metrics: Vec::new(),
+                        };
+
+                        // Registry is seeded with prior metrics on resume, so these are cumulative.
+                        if let Some(metrics) = registry.get(&(scenario_id, executor_id)) {
+                            checkpoint.metrics = metrics
+                                .states()
+                                .map(|(key, state)| (MetricKey::from(&key), state))
+                                .collect();
+                        }
+
+                        checkpoint
+                    })
+                    .collect();
+
+                ScenarioCheckpoint {
+                    name: scenario.name().to_string(),
+                    executors,
+                }
+            })
+            .collect();
+
+        Self { scenarios }
+    }
+
+    /// Read a checkpoint from disk. A missing file means there is nothing to resume from.
+    pub fn load(path: &std::path::Path) -> Result<Option<Self>, crate::error::Error> {
+        let content = match std::fs::read(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(anyhow::Error::from(err).into()),
+        };
+        let checkpoint = serde_json::from_slice(&content).map_err(anyhow::Error::from)?;
+        Ok(Some(checkpoint))
+    }
+
+    async fn save(&self, path: &std::path::Path) -> std::io::Result<()> {
+        let content = serde_json::to_vec(self)?;
+        crate::runner::write_atomic(path, content).await
+    }
+
+    /// Make sure this checkpoint was created from the same scenarios.
+    pub fn validate(&self, scenarios: &[logical::Scenario]) -> Result<(), crate::error::Error> {
+        let matches =
+            self.scenarios.len() == scenarios.len()
+                && self.scenarios.iter().zip(scenarios).all(|(x, y)| {
+                    x.name == y.label && x.executors.len() == y.execution_provider.len()
+                });
+        if !matches {
+            return Err(crate::error::Error::new(
+                "checkpoint does not match the scenarios of this runner",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Populate the registry with metrics recorded before the checkpoint.
+    pub fn seed(&self, registry: &MetricRegistry, config: MetricsConfig) {
+        for (scenario_id, scenario) in self.scenarios.iter().enumerate() {
+            for (executor_id, exec) in scenario.executors.iter().enumerate() {
+                let metrics = MetricSet::resumed(config, exec.elapsed);
+                for (key, state) in &exec.metrics {
+                    let (metric_type, _) = state.kind();
+                    metrics.restore(key.to_key(metric_type), state.clone());
+                }
+                registry.insert((scenario_id, executor_id), metrics.into());
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<&MetricSetKey> for MetricKey {
+    fn from(key: &MetricSetKey) -> Self {
+        let attributes = key
+            .attributes
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    Value::String(x) => AttributeValue::String(x.clone()),
+                    Value::Number(x) => AttributeValue::Number(*x),
+                    Value::UnsignedNumber(x) => AttributeValue::UnsignedNumber(*x),
+                    Value::Bytes(x) => AttributeValue::Bytes(*x),
+                    Value::Float(x) => AttributeValue::Float(x.0),
+                    Value::Duration(x) => AttributeValue::Duration(*x),
+                    Value::Bool(x) => AttributeValue::Bool(*x),
+                };
+                (name.to_string(), value)
+            })
+            .collect();
+        Self {
+            name: key.name.to_string(),
+            attributes,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl MetricKey {
+    /// Metric names are static strings from tracing metadata.
+    /// Restored names are leaked, this happens once per metric when resuming.
+    fn to_key(&self, metric_type: MetricType) -> MetricSetKey {
+        fn leak(x: &str) -> &'static str {
+            Box::leak(x.to_string().into_boxed_str())
+        }
+
+        let attributes = self
+            .attributes
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    AttributeValue::String(x) => Value::String(x.clone()),
+                    AttributeValue::Number(x) => Value::Number(*x),
+                    AttributeValue::UnsignedNumber(x) => Value::UnsignedNumber(*x),
+                    AttributeValue::Bytes(x) => Value::Bytes(*x),
+                    AttributeValue::Float(x) => Value::Float((*x).into()),
+                    AttributeValue::Duration(x) => Value::Duration(*x),
+                    AttributeValue::Bool(x) => Value::Bool(*x),
+                };
+                (leak(name), value)
+            })
+            .collect();
+
+        MetricSetKey {
+            name: leak(&self.name),
+            metric_type,
+            attributes,
+        }
+    }
+}
+
+/// Track run progress from the message stream and write a checkpoint every `interval`
+/// and once more when the run ends.
+#[cfg(feature = "serde")]
+pub(crate) async fn run(
+    mut app: App,
+    mut rx: crate::Receiver<Message>,
+    registry: std::sync::Arc<MetricRegistry>,
+    path: std::path::PathBuf,
+    interval: Duration,
+    base: Option<Checkpoint>,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            message = rx.recv() => match message {
+                Some(Message::End) | None => break,
+                Some(message) => app.handle_message(message),
+            },
+            _ = ticker.tick() => {
+                let checkpoint = Checkpoint::new(&app, &registry, base.as_ref());
+                if let Err(err) = checkpoint.save(&path).await {
+                    tracing::warn!("failed to write checkpoint: {err}");
+                }
+            }
+        }
+    }
+
+    let checkpoint = Checkpoint::new(&app, &registry, base.as_ref());
+    if let Err(err) = checkpoint.save(&path).await {
+        tracing::warn!("failed to write checkpoint: {err}");
+    }
+}
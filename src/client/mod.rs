@@ -1,2 +1,18 @@
 #[cfg(feature = "reqwest")]
 pub mod reqwest;
+
+use tracing::{event, Level};
+
+use crate::USER_TASK;
+
+/// Count a response with status code `status` to `route` from within a user task, for clients other than
+/// the bundled `reqwest` one which does this on its own.
+/// Responses are added to the [`HTTP_STATUS`](crate::tracing::HTTP_STATUS) counter and summarized
+/// by status class such as `2xx` or `5xx`.
+///
+/// ```no_run
+/// rusher::client::record_status(404, "/users/{id}");
+/// ```
+pub fn record_status(status: u16, route: &str) {
+    event!(name: "http_status.counter", target: USER_TASK, Level::INFO, value = 1u64, code = status, route);
+}
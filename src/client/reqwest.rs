@@ -1,3 +1,14 @@
+//! [`reqwest`] client that records HTTP metrics of every request it sends.
+//!
+//! * `http_req_duration` - histogram of the time until response headers are received.
+//! * `http_status` - counter of responses with the status in a `code` attribute.
+//! * `data_sent`, `data_received` - bytes of request and response bodies, when their size is known.
+//!
+//! Metrics carry `method` and `route` attributes, the route is the path of the request
+//! unless a label is set with [`RequestBuilder::route`].
+
+use std::borrow::Cow;
+
 use tracing::{event, field, span, Level};
 
 use crate::USER_TASK;
@@ -60,6 +71,7 @@ impl Client {
 #[must_use = "RequestBuilder does nothing until you 'send' it"]
 pub struct RequestBuilder {
     inner: reqwest::RequestBuilder,
+    route: Option<Cow<'static, str>>,
 }
 
 impl std::ops::Deref for RequestBuilder {
@@ -77,7 +89,10 @@ impl std::ops::DerefMut for RequestBuilder {
 
 impl From<reqwest::RequestBuilder> for RequestBuilder {
     fn from(value: reqwest::RequestBuilder) -> Self {
-        Self { inner: value }
+        Self {
+            inner: value,
+            route: None,
+        }
     }
 }
 
@@ -87,29 +102,38 @@ impl RequestBuilder {
         self
     }
 
+    /// Label metrics of this request with `route` instead of its path, such as `/users/{id}`,
+    /// so requests to parametrized paths share a series.
+    pub fn route(mut self, route: impl Into<Cow<'static, str>>) -> RequestBuilder {
+        self.route = Some(route.into());
+        self
+    }
+
     pub async fn send(self) -> Result<reqwest::Response, reqwest::Error> {
         let (client, request) = self.inner.build_split();
         let request = request?;
         let host = request.url().host();
-        let path = request.url().path();
+        let route = self
+            .route
+            .as_deref()
+            .unwrap_or_else(|| request.url().path());
         let method = request.method();
-        let span =
-            span!(target: USER_TASK, Level::INFO, "reqwest", url = field::Empty, %path, %method);
+        let span = span!(target: USER_TASK, Level::INFO, "http_req_duration", url = field::Empty, %method, %route);
         let _t = span.enter();
         if let Some(host) = host {
             span.record("url", field::display(host));
         }
         use http_body::Body as _;
         if let Some(size) = request.body().and_then(|x| x.size_hint().exact()) {
-            event!(name: "sent.gauge", target: USER_TASK, Level::INFO, value = size as f64);
+            event!(name: "data_sent.bytes", target: USER_TASK, Level::INFO, value = size);
         }
         drop(_t);
         let resp = client.execute(request).await?;
         let _t = span.enter();
         if let Some(size) = resp.content_length() {
-            event!(name: "receive.gauge", target: USER_TASK, Level::INFO, value = size as f64);
+            event!(name: "data_received.bytes", target: USER_TASK, Level::INFO, value = size);
         }
-        event!(name: "status.counter", target: USER_TASK, Level::INFO, status = resp.status().as_str(), value = 1u64);
+        event!(name: "http_status.counter", target: USER_TASK, Level::INFO, code = resp.status().as_u16(), value = 1u64);
         Ok(resp)
     }
 }
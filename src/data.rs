@@ -76,12 +76,13 @@ impl RuntimeDataStore {
 ///
 /// ## Manual Implementaion
 /// ```no_run
+/// # use rusher::data::{DatastoreModifier, RuntimeDataStore};
 /// // Create a cheaply clonable type for using same data across execution.
 /// #[derive(Clone)]
 /// struct Def {
 ///     field: std::sync::Arc<usize>,
 /// }
-
+///
 /// #[async_trait::async_trait]
 /// impl DatastoreModifier for Def {
 ///     async fn init_store(&self, store: &mut RuntimeDataStore) {
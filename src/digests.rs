@@ -0,0 +1,159 @@
+//! Histogram digests of a run saved to a file, to be merged with the digests of other runs
+//! or worker processes.
+//!
+//! Percentiles of separate runs can not be averaged into a meaningful value, but their digests
+//! can be merged into accurate percentiles of all values observed. Runs save their digests with
+//! [`Runner::save_digests`](crate::runner::Runner::save_digests), and [`merge_files`] combines
+//! the files of every worker or round.
+//!
+//! ```no_run
+//! let merged = rusher::digests::merge_files(["worker-1.json", "worker-2.json"]).unwrap();
+//! for digest in merged {
+//!     println!("{} / {} {}: {:?}", digest.scenario, digest.executor, digest.name, digest.digest.value());
+//! }
+//! ```
+
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    error::Error,
+    logical,
+    tracing::{task_event::metrics::HistogramDigest, MetricRegistry},
+};
+
+/// Digest of one histogram of an executor, identified by names so that it can be matched
+/// with the same histogram of other runs.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SavedDigest {
+    pub scenario: String,
+    pub executor: String,
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub digest: HistogramDigest,
+}
+
+/// Digests of every histogram of every executor in the registry.
+pub(crate) fn collect(
+    registry: &MetricRegistry,
+    scenarios: &[logical::Scenario<'_>],
+) -> Vec<SavedDigest> {
+    let mut digests = Vec::new();
+    for (scenario_id, scenario) in scenarios.iter().enumerate() {
+        for (exec_id, exec) in scenario.execution_provider.iter().enumerate() {
+            let Some(metrics) = registry.get(&(scenario_id, exec_id)) else {
+                continue;
+            };
+            digests.extend(metrics.digests().map(|(key, digest)| {
+                SavedDigest {
+                    scenario: scenario.label.to_string(),
+                    executor: exec.config().to_string(),
+                    name: key.name.to_string(),
+                    attributes: key
+                        .attributes
+                        .iter()
+                        .map(|(name, value)| (name.to_string(), value.to_string()))
+                        .collect(),
+                    digest,
+                }
+            }));
+        }
+    }
+    digests
+}
+
+/// Write `digests` to `path` as JSON.
+pub fn write(path: impl AsRef<Path>, digests: &[SavedDigest]) -> Result<(), Error> {
+    let content = serde_json::to_vec(digests).map_err(anyhow::Error::from)?;
+    std::fs::write(path, content).map_err(anyhow::Error::from)?;
+    Ok(())
+}
+
+/// Read digests written by [`write`].
+pub fn read(path: impl AsRef<Path>) -> Result<Vec<SavedDigest>, Error> {
+    let content = std::fs::read(path).map_err(anyhow::Error::from)?;
+    Ok(serde_json::from_slice(&content).map_err(anyhow::Error::from)?)
+}
+
+/// Merge digests of the same histogram, those with equal scenario, executor, name and attributes.
+/// Order of first appearance is kept.
+pub fn merge(digests: impl IntoIterator<Item = SavedDigest>) -> Vec<SavedDigest> {
+    let mut merged: Vec<SavedDigest> = Vec::new();
+    let mut index: HashMap<_, usize> = HashMap::new();
+    for digest in digests {
+        let key = (
+            digest.scenario.clone(),
+            digest.executor.clone(),
+            digest.name.clone(),
+            digest.attributes.clone(),
+        );
+        match index.get(&key) {
+            Some(&i) => {
+                let existing = std::mem::take(&mut merged[i].digest);
+                merged[i].digest = HistogramDigest::merge([existing, digest.digest]);
+            }
+            None => {
+                index.insert(key, merged.len());
+                merged.push(digest);
+            }
+        }
+    }
+    merged
+}
+
+/// Read every file and [`merge`] their digests.
+pub fn merge_files(
+    paths: impl IntoIterator<Item = impl AsRef<Path>>,
+) -> Result<Vec<SavedDigest>, Error> {
+    let mut digests = Vec::new();
+    for path in paths {
+        digests.extend(read(path)?);
+    }
+    Ok(merge(digests))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracing::task_event::{
+        metrics::MetricType, MetricSet, MetricsConfig, TaskEvent, Value,
+    };
+
+    fn digest(values: impl IntoIterator<Item = f64>) -> HistogramDigest {
+        let set = MetricSet::new(MetricsConfig::default());
+        for value in values {
+            set.update(TaskEvent::new(
+                "latency",
+                MetricType::Histogram,
+                vec![],
+                Value::Float(value.into()),
+            ));
+        }
+        let (_, digest) = set.digests().next().unwrap();
+        digest
+    }
+
+    #[test]
+    fn merge_saved_files() {
+        let saved = |executor: &str, values: std::ops::Range<u32>| SavedDigest {
+            scenario: "checkout".to_string(),
+            executor: executor.to_string(),
+            name: "latency".to_string(),
+            attributes: vec![],
+            digest: digest(values.map(f64::from)),
+        };
+        let dir = std::env::temp_dir();
+        let paths =
+            [1, 2].map(|x| dir.join(format!("rusher-digests-{}-{x}.json", std::process::id())));
+        write(&paths[0], &[saved("Once", 0..50), saved("Other", 0..10)]).unwrap();
+        write(&paths[1], &[saved("Once", 50..100)]).unwrap();
+
+        let merged = merge_files(&paths).unwrap();
+        for path in &paths {
+            std::fs::remove_file(path).unwrap();
+        }
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].digest.count(), 100);
+        assert_eq!(merged[0].digest.sum(), (0..100).sum::<u32>() as f64);
+        assert_eq!(merged[1].digest.count(), 10);
+    }
+}
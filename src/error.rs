@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, time::Duration};
 
 use anyhow::anyhow;
 
@@ -6,17 +6,43 @@ use anyhow::anyhow;
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     /// If a user task returns this error variant, it means that the user has reached a critical failure and wants the test to be stopped immediately.
+    /// Other errors only fail the iteration, which is counted while the scenario goes on.
     #[error(transparent)]
     TerminationError(anyhow::Error),
     /// Error variant which should be shown in the UI
     #[error(transparent)]
     GenericError(#[from] anyhow::Error),
+    /// Error classified with a user supplied code such as `timeout` or `500`, created with [`Error::tagged`].
+    #[error("{source}")]
+    Tagged {
+        code: Cow<'static, str>,
+        source: Box<Error>,
+    },
+    /// Call of a user wrapped with [`UserExt::with_timeout`](crate::user::UserExt::with_timeout)
+    /// took longer than the timeout.
+    #[error("user call timed out after {0:?}")]
+    Timeout(Duration),
 }
 
 impl Error {
     /// Error is of variant TerminationError
     pub fn is_termination_err(&self) -> bool {
-        matches!(self, Error::TerminationError(_))
+        match self {
+            Error::TerminationError(_) => true,
+            Error::GenericError(_) | Error::Timeout(_) => false,
+            Error::Tagged { source, .. } => source.is_termination_err(),
+        }
+    }
+
+    /// Class of this error used for error counters. This is the code of a tagged error,
+    /// otherwise name of the variant.
+    pub fn class(&self) -> &str {
+        match self {
+            Error::TerminationError(_) => "termination",
+            Error::GenericError(_) => "generic",
+            Error::Tagged { code, .. } => code,
+            Error::Timeout(_) => "timeout",
+        }
     }
 }
 
@@ -28,6 +54,20 @@ impl Error {
     pub fn termination(err: impl Into<Cow<'static, str>>) -> Self {
         Self::TerminationError(anyhow!(err.into()))
     }
+
+    /// Tag this error with a code so that it is counted separately from other errors.
+    ///
+    /// ```
+    /// # use rusher::error::Error;
+    /// let err = Error::new("request timed out").tagged("timeout");
+    /// assert_eq!(err.class(), "timeout");
+    /// ```
+    pub fn tagged(self, code: impl Into<Cow<'static, str>>) -> Self {
+        Self::Tagged {
+            code: code.into(),
+            source: Box::new(self),
+        }
+    }
 }
 
 #[cfg(feature = "reqwest")]
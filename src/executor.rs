@@ -1,11 +1,14 @@
 use std::{
     pin::Pin,
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
 use futures::Future;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
 use tracing::{event, Instrument, Level};
 
 use crate::{
@@ -18,8 +21,241 @@ use crate::{
 
 type ExecutorTask<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
 
+/// Delay before trying again to build a user, growing with every attempt.
+const BUILD_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Spawns user tasks on the current runtime, or round robin across dedicated
+/// runtimes when users are sharded.
+#[derive(Clone, Default)]
+pub(crate) struct UserSpawner {
+    shards: Option<Arc<Shards>>,
+    /// Run user calls without a task span, see [`Runner::raw_throughput`](crate::runner::Runner::raw_throughput).
+    raw: bool,
+    pause: Arc<Pause>,
+    scale: Arc<Scale>,
+}
+
+/// Gate holding users back from starting new iterations while the run is paused,
+/// see [`RunnerHandle::pause`](crate::runner::RunnerHandle::pause), or for good
+/// once it is stopped, see [`RunnerHandle::stop`](crate::runner::RunnerHandle::stop).
+#[derive(Debug, Default)]
+pub(crate) struct Pause {
+    paused: AtomicBool,
+    stopped: AtomicBool,
+    resumed: Notify,
+    aborted: AtomicBool,
+    abort: Notify,
+}
+
+impl Pause {
+    pub fn set(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+        if !paused {
+            self.resumed.notify_waiters();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Stop scheduling iterations for the rest of the run, waking paused users.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        self.resumed.notify_waiters();
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Stop the run and cancel iterations in flight.
+    pub fn abort(&self) {
+        self.aborted.store(true, Ordering::Relaxed);
+        self.stop();
+        self.abort.notify_waiters();
+    }
+
+    /// Resolves once the run is aborted.
+    pub async fn aborted(&self) {
+        loop {
+            let abort = self.abort.notified();
+            if self.aborted.load(Ordering::Relaxed) {
+                return;
+            }
+            abort.await;
+        }
+    }
+
+    /// Resolves once the run is not paused or has been stopped.
+    pub async fn wait(&self) {
+        loop {
+            let resumed = self.resumed.notified();
+            if !self.is_paused() || self.is_stopped() {
+                return;
+            }
+            resumed.await;
+        }
+    }
+
+    /// Waits while the run is paused, then whether another iteration may start.
+    pub async fn proceed(&self) -> bool {
+        self.wait().await;
+        !self.is_stopped()
+    }
+
+    /// Waits while the run is paused, then whether `end_time` is still ahead
+    /// and the run was not stopped.
+    pub async fn resumed_before(&self, end_time: Instant) -> bool {
+        self.proceed().await && Instant::now() < end_time
+    }
+}
+
+/// Target set while the run is in progress for the users of a ramping user executor or
+/// the rate of an arrival rate executor, see [`RunnerHandle::scale`](crate::runner::RunnerHandle::scale).
+/// It replaces the targets of the configured stages for the rest of the run.
+#[derive(Debug)]
+pub(crate) struct Scale {
+    /// `usize::MAX` while the configured stages are followed.
+    target: AtomicUsize,
+    changes: AtomicUsize,
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self {
+            target: AtomicUsize::new(usize::MAX),
+            changes: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Scale {
+    pub fn set(&self, target: usize) {
+        self.target.store(target, Ordering::Relaxed);
+        self.changes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn target(&self) -> Option<usize> {
+        Some(self.target.load(Ordering::Relaxed)).filter(|x| *x != usize::MAX)
+    }
+
+    /// Number of times the target was set, to tell whether it changed since.
+    pub fn changes(&self) -> usize {
+        self.changes.load(Ordering::Relaxed)
+    }
+}
+
+struct Shards {
+    handles: Vec<tokio::runtime::Handle>,
+    next: AtomicUsize,
+    // Runtime threads exit once these are dropped.
+    _shutdown: Vec<tokio::sync::oneshot::Sender<()>>,
+}
+
+impl UserSpawner {
+    /// Start `shards` runtimes, each on its own thread.
+    /// Runtimes are single threaded unless `worker_threads` is more than one.
+    pub fn sharded(shards: usize, worker_threads: usize) -> std::io::Result<Self> {
+        let mut handles = Vec::with_capacity(shards);
+        let mut shutdown = Vec::with_capacity(shards);
+        for index in 0..shards.max(1) {
+            let mut builder = if worker_threads > 1 {
+                let mut builder = tokio::runtime::Builder::new_multi_thread();
+                builder.worker_threads(worker_threads);
+                builder
+            } else {
+                tokio::runtime::Builder::new_current_thread()
+            };
+            let runtime = builder
+                .enable_all()
+                .thread_name(format!("{CRATE_NAME}-shard-{index}"))
+                .build()?;
+            let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+            handles.push(runtime.handle().clone());
+            std::thread::Builder::new()
+                .name(format!("{CRATE_NAME}-shard-{index}"))
+                .spawn(move || {
+                    runtime.block_on(async {
+                        let _ = rx.await;
+                    })
+                })?;
+            shutdown.push(tx);
+        }
+
+        Ok(Self {
+            shards: Some(Arc::new(Shards {
+                handles,
+                next: AtomicUsize::new(0),
+                _shutdown: shutdown,
+            })),
+            raw: false,
+            pause: Default::default(),
+            scale: Default::default(),
+        })
+    }
+
+    /// Skip creating a span for every user call.
+    pub fn raw(mut self, raw: bool) -> Self {
+        self.raw = raw;
+        self
+    }
+
+    /// Hold back new iterations of users while `pause` is set.
+    pub fn pause(mut self, pause: Arc<Pause>) -> Self {
+        self.pause = pause;
+        self
+    }
+
+    /// Follow the target of `scale` instead of the configured one once it is set.
+    pub fn scale(mut self, scale: Arc<Scale>) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+/// Span of a single user call, none in raw mode.
+fn task_span(raw: bool, user_id: usize) -> tracing::Span {
+    if raw {
+        tracing::Span::none()
+    } else {
+        tracing::span!(target: CRATE_NAME, tracing::Level::INFO, SPAN_TASK, user_id)
+    }
+}
+
+unsafe impl<T: Send + 'static> async_scoped::spawner::Spawner<T> for UserSpawner {
+    type FutureOutput = Result<T, tokio::task::JoinError>;
+    type SpawnHandle = tokio::task::JoinHandle<T>;
+
+    fn spawn<F: Future<Output = T> + Send + 'static>(&self, f: F) -> Self::SpawnHandle {
+        match &self.shards {
+            Some(shards) => {
+                let index = shards.next.fetch_add(1, Ordering::Relaxed) % shards.handles.len();
+                shards.handles[index].spawn(f)
+            }
+            None => tokio::task::spawn(f),
+        }
+    }
+}
+
+unsafe impl async_scoped::spawner::Blocker for UserSpawner {
+    fn block_on<T, F: Future<Output = T>>(&self, f: F) -> T {
+        async_scoped::spawner::use_tokio::Tokio.block_on(f)
+    }
+}
+
 pub trait Executor: Send {
     fn execute(&mut self, tx: crate::Sender<UserResult>) -> ExecutorTask<'_>;
+
+    /// Stop every user built by the executor, see [`User::stop`]. Called once after the
+    /// executor ran or was cancelled, or once the run is over if it never ran.
+    fn stop(&mut self) -> ExecutorTask<'_>;
+
+    /// Error building users once the executor started, which ended its scenario.
+    /// Only executors scaling up their users while running can fail this way.
+    fn take_build_error(&mut self) -> Option<Error> {
+        None
+    }
 }
 
 pub(crate) enum DataExecutor<'ctx, Ub: for<'a> AsyncUserBuilder<'a>> {
@@ -38,27 +274,35 @@ impl<'ctx, Ub: for<'a> AsyncUserBuilder<'a>> DataExecutor<'ctx, Ub> {
         datastore: &'ctx RuntimeDataStore,
         user_builder: &'ctx Ub,
         executor: logical::Executor,
+        retries: usize,
+        spawner: UserSpawner,
     ) -> Result<Self, Error> {
+        let factory = UserFactory {
+            datastore,
+            user_builder,
+            label: executor.to_string(),
+            retries,
+        };
         let s = match executor {
             logical::Executor::Once => {
-                let mut users = build_users(datastore, user_builder, 1).await?;
-                Self::Once(Once::new(users.pop().unwrap()))
+                let mut users = factory.build(1, 0).await?;
+                Self::Once(Once::new(users.pop().unwrap(), spawner))
             }
             logical::Executor::Constant { users, duration } => {
-                let users = build_users(datastore, user_builder, users).await?;
-                Self::Constant(Constant::new(users, duration))
+                let users = factory.build(users, 0).await?;
+                Self::Constant(Constant::new(users, duration, spawner))
             }
             logical::Executor::Shared {
                 users,
                 iterations,
                 duration,
             } => {
-                let users = build_users(datastore, user_builder, users).await?;
-                Self::Shared(SharedIterations::new(users, iterations, duration))
+                let users = factory.build(users, 0).await?;
+                Self::Shared(SharedIterations::new(users, iterations, duration, spawner))
             }
             logical::Executor::PerUser { users, iterations } => {
-                let users = build_users(datastore, user_builder, users).await?;
-                Self::PerUser(PerUserIteration::new(users, iterations))
+                let users = factory.build(users, 0).await?;
+                Self::PerUser(PerUserIteration::new(users, iterations, spawner))
             }
             logical::Executor::ConstantArrivalRate {
                 pre_allocate_users,
@@ -66,31 +310,31 @@ impl<'ctx, Ub: for<'a> AsyncUserBuilder<'a>> DataExecutor<'ctx, Ub> {
                 max_users,
                 duration,
             } => Self::ConstantArrivalRate(RampingArrivalRate::new(
-                datastore,
-                user_builder,
+                factory,
                 pre_allocate_users,
                 vec![(rate, duration)],
                 max_users,
+                spawner,
             )),
             logical::Executor::RampingUser {
                 pre_allocate_users,
                 stages,
             } => Self::RampingUser(RampingUser::new(
-                datastore,
-                user_builder,
+                factory,
                 stages,
                 pre_allocate_users,
+                spawner,
             )),
             logical::Executor::RampingArrivalRate {
                 pre_allocate_users,
                 max_users,
                 stages,
             } => Self::RampingArrivalRate(RampingArrivalRate::new(
-                datastore,
-                user_builder,
+                factory,
                 pre_allocate_users,
                 stages,
                 max_users,
+                spawner,
             )),
         };
 
@@ -114,15 +358,37 @@ where
             DataExecutor::RampingArrivalRate(exec) => exec.execute(tx),
         }
     }
+
+    fn stop(&mut self) -> ExecutorTask<'_> {
+        match self {
+            DataExecutor::Once(exec) => exec.stop(),
+            DataExecutor::Constant(exec) => exec.stop(),
+            DataExecutor::Shared(exec) => exec.stop(),
+            DataExecutor::PerUser(exec) => exec.stop(),
+            DataExecutor::RampingUser(exec) => exec.stop(),
+            DataExecutor::ConstantArrivalRate(exec) => exec.stop(),
+            DataExecutor::RampingArrivalRate(exec) => exec.stop(),
+        }
+    }
+
+    fn take_build_error(&mut self) -> Option<Error> {
+        match self {
+            DataExecutor::RampingUser(exec) => exec.take_build_error(),
+            DataExecutor::ConstantArrivalRate(exec) => exec.take_build_error(),
+            DataExecutor::RampingArrivalRate(exec) => exec.take_build_error(),
+            _ => None,
+        }
+    }
 }
 
 pub(crate) struct Once<U> {
-    user: U,
+    user: VirtualUser<U>,
+    spawner: UserSpawner,
 }
 
 impl<U> Once<U> {
-    fn new(user: U) -> Self {
-        Once { user }
+    fn new(user: VirtualUser<U>, spawner: UserSpawner) -> Self {
+        Once { user, spawner }
     }
 }
 
@@ -131,61 +397,85 @@ where
     U: User,
 {
     fn execute(&mut self, tx: crate::Sender<UserResult>) -> ExecutorTask<'_> {
-        let task = self.user.call();
+        let spawner = self.spawner.clone();
+        let raw = spawner.raw;
+        let pause = spawner.pause.clone();
+        let user = &mut self.user;
         let exec = async move {
-            let spawner = async_scoped::spawner::use_tokio::Tokio;
-            let mut scope = unsafe { async_scoped::TokioScope::create(spawner) };
+            let mut scope = unsafe { async_scoped::Scope::create(spawner.clone()) };
             event!(target: CRATE_NAME, Level::INFO, users = 1u64, users_max = 1u64);
             scope.spawn_cancellable(
                 async move {
-                    let _ = tx.send(user_call(task).await);
+                    if pause.proceed().await {
+                        user.start().await;
+                        let res = user_call(user.call()).instrument(task_span(raw, 0)).await;
+                        let _ = tx.send(res);
+                    }
                 }
-                .instrument(tracing::span!(target: CRATE_NAME, tracing::Level::INFO, SPAN_TASK)),
+                .in_current_span(),
                 || (),
             );
             let _ = scope.collect().await;
         };
         Box::pin(exec)
     }
+
+    fn stop(&mut self) -> ExecutorTask<'_> {
+        Box::pin(self.user.stop())
+    }
 }
 
 pub(crate) struct Constant<U> {
-    users: Vec<U>,
+    users: Vec<VirtualUser<U>>,
     duration: Duration,
+    spawner: UserSpawner,
 }
 
 impl<U> Constant<U> {
-    fn new(users: Vec<U>, duration: Duration) -> Self {
-        Self { users, duration }
+    fn new(users: Vec<VirtualUser<U>>, duration: Duration, spawner: UserSpawner) -> Self {
+        Self {
+            users,
+            duration,
+            spawner,
+        }
     }
 }
 
 impl<U: User> Executor for Constant<U> {
     fn execute(&mut self, tx: crate::Sender<UserResult>) -> ExecutorTask<'_> {
+        let spawner = self.spawner.clone();
+        let raw = spawner.raw;
+        let pause = spawner.pause.clone();
         let users_len = self.users.len();
         let total_duration_as_secs = self.duration.as_secs();
         let total_duration = self.duration;
 
         let end_time = Instant::now() + total_duration;
-        let tasks = self.users.iter_mut().map(move |user| {
-            let tx = tx.clone();
-            async move {
-                while std::time::Instant::now() < end_time {
-                    let res = user_call(user.call())
-                        .instrument(
-                            tracing::span!(target: CRATE_NAME, tracing::Level::INFO, SPAN_TASK),
-                        )
-                        .await;
-                    let _ = tx.send(res);
+        let tasks = self
+            .users
+            .iter_mut()
+            .enumerate()
+            .map(move |(user_id, user)| {
+                let tx = tx.clone();
+                let pause = pause.clone();
+                async move {
+                    while pause.resumed_before(end_time).await {
+                        if !user.think(Some(end_time)).await {
+                            break;
+                        }
+                        user.start().await;
+                        let res = user_call(user.call())
+                            .instrument(task_span(raw, user_id))
+                            .await;
+                        let _ = tx.send(res);
+                    }
                 }
-            }
-        });
+            });
 
         let task = async move {
             event!(target: CRATE_NAME, Level::INFO, users = users_len, users_max = users_len);
             event!(target: CRATE_NAME, Level::INFO, total_duration = total_duration_as_secs);
-            let spawner = async_scoped::spawner::use_tokio::Tokio;
-            let mut scope = unsafe { async_scoped::TokioScope::create(spawner) };
+            let mut scope = unsafe { async_scoped::Scope::create(spawner.clone()) };
             for task in tasks {
                 scope.spawn_cancellable(task.in_current_span(), || ());
             }
@@ -194,26 +484,39 @@ impl<U: User> Executor for Constant<U> {
 
         Box::pin(task)
     }
+
+    fn stop(&mut self) -> ExecutorTask<'_> {
+        Box::pin(stop_users(self.users.iter_mut()))
+    }
 }
 
 pub(crate) struct SharedIterations<U> {
-    users: Vec<U>,
+    users: Vec<VirtualUser<U>>,
     iterations: usize,
     duration: Duration,
+    spawner: UserSpawner,
 }
 
 impl<U: User> SharedIterations<U> {
-    fn new(users: Vec<U>, iterations: usize, duration: Duration) -> Self {
+    fn new(
+        users: Vec<VirtualUser<U>>,
+        iterations: usize,
+        duration: Duration,
+        spawner: UserSpawner,
+    ) -> Self {
         Self {
             users,
             iterations,
             duration,
+            spawner,
         }
     }
 }
 
 impl<U: User> SharedIterations<U> {
     fn execute(&mut self, tx: crate::Sender<UserResult>) -> ExecutorTask<'_> {
+        let spawner = self.spawner.clone();
+        let raw = spawner.raw;
         let users_len = self.users.len();
         let iterations = self.iterations;
         let total_duration_as_secs = self.duration.as_secs();
@@ -223,25 +526,27 @@ impl<U: User> SharedIterations<U> {
             event!(target: CRATE_NAME, Level::INFO, users = users_len, users_max = users_len);
             event!(target: CRATE_NAME, Level::INFO, total_duration = total_duration_as_secs);
             let iterations_completed = AtomicUsize::new(0);
-            let tasks = self.users.iter_mut().map(|user| {
+            let tasks = self.users.iter_mut().enumerate().map(|(user_id, user)| {
                 let tx = tx.clone();
                 let iterations_completed = &iterations_completed;
+                let pause = &spawner.pause;
                 async move {
-                    while std::time::Instant::now() < end_time {
+                    while pause.resumed_before(end_time).await {
                         let current_iteration =
                             iterations_completed.fetch_add(1, Ordering::Relaxed);
-                        if current_iteration >= iterations {
+                        if current_iteration >= iterations || !user.think(Some(end_time)).await {
                             break;
                         }
-                        let _ = tx.send(user_call(user.call()).instrument(
-                            tracing::span!(target: CRATE_NAME, tracing::Level::INFO, SPAN_TASK),
-                        ).await);
+                        user.start().await;
+                        let _ = tx.send(
+                            user_call(user.call())
+                                .instrument(task_span(raw, user_id))
+                                .await,
+                        );
                     }
                 }
             });
-
-            let spawner = async_scoped::spawner::use_tokio::Tokio;
-            let mut scope = unsafe { async_scoped::TokioScope::create(spawner) };
+            let mut scope = unsafe { async_scoped::Scope::create(spawner.clone()) };
             for task in tasks {
                 scope.spawn_cancellable(task.in_current_span(), || ());
             }
@@ -250,33 +555,53 @@ impl<U: User> SharedIterations<U> {
 
         Box::pin(task)
     }
+
+    fn stop(&mut self) -> ExecutorTask<'_> {
+        Box::pin(stop_users(self.users.iter_mut()))
+    }
 }
 
 pub(crate) struct PerUserIteration<U> {
-    users: Vec<U>,
+    users: Vec<VirtualUser<U>>,
     iterations: usize,
+    spawner: UserSpawner,
 }
 
 impl<U> PerUserIteration<U> {
-    fn new(users: Vec<U>, iterations: usize) -> Self {
-        Self { users, iterations }
+    fn new(users: Vec<VirtualUser<U>>, iterations: usize, spawner: UserSpawner) -> Self {
+        Self {
+            users,
+            iterations,
+            spawner,
+        }
     }
 }
 
 impl<U: User> Executor for PerUserIteration<U> {
     fn execute(&mut self, tx: crate::Sender<UserResult>) -> ExecutorTask<'_> {
-        let Self { users, iterations } = self;
+        let Self {
+            users,
+            iterations,
+            spawner,
+        } = self;
+        let spawner = spawner.clone();
+        let raw = spawner.raw;
         let users_len = users.len();
         let iterations = *iterations;
-        let tasks = users.iter_mut().map(move |user| {
+        let pause = spawner.pause.clone();
+        let tasks = users.iter_mut().enumerate().map(move |(user_id, user)| {
             let tx = tx.clone();
+            let pause = pause.clone();
             async move {
                 for _ in 0..iterations {
+                    user.think(None).await;
+                    if !pause.proceed().await {
+                        break;
+                    }
+                    user.start().await;
                     let _ = tx.send(
                         user_call(user.call())
-                            .instrument(
-                                tracing::span!(target: CRATE_NAME, tracing::Level::INFO, SPAN_TASK),
-                            )
+                            .instrument(task_span(raw, user_id))
                             .await,
                     );
                 }
@@ -286,8 +611,7 @@ impl<U: User> Executor for PerUserIteration<U> {
         let task = async move {
             event!(target: CRATE_NAME, Level::INFO, users = users_len, users_max = users_len);
             event!(target: CRATE_NAME, Level::INFO, total_iteration = iterations);
-            let spawner = async_scoped::spawner::use_tokio::Tokio;
-            let mut scope = unsafe { async_scoped::TokioScope::create(spawner) };
+            let mut scope = unsafe { async_scoped::Scope::create(spawner.clone()) };
             for task in tasks {
                 scope.spawn_cancellable(task.in_current_span(), || ());
             }
@@ -296,27 +620,36 @@ impl<U: User> Executor for PerUserIteration<U> {
 
         Box::pin(task)
     }
+
+    fn stop(&mut self) -> ExecutorTask<'_> {
+        Box::pin(stop_users(self.users.iter_mut()))
+    }
 }
 
-pub(crate) struct RampingUser<'ctx, Ub> {
-    datastore: &'ctx RuntimeDataStore,
-    user_builder: &'ctx Ub,
+pub(crate) struct RampingUser<'ctx, Ub: AsyncUserBuilder<'ctx>> {
+    factory: UserFactory<'ctx, Ub>,
+    /// Users built so far, kept once the executor is done to be stopped.
+    users: Vec<VirtualUser<Ub::Output>>,
     pre_allocate_users: usize,
     stages: Vec<(usize, Duration)>,
+    spawner: UserSpawner,
+    build_error: Option<Error>,
 }
 
-impl<'ctx, Ub> RampingUser<'ctx, Ub> {
+impl<'ctx, Ub: AsyncUserBuilder<'ctx>> RampingUser<'ctx, Ub> {
     fn new(
-        datastore: &'ctx RuntimeDataStore,
-        user_builder: &'ctx Ub,
+        factory: UserFactory<'ctx, Ub>,
         stages: Vec<(usize, Duration)>,
         initial_users: usize,
+        spawner: UserSpawner,
     ) -> Self {
         Self {
-            datastore,
-            user_builder,
+            factory,
+            users: Vec::new(),
             pre_allocate_users: initial_users,
             stages,
+            spawner,
+            build_error: None,
         }
     }
 }
@@ -326,77 +659,124 @@ where
     Ub: for<'a> AsyncUserBuilder<'a>,
 {
     fn execute(&mut self, tx: crate::Sender<UserResult>) -> ExecutorTask<'_> {
-        let datastore = self.datastore;
-        let user_builder = self.user_builder;
+        let factory = &self.factory;
         let pre_allocated_users = self.pre_allocate_users;
+        let spawner = self.spawner.clone();
+        let raw = spawner.raw;
         let stages = &*self.stages;
         let total_duration: u64 = stages.iter().map(|(_, duration)| duration.as_secs()).sum();
 
+        let users = &mut self.users;
+        let build_error = &mut self.build_error;
+
         let task = async move {
             event!(target: CRATE_NAME, Level::INFO, total_duration = total_duration);
-            let mut users = build_users(datastore, user_builder, pre_allocated_users)
-                .await
-                .unwrap();
+            *users = match factory.build(pre_allocated_users, 0).await {
+                Ok(users) => users,
+                Err(err) => return build_failed(err, build_error, &tx),
+            };
             event!(target: CRATE_NAME, Level::INFO, users = users.len(), users_max = pre_allocated_users);
 
             for (index, (target_users, duration)) in stages.iter().enumerate() {
-                event!(target: CRATE_NAME, Level::INFO, stage = index + 1, stages = stages.len(), stage_duration = duration.as_secs());
-                event!(target: CRATE_NAME, Level::INFO, users = users.len(), users_max = target_users.max(&pre_allocated_users));
-
-                let len = users.len();
-                if len < *target_users {
-                    users.extend(
-                        build_users(datastore, user_builder, target_users - len)
-                            .await
-                            .unwrap(),
-                    );
+                if spawner.pause.is_stopped() {
+                    break;
                 }
-                event!(target: CRATE_NAME, Level::INFO, users = users.len(), users_max = target_users.max(&pre_allocated_users));
-
+                event!(target: CRATE_NAME, Level::INFO, stage = index + 1, stages = stages.len(), stage_duration = duration.as_secs());
                 let end_time = Instant::now() + *duration;
-                let tasks = users.iter_mut().map(|user| {
-                    let tx = tx.clone();
-                    async move {
-                        while Instant::now() < end_time {
-                            let _ = tx.send(user_call(user.call()).instrument(tracing::span!(target: CRATE_NAME, tracing::Level::INFO, SPAN_TASK)).await);
+                // Run again with the new number of users whenever the target is scaled.
+                loop {
+                    let changes = spawner.scale.changes();
+                    let scaled = spawner.scale.target();
+                    let target_users = scaled.unwrap_or(*target_users);
+                    event!(target: CRATE_NAME, Level::INFO, users = users.len(), users_max = target_users.max(pre_allocated_users));
+
+                    let len = users.len();
+                    if len < target_users {
+                        match factory.build(target_users - len, len).await {
+                            Ok(built) => users.extend(built),
+                            Err(err) => return build_failed(err, build_error, &tx),
                         }
                     }
-                });
-                let spawner = async_scoped::spawner::use_tokio::Tokio;
-                let mut scope = unsafe { async_scoped::TokioScope::create(spawner) };
-                tasks.into_iter().for_each(|task| {
-                    scope.spawn_cancellable(task.in_current_span(), || ());
-                });
-                let _ = scope.collect().await;
+                    // Users built for earlier stages keep running unless scaled down.
+                    let active = scaled.unwrap_or(users.len());
+                    stop_users(users.iter_mut().skip(active).filter(|x| x.started)).await;
+                    event!(target: CRATE_NAME, Level::INFO, users = active, users_max = target_users.max(pre_allocated_users));
+
+                    let tasks = users
+                        .iter_mut()
+                        .take(active)
+                        .enumerate()
+                        .map(|(user_id, user)| {
+                            let tx = tx.clone();
+                            let pause = &spawner.pause;
+                            let scale = &spawner.scale;
+                            async move {
+                                while scale.changes() == changes
+                                    && pause.resumed_before(end_time).await
+                                {
+                                    if !user.think(Some(end_time)).await {
+                                        break;
+                                    }
+                                    user.start().await;
+                                    let _ = tx.send(
+                                        user_call(user.call())
+                                            .instrument(task_span(raw, user_id))
+                                            .await,
+                                    );
+                                }
+                            }
+                        });
+                    let mut scope = unsafe { async_scoped::Scope::create(spawner.clone()) };
+                    tasks.into_iter().for_each(|task| {
+                        scope.spawn_cancellable(task.in_current_span(), || ());
+                    });
+                    let _ = scope.collect().await;
+                    if spawner.scale.changes() == changes {
+                        break;
+                    }
+                }
             }
         };
 
         Box::pin(task)
     }
+
+    fn stop(&mut self) -> ExecutorTask<'_> {
+        Box::pin(stop_users(self.users.iter_mut()))
+    }
+
+    fn take_build_error(&mut self) -> Option<Error> {
+        self.build_error.take()
+    }
 }
 
-pub(crate) struct RampingArrivalRate<'ctx, Ub> {
-    datastore: &'ctx RuntimeDataStore,
-    user_builder: &'ctx Ub,
+pub(crate) struct RampingArrivalRate<'ctx, Ub: AsyncUserBuilder<'ctx>> {
+    factory: UserFactory<'ctx, Ub>,
+    /// Users built so far, kept once the executor is done to be stopped.
+    users: Vec<Mutex<VirtualUser<Ub::Output>>>,
     pre_allocate_users: usize,
     stages: Vec<(Rate, Duration)>,
     max_users: usize,
+    spawner: UserSpawner,
+    build_error: Option<Error>,
 }
 
-impl<'ctx, Ub> RampingArrivalRate<'ctx, Ub> {
+impl<'ctx, Ub: AsyncUserBuilder<'ctx>> RampingArrivalRate<'ctx, Ub> {
     fn new(
-        datastore: &'ctx RuntimeDataStore,
-        user_builder: &'ctx Ub,
+        factory: UserFactory<'ctx, Ub>,
         pre_allocate_users: usize,
         stages: Vec<(Rate, Duration)>,
         max_users: usize,
+        spawner: UserSpawner,
     ) -> Self {
         Self {
-            datastore,
-            user_builder,
+            factory,
+            users: Vec::new(),
             pre_allocate_users,
             stages,
             max_users,
+            spawner,
+            build_error: None,
         }
     }
 }
@@ -406,64 +786,71 @@ where
     Ub: for<'a> AsyncUserBuilder<'a>,
 {
     fn execute(&mut self, tx: crate::Sender<UserResult>) -> ExecutorTask<'_> {
-        let datastore = self.datastore;
-        let user_builder = self.user_builder;
+        let factory = &self.factory;
         let pre_allocated_users = self.pre_allocate_users;
         let max_users = self.max_users;
+        let spawner = self.spawner.clone();
+        let raw = spawner.raw;
         let stages = &*self.stages;
         let total_duration: u64 = stages.iter().map(|(_, duration)| duration.as_secs()).sum();
 
+        let users = &mut self.users;
+        let build_error = &mut self.build_error;
+
         let task = async move {
             event!(target: CRATE_NAME, Level::INFO, total_duration = total_duration);
-            let mut users: Vec<_> = build_users(datastore, user_builder, pre_allocated_users)
-                .await
-                .unwrap()
-                .into_iter()
-                .map(Mutex::new)
-                .collect();
+            *users = match factory.build(pre_allocated_users, 0).await {
+                Ok(users) => users.into_iter().map(Mutex::new).collect(),
+                Err(err) => return build_failed(err, build_error, &tx),
+            };
             event!(target: CRATE_NAME, Level::INFO, users = users.len(), users_max = pre_allocated_users);
 
-            for (index, (Rate(rate, time_unit), duration)) in stages.iter().enumerate() {
+            for (index, (stage, duration)) in stages.iter().enumerate() {
+                if spawner.pause.is_stopped() {
+                    break;
+                }
+                let Rate(stage_rate, time_unit) = stage;
                 let end_time = Instant::now() + *duration;
-                event!(target: CRATE_NAME, Level::INFO, stage = index + 1, stages = stages.len(), stage_duration = duration.as_secs());
+                let arrival_interval = stage.interval().map_or(0, |x| x.as_nanos() as u64);
+                event!(target: CRATE_NAME, Level::INFO, stage = index + 1, stages = stages.len(), stage_duration = duration.as_secs(), arrival_interval);
 
-                while Instant::now() < end_time {
+                while spawner.pause.resumed_before(end_time).await {
+                    let rate = spawner.scale.target().unwrap_or(*stage_rate);
                     let next_rate_check_time = Instant::now() + *time_unit;
                     let mut current_rate = 0;
+                    let mut scope = unsafe { async_scoped::Scope::create(spawner.clone()) };
 
-                    let spawner = async_scoped::spawner::use_tokio::Tokio;
-                    let mut scope = unsafe { async_scoped::TokioScope::create(spawner) };
-
-                    let mut user_iter = users.iter().cycle().filter_map(|x| x.try_lock().ok());
+                    let mut user_iter = users
+                        .iter()
+                        .enumerate()
+                        .cycle()
+                        .filter_map(|(user_id, x)| Some((user_id, x.try_lock().ok()?)));
 
                     let now = Instant::now();
-                    while now < next_rate_check_time && now < end_time && current_rate < *rate {
-                        let mut user = user_iter.next().unwrap();
+                    while now < next_rate_check_time && now < end_time && current_rate < rate {
+                        let (user_id, mut user) = user_iter.next().unwrap();
                         let tx = tx.clone();
                         let task = async move {
-                            let _ = tx.send(user_call(user.call()).await);
+                            user.start().await;
+                            let span = task_span(raw, user_id);
+                            let _ = tx.send(user_call(user.call()).instrument(span).await);
                         };
-                        let span =
-                            tracing::span!(target: CRATE_NAME, tracing::Level::INFO, SPAN_TASK);
-                        scope.spawn_cancellable(task.instrument(span), || ());
+                        scope.spawn_cancellable(task.in_current_span(), || ());
                         current_rate += 1;
                     }
 
                     scope.collect().await;
                     drop(scope);
 
-                    if current_rate < *rate && users.len() < max_users {
-                        users.extend(
-                            build_users(datastore, user_builder, rate - current_rate)
-                                .await
-                                .unwrap()
-                                .into_iter()
-                                .map(Mutex::new),
-                        );
+                    if current_rate < rate && users.len() < max_users {
+                        match factory.build(rate - current_rate, users.len()).await {
+                            Ok(built) => users.extend(built.into_iter().map(Mutex::new)),
+                            Err(err) => return build_failed(err, build_error, &tx),
+                        }
                     }
                     event!(target: CRATE_NAME, Level::INFO, users = users.len(), users_max = pre_allocated_users);
 
-                    if Instant::now() <= end_time || current_rate < *rate {
+                    if Instant::now() <= end_time || current_rate < rate {
                         // Sleep until to make sure we wait before next set of task;
                         tokio::time::sleep_until(next_rate_check_time.into()).await;
                     }
@@ -473,6 +860,21 @@ where
 
         Box::pin(task)
     }
+
+    fn stop(&mut self) -> ExecutorTask<'_> {
+        Box::pin(stop_users(self.users.iter_mut().map(Mutex::get_mut)))
+    }
+
+    fn take_build_error(&mut self) -> Option<Error> {
+        self.build_error.take()
+    }
+}
+
+/// Keep an error building users while running to be returned by the runner,
+/// and end the scenario with it.
+fn build_failed(err: Error, build_error: &mut Option<Error>, tx: &crate::Sender<UserResult>) {
+    let _ = tx.send(Err(Error::termination(err.to_string())));
+    *build_error = Some(err);
 }
 
 async fn user_call<'a>(
@@ -480,20 +882,110 @@ async fn user_call<'a>(
 ) -> Result<(), crate::error::Error> {
     let res = task.await;
     if let Err(ref err) = res {
-        event!(name: "error", target: CRATE_NAME, Level::INFO, err = %err)
+        event!(name: "error", target: CRATE_NAME, Level::INFO, err = %err, class = err.class())
     }
     res
 }
 
-async fn build_users<'a, Ub: AsyncUserBuilder<'a>>(
-    store: &'a RuntimeDataStore,
-    user_builder: &'a Ub,
-    count: usize,
-) -> Result<Vec<<Ub as AsyncUserBuilder<'a>>::Output>, Error> {
-    let mut res = vec![];
-    for _ in 0..count {
-        let user = user_builder.build(store).await?;
-        res.push(user)
+/// Stop `users` concurrently, see [`User::stop`].
+async fn stop_users<'a, U: User + 'a>(users: impl Iterator<Item = &'a mut VirtualUser<U>>) {
+    futures::future::join_all(users.map(VirtualUser::stop)).await;
+}
+
+/// User built by an executor, along with whether it was started and stopped.
+pub(crate) struct VirtualUser<U> {
+    user: U,
+    started: bool,
+    stopped: bool,
+}
+
+impl<U: User> VirtualUser<U> {
+    /// Run [`User::start`] before the first iteration, and again after the user was stopped.
+    /// Awaited outside of the span of the iteration, so that it does not count toward its
+    /// duration.
+    async fn start(&mut self) {
+        if !self.started {
+            self.started = true;
+            self.stopped = false;
+            self.user.start().await;
+        }
+    }
+
+    /// Run [`User::stop`] unless the user is stopped already. A user scheduled again
+    /// afterwards is started again.
+    async fn stop(&mut self) {
+        if !self.stopped {
+            self.stopped = true;
+            self.started = false;
+            self.user.stop().await;
+        }
+    }
+
+    /// Run [`User::think`] between two iterations, also outside of their span. Cut short at
+    /// `end_time`, returns whether it is still ahead.
+    async fn think(&mut self, end_time: Option<Instant>) -> bool {
+        if !self.started {
+            return true;
+        }
+        match end_time {
+            Some(end_time) => tokio::time::timeout_at(end_time.into(), self.user.think())
+                .await
+                .is_ok(),
+            None => {
+                self.user.think().await;
+                true
+            }
+        }
+    }
+
+    fn call(&mut self) -> impl Future<Output = UserResult> + Send + '_ {
+        self.user.call()
+    }
+}
+
+/// Builds the users of an executor, trying again when the builder fails.
+pub(crate) struct UserFactory<'ctx, Ub> {
+    datastore: &'ctx RuntimeDataStore,
+    user_builder: &'ctx Ub,
+    /// Executor the users are built for, named in errors.
+    label: String,
+    retries: usize,
+}
+
+impl<'ctx, Ub: AsyncUserBuilder<'ctx>> UserFactory<'ctx, Ub> {
+    /// Build `count` more users after the `built` ones of the executor. Each user is tried
+    /// again up to the configured number of retries, the last error ends the execution.
+    async fn build(
+        &self,
+        count: usize,
+        built: usize,
+    ) -> Result<Vec<VirtualUser<Ub::Output>>, Error> {
+        let mut res = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut attempt = 0;
+            let user = loop {
+                match self.user_builder.build(self.datastore).await {
+                    Ok(user) => break user,
+                    Err(_) if attempt < self.retries => {
+                        attempt += 1;
+                        tokio::time::sleep(BUILD_RETRY_DELAY * attempt as u32).await;
+                    }
+                    Err(err) => {
+                        return Err(Error::termination(format!(
+                            "{}: failed to build a user on attempt {}, {} users built: {err}",
+                            self.label,
+                            attempt + 1,
+                            built + res.len()
+                        )))
+                    }
+                }
+            };
+            res.push(VirtualUser {
+                user,
+                started: false,
+                stopped: false,
+            })
+        }
+        Ok(res)
     }
-    Ok(res)
 }
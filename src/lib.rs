@@ -16,7 +16,7 @@ By simulating multiple users accessing the application simultaneously, developer
 ensure the system can handle expected traffic.
 
 * *Virtual Users* - Implementaion of User trait defines how to simulate this interaction with your application in ways similar to a real users. For more detail explaination look at [`User`](user::User).
-Users of this framework are suppose to implement [`User`](user::User) trait for each variant of user along with their user builder function.
+  Users of this framework are suppose to implement [`User`](user::User) trait for each variant of user along with their user builder function.
 
 * *User Builder* - A user builder function defines how to create a User instance. A User can borrow shared data from a [RuntimeDataStore](data::RuntimeDataStore).
 
@@ -31,6 +31,8 @@ Users of this framework are suppose to implement [`User`](user::User) trait for
 More examples are available in the [github repo](https://github.com/trueleo/rusher/examples)
 
 ```no_run
+# #[cfg(all(feature = "reqwest", feature = "web"))]
+# mod example {
 use std::time::Duration;
 
 use rusher::client::reqwest::Client;
@@ -87,14 +89,16 @@ async fn main() {
 
     Runner::new(scenarios).enable_web(true).run().await.unwrap();
 }
-
+# }
+# fn main() {}
 ```
 
 # Features
 - `tui` Enables tui mode, allowing for user to look at live feed of execution in terminal.
 - `web` Enables web mode which contains a simple axum server along with a inbuilt UI for looking at updates.
 - `serde` - Enable serialization with serde.
-- `reqwest` - Wrapper client type for reqwest.
+- `otel` - Export of user iterations as OpenTelemetry traces.
+- `reqwest` - Wrapper client type for reqwest that records duration, status and bytes of every request.
 
 # Architecture
 
@@ -137,34 +141,72 @@ To emit a custom metric from within a user task, use [`event`](https://docs.rs/t
 any other fields in the event is captures as the attributes for this metric which also includes all parent span's attributes.
 
 ```no_run
+# use rusher::USER_TASK;
+# use tracing::{event, Level};
 event!(name: "failure.counter", target: USER_TASK, Level::INFO, value = 1u64);
 ```
 
-There are three type of event signals that you can emit from within a user's task.
+The [`counter!`], [`gauge!`], [`histogram!`], [`rate!`], [`trend!`], [`bytes!`] and [`ratio!`] macros emit events of this shape
+and check at compile time that the value is of a type permitted for the metric.
+
+```no_run
+rusher::counter!("failure", 1, endpoint = "/login");
+```
+
+There are seven type of event signals that you can emit from within a user's task.
 * `counter` - Sums all values emitted during a run and shows a counter. only accepts `u64`
 * `gauge` - Shows timeseries value over fixed sample range as graph. Permitted types are `u64`, `i64`, `f64` or Durations as nanos (`u128`)
 * `histogram` - Captures *p50*, *p90*, *p95*, *p99* values from sampled values. Permitted types are `f64` or Duration as nanos (`u128`)
+* `rate` - Per second rate of the summed values over the run and over the last 10 seconds, such as requests per second. only accepts `u64`
+* `trend` - Same as `histogram` along with count, mean, standard deviation, min and max of sampled values. Permitted types are `f64` or Duration as nanos (`u128`)
+* `bytes` - Total bytes transferred and throughput per second, shown with binary units such as `KiB` or `MiB`. only accepts `u64`
+* `ratio` - Fraction of `true` values over the run and over the last 10 seconds, such as cache hits. only accepts `bool`
 
 Any span(s) inside of a user task is converted to a histogram metric which would track duration of its execution as its value.
+[`user::group`] opens such a span for a named step of a user flow, giving a latency breakdown per step in a `group_duration` histogram.
+
+Every executor also counts its iterations in an `iterations_total` counter, with a `result` attribute
+of `ok` or `error` depending on the outcome of the user call. Failed iterations are also counted in
+an `errors_total` counter with a `class` attribute, see [`Error::tagged`](error::Error::tagged) to classify errors.
+
+## Checks
+[`check!`] records whether a condition held without failing the iteration. Outcomes are counted
+in a `checks` counter and summarized with a pass percentage per check.
+
+```no_run
+# let status = 200;
+rusher::check!("status is 200", status == 200);
+```
 
 */
 
 #[cfg(any(feature = "tui", feature = "web"))]
 pub mod app;
+mod checkpoint;
 
-#[cfg(feature = "reqwest")]
 pub mod client;
 
 pub mod data;
+#[cfg(feature = "serde")]
+pub mod digests;
 pub mod error;
 mod executor;
 pub mod logical;
+mod macros;
 pub mod runner;
+pub mod state;
+pub mod thresholds;
 pub mod tracing;
 pub mod user;
 
 pub type UserResult = Result<(), crate::error::Error>;
 
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::macros::{CounterValue, GaugeValue, HistogramValue, RatioValue};
+    pub use tracing;
+}
+
 pub mod prelude {
     pub use crate::data::RuntimeDataStore;
     pub use crate::logical::Execution;
@@ -1,7 +1,9 @@
 use std::{borrow::Cow, fmt::Write, time::Duration};
 
+use chrono::{DateTime, Utc};
+
 use crate::{
-    data::DatastoreModifier, executor::DataExecutor, runner::ExecutionRuntimeCtx,
+    data::DatastoreModifier, error::Error, executor::DataExecutor, runner::ExecutionRuntimeCtx,
     user::AsyncUserBuilder,
 };
 
@@ -9,6 +11,14 @@ use crate::{
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Rate(pub usize, pub Duration);
 
+impl Rate {
+    /// Time between two arrivals at this rate, None for a rate of zero.
+    pub fn interval(&self) -> Option<Duration> {
+        self.1
+            .checked_div(u32::try_from(self.0).unwrap_or(u32::MAX))
+    }
+}
+
 impl From<Rate> for (usize, Duration) {
     fn from(value: Rate) -> Self {
         (value.0, value.1)
@@ -86,18 +96,136 @@ impl std::fmt::Display for Executor {
     }
 }
 
+impl Executor {
+    /// Time the executor runs for according to its configuration, None when it ends after a
+    /// number of iterations instead.
+    pub(crate) fn duration(&self) -> Option<Duration> {
+        match self {
+            Executor::Once | Executor::PerUser { .. } => None,
+            Executor::Constant { duration, .. }
+            | Executor::Shared { duration, .. }
+            | Executor::ConstantArrivalRate { duration, .. } => Some(*duration),
+            Executor::RampingUser { stages, .. } => Some(stages.iter().map(|x| x.1).sum()),
+            Executor::RampingArrivalRate { stages, .. } => Some(stages.iter().map(|x| x.1).sum()),
+        }
+    }
+
+    /// Work left for this executor after `iterations` were completed in `elapsed` time.
+    /// Returns None if there is nothing left to run.
+    pub(crate) fn remaining(&self, iterations: u64, elapsed: Duration) -> Option<Executor> {
+        let completed = iterations as usize;
+        let remaining_duration =
+            |duration: Duration| Some(duration.saturating_sub(elapsed)).filter(|x| !x.is_zero());
+        let remaining_iterations =
+            |total: usize, done: usize| Some(total.saturating_sub(done)).filter(|&x| x > 0);
+
+        let executor = match self.clone() {
+            Executor::Once => {
+                remaining_iterations(1, completed)?;
+                Executor::Once
+            }
+            Executor::Constant { users, duration } => Executor::Constant {
+                users,
+                duration: remaining_duration(duration)?,
+            },
+            Executor::Shared {
+                users,
+                iterations,
+                duration,
+            } => Executor::Shared {
+                users,
+                iterations: remaining_iterations(iterations, completed)?,
+                duration: remaining_duration(duration)?,
+            },
+            Executor::PerUser { users, iterations } => Executor::PerUser {
+                users,
+                iterations: remaining_iterations(iterations, completed / users.max(1))?,
+            },
+            Executor::ConstantArrivalRate {
+                pre_allocate_users,
+                rate,
+                max_users,
+                duration,
+            } => Executor::ConstantArrivalRate {
+                pre_allocate_users,
+                rate,
+                max_users,
+                duration: remaining_duration(duration)?,
+            },
+            Executor::RampingUser {
+                pre_allocate_users,
+                stages,
+            } => Executor::RampingUser {
+                pre_allocate_users,
+                stages: remaining_stages(stages, elapsed)?,
+            },
+            Executor::RampingArrivalRate {
+                pre_allocate_users,
+                max_users,
+                stages,
+            } => Executor::RampingArrivalRate {
+                pre_allocate_users,
+                max_users,
+                stages: remaining_stages(stages, elapsed)?,
+            },
+        };
+
+        Some(executor)
+    }
+}
+
+/// Correction of iteration durations for coordinated omission.
+///
+/// When the target stalls, users waiting on slow calls do not issue the calls they would have
+/// made in the meantime, so the worst latencies are under-sampled. With a correction in place
+/// iteration durations are recorded in the
+/// [`ITERATION_DURATION`](crate::tracing::ITERATION_DURATION) histogram, and a duration longer
+/// than the expected interval is back-filled with the samples that were missed, the same way
+/// HdrHistogram based tools do. The histogram carries a `corrected` attribute.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OmissionCorrection {
+    /// Expect a call every [interval](Rate::interval) of the rate of the running stage, for
+    /// arrival rate executors.
+    ArrivalRate,
+    /// Expect a call from a user every interval, for executors with a fixed number of users.
+    Interval(Duration),
+}
+
+/// Drop stages that are fully elapsed and shorten the stage that was running.
+fn remaining_stages<T>(
+    stages: Vec<(T, Duration)>,
+    mut elapsed: Duration,
+) -> Option<Vec<(T, Duration)>> {
+    let stages: Vec<_> = stages
+        .into_iter()
+        .filter_map(|(target, duration)| {
+            let left = duration.saturating_sub(elapsed);
+            elapsed = elapsed.saturating_sub(duration);
+            (!left.is_zero()).then_some((target, left))
+        })
+        .collect();
+    (!stages.is_empty()).then_some(stages)
+}
+
 #[async_trait::async_trait]
 pub(crate) trait ExecutionProvider {
     fn config(&self) -> &Executor;
+    /// Correction of iteration durations, if any.
+    fn omission_correction(&self) -> Option<OmissionCorrection>;
+    /// Tag user metrics with the id of the user that emitted them.
+    fn user_id_attribute(&self) -> bool;
     async fn execution<'a>(
         &'a self,
         ctx: &'a mut ExecutionRuntimeCtx,
-    ) -> Box<dyn crate::executor::Executor + 'a>;
+        executor: Executor,
+    ) -> Result<Box<dyn crate::executor::Executor + 'a>, Error>;
 }
 
 pub struct Scenario<'env> {
     pub(crate) label: Cow<'static, str>,
     pub(crate) execution_provider: Vec<Box<dyn ExecutionProvider + 'env>>,
+    pub(crate) start_at: Option<DateTime<Utc>>,
+    pub(crate) tags: Vec<Cow<'static, str>>,
 }
 
 impl<'env> Scenario<'env> {
@@ -108,9 +236,36 @@ impl<'env> Scenario<'env> {
         Self {
             label: label.into(),
             execution_provider: vec![Box::new(execution)],
+            start_at: None,
+            tags: Vec::new(),
         }
     }
 
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Tag this scenario so it can be selected with [`Runner::filter_scenarios`](crate::runner::Runner::filter_scenarios).
+    pub fn with_tag(mut self, tag: impl Into<Cow<'static, str>>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        self.tags.iter().map(|x| x.as_ref())
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags().any(|x| x == tag)
+    }
+
+    /// Hold this scenario until the given wall-clock time.
+    /// If the time has already passed when the scenario is reached, it starts right away.
+    pub fn start_at(mut self, start_at: DateTime<Utc>) -> Self {
+        self.start_at = Some(start_at);
+        self
+    }
+
     pub fn with_executor<Ub>(mut self, execution: Execution<'env, Ub>) -> Self
     where
         Ub: for<'a> AsyncUserBuilder<'a> + 'env,
@@ -124,6 +279,9 @@ pub struct Execution<'env, Ub> {
     user_builder: Ub,
     datastore_modifiers: Vec<Box<dyn DatastoreModifier + 'env>>,
     executor: Executor,
+    omission_correction: Option<OmissionCorrection>,
+    user_id_attribute: bool,
+    build_retries: usize,
 }
 
 impl<'env, Ub> Execution<'env, Ub> {
@@ -132,6 +290,9 @@ impl<'env, Ub> Execution<'env, Ub> {
             user_builder,
             datastore_modifiers: vec![],
             executor,
+            omission_correction: None,
+            user_id_attribute: false,
+            build_retries: 0,
         }
     }
 }
@@ -142,6 +303,9 @@ impl Execution<'static, ()> {
             user_builder: (),
             datastore_modifiers: Vec::new(),
             executor: Executor::Once,
+            omission_correction: None,
+            user_id_attribute: false,
+            build_retries: 0,
         }
     }
 
@@ -153,6 +317,9 @@ impl Execution<'static, ()> {
             user_builder,
             executor: self.executor,
             datastore_modifiers: self.datastore_modifiers,
+            omission_correction: self.omission_correction,
+            user_id_attribute: self.user_id_attribute,
+            build_retries: self.build_retries,
         }
     }
 }
@@ -172,6 +339,31 @@ where
         self
     }
 
+    /// Record iteration durations of this execution corrected for coordinated omission.
+    pub fn with_omission_correction(mut self, correction: OmissionCorrection) -> Self {
+        self.omission_correction = Some(correction);
+        self
+    }
+
+    /// Tag every metric emitted from within a user call with a [`user_id`](crate::tracing::USER_ID)
+    /// attribute, the index of the user within this execution which stays the same across iterations.
+    /// Useful to tell whether outliers come from specific users or the data fed to them.
+    ///
+    /// Each user adds a series to every metric, so keep this off for runs with many users
+    /// or use [`MetricsConfig::cardinality_limit`](crate::tracing::task_event::MetricsConfig::cardinality_limit).
+    pub fn with_user_id_attribute(mut self) -> Self {
+        self.user_id_attribute = true;
+        self
+    }
+
+    /// Try again up to `retries` times to build a user when the user builder returns an error,
+    /// such as a login refused while the target is busy. Defaults to none, the first error
+    /// ends the run.
+    pub fn with_build_retries(mut self, retries: usize) -> Self {
+        self.build_retries = retries;
+        self
+    }
+
     pub fn to_scenario(self, label: impl Into<Cow<'static, str>>) -> Scenario<'env> {
         Scenario::new(label, self)
     }
@@ -186,19 +378,97 @@ where
         &self.executor
     }
 
+    fn omission_correction(&self) -> Option<OmissionCorrection> {
+        self.omission_correction
+    }
+
+    fn user_id_attribute(&self) -> bool {
+        self.user_id_attribute
+    }
+
     async fn execution<'a>(
         &'a self,
         ctx: &'a mut ExecutionRuntimeCtx,
-    ) -> Box<dyn crate::executor::Executor + 'a> {
+        executor: Executor,
+    ) -> Result<Box<dyn crate::executor::Executor + 'a>, Error> {
         for modifiers in self.datastore_modifiers.iter() {
             ctx.modify(&**modifiers).await;
         }
         let user_builder = &self.user_builder;
-        let executor = self.executor.clone();
-        Box::new(
-            DataExecutor::<Ub>::new(ctx.datastore_mut(), user_builder, executor)
-                .await
-                .unwrap(),
-        ) as Box<dyn crate::executor::Executor + '_>
+        let spawner = ctx.spawner();
+        let executor = DataExecutor::<Ub>::new(
+            ctx.datastore_mut(),
+            user_builder,
+            executor,
+            self.build_retries,
+            spawner,
+        )
+        .await?;
+        Ok(Box::new(executor) as Box<dyn crate::executor::Executor + '_>)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Executor, Rate};
+
+    #[test]
+    fn remaining_work() {
+        let secs = Duration::from_secs;
+
+        let shared = Executor::Shared {
+            users: 2,
+            iterations: 10,
+            duration: secs(10),
+        };
+        assert!(matches!(
+            shared.remaining(4, secs(3)),
+            Some(Executor::Shared { iterations: 6, duration, .. }) if duration == secs(7)
+        ));
+        assert!(shared.remaining(10, secs(3)).is_none());
+        assert!(shared.remaining(4, secs(10)).is_none());
+
+        let per_user = Executor::PerUser {
+            users: 2,
+            iterations: 5,
+        };
+        assert!(matches!(
+            per_user.remaining(4, secs(1)),
+            Some(Executor::PerUser { iterations: 3, .. })
+        ));
+
+        let ramping = Executor::RampingArrivalRate {
+            pre_allocate_users: 1,
+            max_users: 10,
+            stages: vec![
+                (Rate(1, secs(1)), secs(5)),
+                (Rate(2, secs(1)), secs(5)),
+                (Rate(3, secs(1)), secs(5)),
+            ],
+        };
+        let Some(Executor::RampingArrivalRate { stages, .. }) = ramping.remaining(0, secs(7))
+        else {
+            panic!("ramping executor has stages left")
+        };
+        assert_eq!(stages.len(), 2);
+        assert_eq!(stages[0].0 .0, 2);
+        assert_eq!(stages[0].1, secs(3));
+        assert!(ramping.remaining(0, secs(15)).is_none());
+        assert!(Executor::Once.remaining(1, secs(0)).is_none());
+    }
+
+    #[test]
+    fn rate_interval() {
+        let secs = Duration::from_secs;
+        assert_eq!(
+            Rate(10, secs(1)).interval(),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(Rate(0, secs(1)).interval(), None);
+        // Rates beyond u32 saturate instead of wrapping around.
+        let rate = Rate(u32::MAX as usize + 1, secs(u32::MAX as u64));
+        assert_eq!(rate.interval(), Some(secs(1)));
     }
 }
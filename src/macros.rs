@@ -0,0 +1,232 @@
+//! Macros emitting metric events in the shape expected by [`TracerLayer`](crate::tracing::TracerLayer).
+//!
+//! Value traits are only implemented for the types a metric accepts,
+//! so passing a wrong kind of value fails to compile instead of being dropped at runtime.
+
+use std::time::Duration;
+
+/// Value accepted by [`counter!`](crate::counter), [`rate!`](crate::rate) and [`bytes!`](crate::bytes).
+pub trait CounterValue {
+    type Field: tracing::Value;
+    fn into_field(self) -> Self::Field;
+}
+
+/// Value accepted by [`gauge!`](crate::gauge).
+pub trait GaugeValue {
+    type Field: tracing::Value;
+    fn into_field(self) -> Self::Field;
+}
+
+/// Value accepted by [`histogram!`](crate::histogram) and [`trend!`](crate::trend).
+pub trait HistogramValue {
+    type Field: tracing::Value;
+    fn into_field(self) -> Self::Field;
+}
+
+/// Value accepted by [`ratio!`](crate::ratio).
+pub trait RatioValue {
+    type Field: tracing::Value;
+    fn into_field(self) -> Self::Field;
+}
+
+impl CounterValue for u64 {
+    type Field = u64;
+    fn into_field(self) -> u64 {
+        self
+    }
+}
+
+impl GaugeValue for u64 {
+    type Field = u64;
+    fn into_field(self) -> u64 {
+        self
+    }
+}
+
+impl GaugeValue for i64 {
+    type Field = i64;
+    fn into_field(self) -> i64 {
+        self
+    }
+}
+
+impl GaugeValue for f64 {
+    type Field = f64;
+    fn into_field(self) -> f64 {
+        self
+    }
+}
+
+// Durations are recorded as nanos.
+impl GaugeValue for Duration {
+    type Field = u128;
+    fn into_field(self) -> u128 {
+        self.as_nanos()
+    }
+}
+
+impl RatioValue for bool {
+    type Field = bool;
+    fn into_field(self) -> bool {
+        self
+    }
+}
+
+impl HistogramValue for f64 {
+    type Field = f64;
+    fn into_field(self) -> f64 {
+        self
+    }
+}
+
+impl HistogramValue for Duration {
+    type Field = u128;
+    fn into_field(self) -> u128 {
+        self.as_nanos()
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __metric {
+    ($ty:literal, $trait:ident, $name:literal, $value:expr $(, $($fields:tt)*)?) => {
+        $crate::__private::tracing::event!(
+            name: concat!($name, ".", $ty),
+            target: $crate::USER_TASK,
+            $crate::__private::tracing::Level::INFO,
+            value = $crate::__private::$trait::into_field($value)
+            $(, $($fields)*)?
+        )
+    };
+}
+
+/// Add to a counter from within a user task. Value must be a `u64`.
+/// Any extra fields are recorded as attributes of the metric.
+///
+/// ```no_run
+/// rusher::counter!("failure", 1, endpoint = "/login");
+/// ```
+///
+/// Values of other types are rejected.
+///
+/// ```compile_fail
+/// rusher::counter!("failure", 1.5);
+/// ```
+#[macro_export]
+macro_rules! counter {
+    ($name:literal, $($rest:tt)*) => {
+        $crate::__metric!("counter", CounterValue, $name, $($rest)*)
+    };
+}
+
+/// Set a gauge from within a user task. Value must be a `u64`, `i64`, `f64` or [`Duration`](std::time::Duration).
+/// Any extra fields are recorded as attributes of the metric.
+///
+/// ```no_run
+/// rusher::gauge!("queue_depth", 12u64);
+/// ```
+#[macro_export]
+macro_rules! gauge {
+    ($name:literal, $($rest:tt)*) => {
+        $crate::__metric!("gauge", GaugeValue, $name, $($rest)*)
+    };
+}
+
+/// Record a value in a histogram from within a user task. Value must be a `f64` or [`Duration`](std::time::Duration).
+/// Any extra fields are recorded as attributes of the metric.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// rusher::histogram!("latency", Duration::from_millis(12), endpoint = "/login");
+/// ```
+#[macro_export]
+macro_rules! histogram {
+    ($name:literal, $($rest:tt)*) => {
+        $crate::__metric!("histogram", HistogramValue, $name, $($rest)*)
+    };
+}
+
+/// Add to a rate from within a user task. Value must be a `u64`.
+/// Any extra fields are recorded as attributes of the metric.
+///
+/// ```no_run
+/// rusher::rate!("bytes_sent", 512);
+/// ```
+#[macro_export]
+macro_rules! rate {
+    ($name:literal, $($rest:tt)*) => {
+        $crate::__metric!("rate", CounterValue, $name, $($rest)*)
+    };
+}
+
+/// Record the outcome of a boolean check from within a user task and return it.
+/// A failed check is counted but does not fail the iteration.
+///
+/// Outcomes are added to the [`CHECKS`](crate::tracing::CHECKS) counter with the name of the check
+/// in a `check` attribute and `pass` or `fail` in a `result` attribute.
+/// Any extra fields are recorded as attributes of the metric.
+///
+/// ```no_run
+/// let status = 200;
+/// if !rusher::check!("status is 200", status == 200) {
+///     // handle the failure
+/// }
+/// ```
+#[macro_export]
+macro_rules! check {
+    ($name:literal, $condition:expr $(, $($fields:tt)*)?) => {{
+        let passed: bool = $condition;
+        $crate::__private::tracing::event!(
+            name: "checks.counter",
+            target: $crate::USER_TASK,
+            $crate::__private::tracing::Level::INFO,
+            value = 1u64,
+            check = $name,
+            result = if passed { "pass" } else { "fail" }
+            $(, $($fields)*)?
+        );
+        passed
+    }};
+}
+
+/// Record a value in a trend from within a user task. Value must be a `f64` or [`Duration`](std::time::Duration).
+/// Any extra fields are recorded as attributes of the metric.
+///
+/// ```no_run
+/// rusher::trend!("payload_size", 1.5);
+/// ```
+#[macro_export]
+macro_rules! trend {
+    ($name:literal, $($rest:tt)*) => {
+        $crate::__metric!("trend", HistogramValue, $name, $($rest)*)
+    };
+}
+
+/// Add transferred bytes from within a user task. Value must be a `u64`.
+/// The metric reports the total amount and the throughput per second, formatted with binary units.
+/// Any extra fields are recorded as attributes of the metric.
+///
+/// ```no_run
+/// rusher::bytes!("upload", 512, endpoint = "/files");
+/// ```
+#[macro_export]
+macro_rules! bytes {
+    ($name:literal, $($rest:tt)*) => {
+        $crate::__metric!("bytes", CounterValue, $name, $($rest)*)
+    };
+}
+
+/// Record a boolean outcome from within a user task, such as a cache hit.
+/// The metric reports the fraction of `true` values over the run and over the last window.
+/// Any extra fields are recorded as attributes of the metric.
+///
+/// ```no_run
+/// let cached = true;
+/// rusher::ratio!("cache_hit", cached, cache = "session");
+/// ```
+#[macro_export]
+macro_rules! ratio {
+    ($name:literal, $($rest:tt)*) => {
+        $crate::__metric!("ratio", RatioValue, $name, $($rest)*)
+    };
+}
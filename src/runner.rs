@@ -1,48 +1,264 @@
 use std::borrow::Cow;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
+mod handle;
+pub mod progress;
+pub mod report;
+pub mod summary;
+#[cfg(all(feature = "reqwest", feature = "serde"))]
+pub mod webhook;
+
+pub use handle::{ExecutorMetrics, MetricsHandle, RunnerHandle, ScaleTarget};
+pub use report::{Reporter, RunReport};
+
+use crate::checkpoint::Checkpoint;
 use crate::data::DatastoreModifier;
 use crate::data::RuntimeDataStore;
-use crate::executor::Executor;
+use crate::executor::{Executor, UserSpawner};
+use crate::thresholds::{Threshold, Thresholds};
+use crate::tracing::message::Message;
+use crate::tracing::sink::MetricSink;
+use crate::tracing::MetricRegistry;
+use crate::tracing::{filter::MetricFilter, task_event::MetricsConfig};
 use crate::{CRATE_NAME, SPAN_EXEC, SPAN_SCENARIO};
 
 use crate::logical;
 
 use async_scoped::{self, Scope};
+use chrono::{DateTime, Utc};
 use tracing::{event, Instrument};
 
+/// Label of a scenario with its executors, each along with its index and configuration.
+type RuntimeScenario<'a> = (
+    Cow<'a, str>,
+    Vec<(usize, &'a logical::Executor, Box<dyn Executor + 'a>)>,
+);
+
 /// The Runner struct is the top level struct for managing and executing series of logical scenarios asynchronously.
 pub struct Runner<'env> {
     logical: LogicalContext<'env>,
+    registry: Arc<MetricRegistry>,
     #[cfg(feature = "tui")]
     enable_tui: bool,
+    #[cfg(feature = "tui")]
+    tui_config: crate::app::tui::TuiConfig,
     #[cfg(feature = "web")]
     enable_web: bool,
+    #[cfg(feature = "web")]
+    web_config: crate::app::web::WebConfig,
+    #[cfg(feature = "serde")]
+    checkpoint: Option<(std::path::PathBuf, std::time::Duration)>,
+    #[cfg(feature = "serde")]
+    resume_from: Option<std::path::PathBuf>,
+    #[cfg(feature = "serde")]
+    dump_interval: Option<std::time::Duration>,
+    #[cfg(feature = "serde")]
+    save_digests: Option<std::path::PathBuf>,
+    start_at: Option<DateTime<Utc>>,
+    shards: Option<RuntimeShards>,
+    log_progress: Option<std::time::Duration>,
+    summary: bool,
+    #[cfg(feature = "serde")]
+    json_result: bool,
+    log_file: Option<crate::tracing::log_file::LogFile>,
+    slowest_iterations: usize,
+    raw_throughput: bool,
+    metrics_config: MetricsConfig,
+    metric_filter: MetricFilter,
+    thresholds: Vec<Threshold>,
+    sinks: std::sync::Mutex<Vec<Box<dyn MetricSink>>>,
+    reporters: std::sync::Mutex<Vec<Box<dyn Reporter>>>,
+    #[cfg(feature = "otel")]
+    trace_exporter: std::sync::Mutex<Option<crate::tracing::otel::TraceExporter>>,
+    handle: RunnerHandle,
+}
+
+/// Dedicated tokio runtimes that users are partitioned across.
+///
+/// Every runtime runs on its own threads, which keeps one busy user from starving
+/// others of a shared scheduler. Metrics from all runtimes still flow into the same
+/// tracing layer.
+#[derive(Debug, Clone, Copy)]
+pub struct RuntimeShards {
+    shards: usize,
+    worker_threads: usize,
+}
+
+impl RuntimeShards {
+    /// `shards` single threaded runtimes.
+    pub fn new(shards: usize) -> Self {
+        Self {
+            shards,
+            worker_threads: 1,
+        }
+    }
+
+    /// One single threaded runtime per available core.
+    pub fn per_core() -> Self {
+        Self::new(std::thread::available_parallelism().map_or(1, |x| x.get()))
+    }
+
+    /// Run each shard on a small multi threaded runtime instead.
+    pub fn worker_threads(mut self, worker_threads: usize) -> Self {
+        self.worker_threads = worker_threads;
+        self
+    }
 }
 
 impl<'env> Runner<'env> {
     // Create new instance of Runner with a [Config](crate::config::Config) and list of [Scenario](create::logical::Scenario)
     pub fn new(scenarios: Vec<logical::Scenario<'env>>) -> Runner<'env> {
+        let registry = Arc::<MetricRegistry>::default();
+        let handle = RunnerHandle::new(registry.clone(), &scenarios);
         Self {
             logical: LogicalContext { scenarios },
+            registry,
             #[cfg(feature = "tui")]
             enable_tui: false,
+            #[cfg(feature = "tui")]
+            tui_config: Default::default(),
             #[cfg(feature = "web")]
             enable_web: false,
+            #[cfg(feature = "web")]
+            web_config: Default::default(),
+            #[cfg(feature = "serde")]
+            checkpoint: None,
+            #[cfg(feature = "serde")]
+            resume_from: None,
+            #[cfg(feature = "serde")]
+            dump_interval: None,
+            #[cfg(feature = "serde")]
+            save_digests: None,
+            start_at: None,
+            shards: None,
+            log_progress: None,
+            summary: true,
+            #[cfg(feature = "serde")]
+            json_result: false,
+            log_file: None,
+            slowest_iterations: 0,
+            raw_throughput: false,
+            metrics_config: MetricsConfig::default(),
+            metric_filter: MetricFilter::default(),
+            thresholds: Vec::new(),
+            sinks: Default::default(),
+            reporters: Default::default(),
+            #[cfg(feature = "otel")]
+            trace_exporter: Default::default(),
+            handle,
         }
     }
 
-    // Spawn the runner
+    /// Spawn the runner.
+    /// Returns an error once the run is over if any [`threshold`](Self::threshold) failed,
+    /// or if the users of an executor could not be built. When that happens before the run
+    /// starts no scenario runs, when an executor adding users while running fails to build
+    /// them its scenario ends and the scenarios after it do not run.
     pub async fn run(&self) -> Result<(), crate::error::Error> {
+        if self.logical.scenarios.is_empty() {
+            return Err(crate::error::Error::new("no scenarios to run"));
+        }
+
+        #[cfg(feature = "serde")]
+        let resume = self.load_checkpoint()?;
+        #[cfg(not(feature = "serde"))]
+        let resume: Option<Checkpoint> = None;
+
+        let raw = self.raw_throughput;
+        if raw && !self.thresholds.is_empty() {
+            return Err(crate::error::Error::new(
+                "thresholds can not be evaluated without metrics in raw throughput mode",
+            ));
+        }
+
+        let log_layer = match &self.log_file {
+            Some(log_file) if !raw => Some(log_file.layer()?),
+            _ => None,
+        };
+
+        let mut consumers = Vec::new();
+
+        let progress_handle = (!raw)
+            .then(|| self.spawn_progress(&mut consumers))
+            .flatten();
+
+        let reports = !self.reporters.lock().unwrap().is_empty();
+        #[cfg(feature = "serde")]
+        let reports = reports || self.json_result;
+        let summary_handle =
+            (!raw && (self.summary || reports)).then(|| self.spawn_summary(&mut consumers));
+
+        let iterations = self.sinks.lock().unwrap().iter().any(|x| x.iterations())
+            || summary_handle.is_some() && self.slowest_iterations > 0;
+        let sink_handles = if raw {
+            Vec::new()
+        } else {
+            self.spawn_sinks(&mut consumers)
+        };
+
+        #[cfg(all(unix, feature = "serde"))]
+        let dump_handle = self.spawn_dump_on_signal();
+
+        #[cfg(feature = "serde")]
+        let periodic_dump_handle = self.spawn_periodic_dump();
+
         #[cfg(feature = "tui")]
-        let tui_handle = self.spawn_tui();
+        let tui_handle = (!raw).then(|| self.spawn_tui(&mut consumers)).flatten();
 
         #[cfg(feature = "web")]
-        let web_handle = self.spawn_web();
+        let web_handle = (!raw).then(|| self.spawn_web(&mut consumers)).flatten();
+
+        #[cfg(feature = "serde")]
+        let checkpoint_handle = (!raw)
+            .then(|| self.spawn_checkpoint(&mut consumers, resume.as_ref()))
+            .flatten();
+
+        let thresholds = Arc::new(Thresholds::new(self.thresholds.clone()));
+        let collected =
+            raw || self.install_tracer(consumers, thresholds.clone(), iterations, log_layer);
+
+        let pause = self.handle.pause_gate();
+        let spawner = match self.shards {
+            Some(shards) => UserSpawner::sharded(shards.shards, shards.worker_threads)
+                .map_err(anyhow::Error::from)?,
+            None => UserSpawner::default(),
+        }
+        .raw(raw)
+        .pause(pause.clone());
+
+        let mut runtime_ctx = self.create_contexts(&spawner);
+        let mut scenarios = Vec::new();
+        let mut build_error = self
+            .runtime_scenarios(&mut scenarios, &mut runtime_ctx, resume.as_ref())
+            .await
+            .err();
+        if let Some(err) = &build_error {
+            event!(name: "termination_error", target: CRATE_NAME, tracing::Level::INFO, err = %err);
+        }
+
+        if let Some(start_at) = self.start_at {
+            sleep_until(start_at).await;
+        }
 
-        let mut runtime_ctx = self.create_contexts();
-        let mut scenarios = self.runtime_scenarios(&mut runtime_ctx).await;
+        let raw_counts = RawCounts::default();
+        let raw_start = std::time::Instant::now();
 
+        // Scenarios before this one had their users stopped.
+        let mut stopped = 0;
         for (scenario_index, (scenario_name, scenario)) in scenarios.iter_mut().enumerate() {
+            if pause.is_stopped() || build_error.is_some() {
+                break;
+            }
+            if scenario.is_empty() {
+                // Every executor of this scenario finished before resuming.
+                continue;
+            }
+
+            if let Some(start_at) = self.logical.scenarios[scenario_index].start_at {
+                sleep_until(start_at).await;
+            }
+
             let span = tracing::span!(target: CRATE_NAME, tracing::Level::INFO, SPAN_SCENARIO, name = scenario_name.as_ref(), id = scenario_index as u64);
             let _entered = span.enter();
 
@@ -52,23 +268,91 @@ impl<'env> Runner<'env> {
             // gather user_results from every executor.
             let (user_result_tx, user_result_rx) = crate::channel();
 
-            for (executor_index, (executor_name, executor)) in scenario.iter_mut().enumerate() {
-                let span = tracing::span!(target: CRATE_NAME, parent: &span, tracing::Level::INFO, SPAN_EXEC, name = %executor_name, id = executor_index as u64);
+            for (executor_index, executor_name, executor) in scenario.iter_mut() {
+                let provider =
+                    &self.logical.scenarios[scenario_index].execution_provider[*executor_index];
+                // Arrival rate executors report the interval of every stage they start.
+                let (expected_interval, arrival_correction) = match provider.omission_correction() {
+                    Some(logical::OmissionCorrection::Interval(x)) if !x.is_zero() => {
+                        (Some(x.as_nanos() as u64), false)
+                    }
+                    Some(logical::OmissionCorrection::ArrivalRate) => (None, true),
+                    _ => (None, false),
+                };
+                let user_ids = provider.user_id_attribute();
+                let span = tracing::span!(target: CRATE_NAME, parent: &span, tracing::Level::INFO, SPAN_EXEC, name = %executor_name, id = *executor_index as u64, expected_interval, arrival_correction, user_ids);
                 let task = executor.execute(user_result_tx.clone());
                 scope.spawn_cancellable(task.instrument(span.clone()), || ());
             }
 
             drop(user_result_tx);
-            if has_user_terminated(user_result_rx).await {
+            let terminated = tokio::select! {
+                terminated = has_user_terminated(user_result_rx, raw.then_some(&raw_counts)) => terminated,
+                threshold = thresholds.aborted() => {
+                    event!(name: "termination_error", target: CRATE_NAME, tracing::Level::INFO, err = %format!("threshold `{threshold}` failed"));
+                    true
+                }
+                _ = pause.aborted() => {
+                    event!(name: "termination_error", target: CRATE_NAME, tracing::Level::INFO, err = %"run aborted");
+                    true
+                }
+            };
+            if terminated {
                 scope.cancel();
-                break;
             } else {
                 Scope::collect(&mut scope).await;
             }
+            drop(scope);
+            build_error = scenario
+                .iter_mut()
+                .find_map(|(_, _, executor)| executor.take_build_error())
+                .map(|err| crate::error::Error::termination(format!("{scenario_name} / {err}")));
+            stop_users(scenario).await;
+            stopped = scenario_index + 1;
+            if terminated {
+                break;
+            }
+        }
+        for (_, scenario) in scenarios.iter_mut().skip(stopped) {
+            stop_users(scenario).await;
         }
 
         event!(name: "runner_exit", target: CRATE_NAME, tracing::Level::INFO, "Exit test");
 
+        if raw {
+            raw_counts.print(raw_start.elapsed());
+        }
+
+        if let Some(handle) = progress_handle {
+            let _ = handle.await;
+        }
+
+        for handle in sink_handles {
+            let _ = handle.await;
+        }
+
+        #[cfg(all(unix, feature = "serde"))]
+        if let Some(handle) = dump_handle {
+            handle.abort();
+        }
+
+        #[cfg(feature = "serde")]
+        if let Some(handle) = periodic_dump_handle {
+            handle.abort();
+            // Final snapshot holds the complete run.
+            if let Err(err) = self.handle.dump().await {
+                tracing::warn!("failed to dump stats: {err}");
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        if let Some(path) = &self.save_digests {
+            let digests = crate::digests::collect(&self.registry, &self.logical.scenarios);
+            if let Err(err) = crate::digests::write(path, &digests) {
+                tracing::warn!("failed to save digests to {}: {err}", path.display());
+            }
+        }
+
         #[cfg(feature = "tui")]
         if let Some(handle) = tui_handle {
             let _ = handle.join();
@@ -79,37 +363,195 @@ impl<'env> Runner<'env> {
             let _ = handle.await;
         }
 
+        #[cfg(feature = "serde")]
+        if let Some(handle) = checkpoint_handle {
+            let _ = handle.await;
+        }
+
+        let summary = match summary_handle {
+            // Without the tracer the summary is empty.
+            Some(handle) if collected => handle.await.ok(),
+            _ => None,
+        };
+        if let Some(mut summary) = summary {
+            if self.summary {
+                summary.print();
+            }
+            if reports {
+                let report = RunReport::new(&mut summary, &self.registry, thresholds.results());
+                let reporters = std::mem::take(&mut *self.reporters.lock().unwrap());
+                #[cfg(feature = "serde")]
+                let json_result = self.json_result;
+                let _ = tokio::task::spawn_blocking(move || {
+                    for mut reporter in reporters {
+                        if let Err(err) = reporter.report(&report) {
+                            tracing::warn!("failed to write report: {err}");
+                        }
+                    }
+                    #[cfg(feature = "serde")]
+                    if json_result {
+                        println!("{}", report::json::result_line(&report));
+                    }
+                })
+                .await;
+            }
+        }
+
+        if let Some(err) = build_error {
+            return Err(err);
+        }
+
+        let failed: Vec<_> = thresholds
+            .results()
+            .into_iter()
+            .filter(|x| !x.passed)
+            .map(|x| x.threshold)
+            .collect();
+        if !failed.is_empty() {
+            return Err(crate::error::Error::new(format!(
+                "thresholds failed: {}",
+                failed.join(", ")
+            )));
+        }
+
         Ok(())
     }
 
+    /// Build the executors of every scenario into `scenarios` along with their index.
+    /// Executors with no work left in the checkpoint being resumed are left out.
+    /// Stops at the first executor failing to build its users, those built before it are kept
+    /// to be stopped.
     async fn runtime_scenarios<'a>(
         &'a self,
+        scenarios: &mut Vec<RuntimeScenario<'a>>,
         runtime_ctx: &'a mut [Vec<ExecutionRuntimeCtx>],
-    ) -> Vec<(
-        Cow<str>,
-        Vec<(&'a logical::Executor, Box<dyn Executor + '_>)>,
-    )> {
-        let mut scenarios = Vec::new();
+        resume: Option<&Checkpoint>,
+    ) -> Result<(), crate::error::Error> {
         let runtime_ctx_mut = runtime_ctx.iter_mut().map(|x| x.iter_mut());
-        for (logical_scenario, context) in self.logical.scenarios.iter().zip(runtime_ctx_mut) {
+        for (scenario_index, (logical_scenario, context)) in self
+            .logical
+            .scenarios
+            .iter()
+            .zip(runtime_ctx_mut)
+            .enumerate()
+        {
             let mut scenario = Vec::new();
-            for (exec, context) in logical_scenario.execution_provider.iter().zip(context) {
-                scenario.push((exec.config(), exec.execution(context).await))
+            for (executor_index, (exec, context)) in logical_scenario
+                .execution_provider
+                .iter()
+                .zip(context)
+                .enumerate()
+            {
+                let config = match resume {
+                    Some(checkpoint) => {
+                        match checkpoint.remaining(scenario_index, executor_index, exec.config()) {
+                            Some(config) => config,
+                            None => continue,
+                        }
+                    }
+                    None => exec.config().clone(),
+                };
+                match exec.execution(context, config).await {
+                    Ok(executor) => scenario.push((executor_index, exec.config(), executor)),
+                    Err(err) => {
+                        scenarios.push((logical_scenario.label.clone(), scenario));
+                        return Err(crate::error::Error::termination(format!(
+                            "{} / {err}",
+                            logical_scenario.label
+                        )));
+                    }
+                }
             }
             scenarios.push((logical_scenario.label.clone(), scenario))
         }
-        scenarios
+        Ok(())
     }
 
-    fn create_contexts(&self) -> Vec<Vec<ExecutionRuntimeCtx>> {
+    /// Install the tracing layer that feeds messages to every consumer.
+    /// Returns false when another global subscriber is already set, the metrics of the run are
+    /// not collected then.
+    fn install_tracer(
+        &self,
+        consumers: Vec<crate::Sender<Message>>,
+        thresholds: Arc<Thresholds>,
+        iterations: bool,
+        log_layer: Option<
+            Box<dyn tracing_subscriber::Layer<tracing_subscriber::Registry> + Send + Sync>,
+        >,
+    ) -> bool {
+        #[cfg(feature = "otel")]
+        let exporter = self.trace_exporter.lock().unwrap().take();
+        #[cfg(not(feature = "otel"))]
+        let exporter: Option<()> = None;
+        #[cfg(feature = "serde")]
+        let dumps = self.handle.has_dump_path() || self.save_digests.is_some();
+        #[cfg(not(feature = "serde"))]
+        let dumps = false;
+        let report = !self.reporters.lock().unwrap().is_empty();
+        #[cfg(feature = "serde")]
+        let report = report || self.json_result;
+        if consumers.is_empty()
+            && thresholds.is_empty()
+            && exporter.is_none()
+            && !dumps
+            && !report
+            && log_layer.is_none()
+        {
+            return true;
+        }
+
+        let tracer = crate::tracing::TracerLayer::new(consumers)
+            .with_registry(self.registry.clone())
+            .with_metrics_config(self.metrics_config)
+            .with_filter(self.metric_filter.clone())
+            .with_iterations(iterations)
+            .with_thresholds(thresholds);
+        // The tracer disables events of other targets for the whole subscriber, which a log file
+        // has to receive. A per layer filter limits the tracer to the events of this crate instead.
+        let (tracer, filtered) = match log_layer {
+            Some(_) => {
+                let filter = tracing_subscriber::filter::filter_fn(|metadata| {
+                    matches!(metadata.target(), crate::USER_TASK | crate::CRATE_NAME)
+                });
+                (
+                    None,
+                    Some(tracing_subscriber::Layer::with_filter(tracer, filter)),
+                )
+            }
+            None => (Some(tracer), None),
+        };
+        let subscriber = tracing_subscriber::layer::SubscriberExt::with(
+            tracing_subscriber::Registry::default(),
+            log_layer,
+        );
+        let subscriber = tracing_subscriber::layer::SubscriberExt::with(subscriber, tracer);
+        let subscriber = tracing_subscriber::layer::SubscriberExt::with(subscriber, filtered);
+        #[cfg(feature = "otel")]
+        let subscriber = tracing_subscriber::layer::SubscriberExt::with(subscriber, exporter);
+
+        if tracing::subscriber::set_global_default(subscriber).is_err() {
+            tracing::warn!(
+                "a global tracing subscriber is already set, metrics of the run are not collected"
+            );
+            return false;
+        }
+        true
+    }
+
+    fn create_contexts(&self, spawner: &UserSpawner) -> Vec<Vec<ExecutionRuntimeCtx>> {
         self.logical
             .scenarios
             .iter()
-            .map(|scenario| {
+            .enumerate()
+            .map(|(scenario_index, scenario)| {
                 scenario
                     .execution_provider
                     .iter()
-                    .map(|_| ExecutionRuntimeCtx::new())
+                    .enumerate()
+                    .map(|(executor_index, _)| {
+                        let scale = self.handle.scale_gate(scenario_index, executor_index);
+                        ExecutionRuntimeCtx::new(spawner.clone().scale(scale))
+                    })
                     .collect()
             })
             .collect()
@@ -119,83 +561,533 @@ impl<'env> Runner<'env> {
         &self.logical.scenarios
     }
 
+    /// Arm the runner to begin at a precise wall-clock time.
+    /// Datastores and users are prepared right away, only the execution waits.
+    pub fn start_at(mut self, start_at: DateTime<Utc>) -> Self {
+        self.start_at = Some(start_at);
+        self
+    }
+
+    /// Partition users across dedicated runtimes instead of the runtime calling [`run`](Self::run).
+    pub fn shard_runtimes(mut self, shards: RuntimeShards) -> Self {
+        self.shards = Some(shards);
+        self
+    }
+
+    /// Keep only the scenarios for which `filter` returns true, for example to run
+    /// a subset selected through an environment variable.
+    ///
+    /// ```no_run
+    /// # use rusher::prelude::*;
+    /// # fn scenarios() -> Vec<Scenario<'static>> { vec![] }
+    /// # let scenarios = scenarios();
+    /// let tag = std::env::var("SCENARIO_TAG").unwrap_or_default();
+    /// let runner = Runner::new(scenarios).filter_scenarios(|scenario| scenario.has_tag(&tag));
+    /// ```
+    pub fn filter_scenarios(
+        mut self,
+        filter: impl FnMut(&logical::Scenario<'env>) -> bool,
+    ) -> Self {
+        self.logical.scenarios.retain(filter);
+        self.handle.set_scenarios(&self.logical.scenarios);
+        self
+    }
+
+    /// Handle for interacting with the run while it is in progress.
+    pub fn handle(&self) -> RunnerHandle {
+        self.handle.clone()
+    }
+
+    /// Handle for polling current metric values while the run is in progress.
+    pub fn metrics_handle(&self) -> MetricsHandle {
+        self.handle.metrics()
+    }
+
+    /// Path where [`RunnerHandle::dump`] writes a JSON snapshot of current metrics.
+    /// On Unix a snapshot is also written whenever the process receives `SIGUSR1`.
+    #[cfg(feature = "serde")]
+    pub fn dump_stats_to(self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.handle.set_dump_path(path.into());
+        self
+    }
+
+    /// Write a snapshot to the path of [`dump_stats_to`](Self::dump_stats_to) every `interval`
+    /// and once more at the end of the run, so a run that is killed still leaves recent metrics on disk.
+    #[cfg(feature = "serde")]
+    pub fn dump_stats_every(
+        mut self,
+        path: impl Into<std::path::PathBuf>,
+        interval: std::time::Duration,
+    ) -> Self {
+        self.dump_interval = Some(interval);
+        self.dump_stats_to(path)
+    }
+
+    /// Save the digest of every histogram to `path` once the run is over, for
+    /// [`digests::merge_files`](crate::digests::merge_files) to combine percentiles of several runs or workers.
+    #[cfg(feature = "serde")]
+    pub fn save_digests(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.save_digests = Some(path.into());
+        self
+    }
+
+    /// Limits applied to metrics collected from user tasks.
+    pub fn metrics_config(mut self, config: MetricsConfig) -> Self {
+        self.metrics_config = config;
+        self
+    }
+
+    /// Record only the user metrics allowed by `filter`, see [`filter`](crate::tracing::filter).
+    pub fn metric_filter(mut self, filter: MetricFilter) -> Self {
+        self.metric_filter = filter;
+        self
+    }
+
+    /// Add a pass/fail condition on a metric, see [`thresholds`](crate::thresholds).
+    /// Thresholds are evaluated throughout the run and [`run`](Self::run) returns an error if any failed.
+    pub fn threshold(mut self, threshold: Threshold) -> Self {
+        self.thresholds.push(threshold);
+        self
+    }
+
+    /// Send every message of the run to `sink` as well, see [`sink`](crate::tracing::sink).
+    /// Sinks are handed over to the first call of [`run`](Self::run).
+    pub fn sink(self, sink: impl MetricSink) -> Self {
+        self.sinks.lock().unwrap().push(Box::new(sink));
+        self
+    }
+
+    /// Export every iteration as an OpenTelemetry trace through `tracer`, see [`otel`](crate::tracing::otel).
+    #[cfg(feature = "otel")]
+    pub fn export_traces(self, tracer: opentelemetry::global::BoxedTracer) -> Self {
+        *self.trace_exporter.lock().unwrap() =
+            Some(crate::tracing::otel::TraceExporter::new(tracer));
+        self
+    }
+
+    /// Run without the tracing layer and without a span for every user call, only counting
+    /// iterations and errors. A summary of the throughput is printed once the run is over.
+    ///
+    /// Meant for calibration runs that measure the most load this generator can produce.
+    /// UIs, sinks, checkpoints and metrics emitted by users are all disabled, and
+    /// [`run`](Self::run) fails if any thresholds are set.
+    pub fn raw_throughput(mut self, enable: bool) -> Self {
+        self.raw_throughput = enable;
+        self
+    }
+
+    /// Print a status line for every running executor each `interval`.
+    /// Meant for runs without a terminal UI, such as CI jobs.
+    pub fn log_progress(mut self, interval: std::time::Duration) -> Self {
+        self.log_progress = Some(interval);
+        self
+    }
+
+    /// Print a summary of every executor once the run is over, enabled by default.
+    pub fn print_summary(mut self, enable: bool) -> Self {
+        self.summary = enable;
+        self
+    }
+
+    /// Print a single line JSON object with the outcome of the run, thresholds and headline
+    /// metrics as the last line of output, for shell pipelines to `jq`.
+    #[cfg(feature = "serde")]
+    pub fn print_json_result(mut self, enable: bool) -> Self {
+        self.json_result = enable;
+        self
+    }
+
+    /// Write `tracing` events of users to a rotated log file, which would otherwise be lost as the
+    /// runner owns the global subscriber. See [`log_file`](crate::tracing::log_file).
+    pub fn log_file(mut self, log_file: crate::tracing::log_file::LogFile) -> Self {
+        self.log_file = Some(log_file);
+        self
+    }
+
+    /// Keep the `n` slowest iterations of the run, with their groups and error, for the
+    /// summary and reports. Tracking them adds a message for every iteration.
+    pub fn slowest_iterations(mut self, n: usize) -> Self {
+        self.slowest_iterations = n;
+        self
+    }
+
+    /// Write a single file HTML report to `path` once the run is over, with charts of
+    /// throughput, errors and every metric over time drawn from the metric history.
+    pub fn report_html(self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.reporter(report::html::HtmlReport(path.into()))
+    }
+
+    /// Write a JUnit XML report to `path` once the run is over, for CI servers to show results
+    /// of the run in their test tabs. Executors and thresholds are the test cases.
+    pub fn report_junit(self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.reporter(report::junit::JunitReport(path.into()))
+    }
+
+    /// Write a summary in the schema of k6's `--summary-export` to `path` once the run is over,
+    /// for tooling built around k6 results, see [`report`].
+    #[cfg(feature = "serde")]
+    pub fn report_k6(self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.reporter(report::k6::K6Report(path.into()))
+    }
+
+    /// Write a Markdown table of every executor and the outcome of thresholds to `path`
+    /// once the run is over, for pasting into pull request comments or wikis.
+    pub fn report_markdown(self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.reporter(report::markdown::MarkdownReport {
+            path: path.into(),
+            baseline: None,
+        })
+    }
+
+    /// Compare the [`report_markdown`](Self::report_markdown) with the Markdown report of an
+    /// earlier run at `baseline`, numbers are followed by their change relative to it.
+    pub fn report_markdown_with_baseline(
+        self,
+        path: impl Into<std::path::PathBuf>,
+        baseline: impl Into<std::path::PathBuf>,
+    ) -> Self {
+        self.reporter(report::markdown::MarkdownReport {
+            path: path.into(),
+            baseline: Some(baseline.into()),
+        })
+    }
+
+    /// Hand the final [`RunReport`] to `reporter` once the run is over, see [`report`].
+    /// Reporters are handed over to the first call of [`run`](Self::run).
+    pub fn reporter(self, reporter: impl Reporter) -> Self {
+        self.reporters.lock().unwrap().push(Box::new(reporter));
+        self
+    }
+
+    /// Notify `webhook` when the run starts, when a threshold starts failing and with a
+    /// summary once the run is over, see [`webhook`].
+    #[cfg(all(feature = "reqwest", feature = "serde"))]
+    pub fn webhook(self, webhook: webhook::Webhook) -> Self {
+        self.sink(webhook.clone()).reporter(webhook)
+    }
+
+    /// Show the live terminal UI. When stdout is not a terminal, such as in CI, status lines
+    /// are printed instead as with [`log_progress`](Self::log_progress).
     #[cfg(feature = "tui")]
     pub fn enable_tui(mut self, enable: bool) -> Self {
         self.enable_tui = enable;
         self
     }
 
+    /// Refresh rate and chart history of the terminal UI.
+    #[cfg(feature = "tui")]
+    pub fn tui_config(mut self, config: crate::app::tui::TuiConfig) -> Self {
+        self.tui_config = config;
+        self
+    }
+
+    /// Serve a dashboard of the run on port 3000, or the address set with [`web_addr`](Self::web_addr),
+    /// with live charts of the throughput, users, errors, iteration time and custom metrics of
+    /// every executor.
+    ///
+    /// Messages of the run are also pushed as JSON to WebSocket clients of `/ws`, the results so
+    /// far can be downloaded from `/report.json` and `/report.html`, Prometheus can scrape current
+    /// metrics from `/metrics`, and `/status` tells whether the run is pending, running, paused,
+    /// draining, finished or failed. `/runs` lists the outcome of the run along with the ones
+    /// saved with [`web_history`](Self::web_history).
+    ///
+    /// `/ws`, `/updates` and the dashboard take `?scenario=<name>&executor=0,1&metric=<names>`
+    /// to receive only a part of a run with many executors or metrics.
     #[cfg(feature = "web")]
     pub fn enable_web(mut self, enable: bool) -> Self {
         self.enable_web = enable;
         self
     }
 
+    /// Allow controlling the run through the web server with `POST /control/pause`,
+    /// `/control/resume`, `/control/stop?graceful=true` and `/control/scale` taking
+    /// `{"scenario": 0, "executor": 0, "users": 10}` or `"rate"`, by clients sending
+    /// `Authorization: Bearer <token>`. These endpoints are refused to everyone without a token,
+    /// so that a run on a shared network cannot be stopped or scaled by others.
+    #[cfg(feature = "web")]
+    pub fn web_auth_token(mut self, token: impl Into<String>) -> Self {
+        self.web_config.auth_token = Some(token.into());
+        self
+    }
+
+    /// Address the web server listens on, `0.0.0.0:3000` by default.
+    #[cfg(feature = "web")]
+    pub fn web_addr(mut self, addr: std::net::SocketAddr) -> Self {
+        self.web_config.addr = addr;
+        self
+    }
+
+    /// Save the JSON and HTML reports of the run to `dir` once it is over. The web server lists
+    /// every run saved there at `/runs`, by previous processes too, to compare them with the
+    /// current one while tuning, and serves their reports at `/runs/<id>/report.json` and
+    /// `/runs/<id>/report.html`, where the id is the start time of the run.
+    #[cfg(feature = "web")]
+    pub fn web_history(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.web_config.history = Some(dir.into());
+        self
+    }
+
+    /// Serve the web server over HTTPS with the certificate chain and private key of the
+    /// PEM files `cert` and `key`. Pair it with [`web_auth_token`](Self::web_auth_token)
+    /// before listening beyond localhost, so that the token is not sent in the clear.
+    #[cfg(feature = "web-tls")]
+    pub fn web_tls(
+        mut self,
+        cert: impl Into<std::path::PathBuf>,
+        key: impl Into<std::path::PathBuf>,
+    ) -> Self {
+        self.web_config.tls = Some((cert.into(), key.into()));
+        self
+    }
+
+    /// Periodically write progress of the run to `path`.
+    /// The file can later be used with [`resume_from`](Self::resume_from).
+    #[cfg(feature = "serde")]
+    pub fn checkpoint(
+        mut self,
+        path: impl Into<std::path::PathBuf>,
+        interval: std::time::Duration,
+    ) -> Self {
+        self.checkpoint = Some((path.into(), interval));
+        self
+    }
+
+    /// Continue the run from a checkpoint written by [`checkpoint`](Self::checkpoint).
+    /// Finished executors are skipped and the others only run for the work that is left.
+    /// Metrics continue from their values in the checkpoint, except for the rolling window.
+    /// If the file does not exist the run starts from zero, so the same path can be
+    /// used for both options.
+    #[cfg(feature = "serde")]
+    pub fn resume_from(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.resume_from = Some(path.into());
+        self
+    }
+
+    #[cfg(feature = "serde")]
+    fn load_checkpoint(&self) -> Result<Option<Checkpoint>, crate::error::Error> {
+        let Some(path) = &self.resume_from else {
+            return Ok(None);
+        };
+        let Some(checkpoint) = Checkpoint::load(path)? else {
+            return Ok(None);
+        };
+        checkpoint.validate(&self.logical.scenarios)?;
+        checkpoint.seed(&self.registry, self.metrics_config);
+        Ok(Some(checkpoint))
+    }
+
+    #[cfg(feature = "serde")]
+    fn spawn_checkpoint(
+        &self,
+        consumers: &mut Vec<crate::Sender<Message>>,
+        resume: Option<&Checkpoint>,
+    ) -> Option<tokio::task::JoinHandle<()>> {
+        let (path, interval) = self.checkpoint.clone()?;
+        // Checkpoint written from this run carries over progress made before resuming.
+        let base = resume.cloned();
+
+        let (tx, rx) = crate::channel();
+        consumers.push(tx);
+
+        let app = crate::state::App::new(&self.logical.scenarios);
+        Some(tokio::spawn(crate::checkpoint::run(
+            app,
+            rx,
+            self.registry.clone(),
+            path,
+            interval,
+            base,
+        )))
+    }
+
+    #[cfg(all(unix, feature = "serde"))]
+    fn spawn_dump_on_signal(&self) -> Option<tokio::task::JoinHandle<()>> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        if !self.handle.has_dump_path() {
+            return None;
+        }
+
+        let handle = self.handle.clone();
+        let mut signal = match signal(SignalKind::user_defined1()) {
+            Ok(signal) => signal,
+            Err(err) => {
+                tracing::warn!("failed to listen for SIGUSR1: {err}");
+                return None;
+            }
+        };
+        Some(tokio::spawn(async move {
+            while signal.recv().await.is_some() {
+                if let Err(err) = handle.dump().await {
+                    tracing::warn!("failed to dump stats: {err}");
+                }
+            }
+        }))
+    }
+
+    #[cfg(feature = "serde")]
+    fn spawn_periodic_dump(&self) -> Option<tokio::task::JoinHandle<()>> {
+        let interval = self.dump_interval?;
+        let handle = self.handle.clone();
+        Some(tokio::spawn(async move {
+            let mut ticker =
+                tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+            loop {
+                ticker.tick().await;
+                if let Err(err) = handle.dump().await {
+                    tracing::warn!("failed to dump stats: {err}");
+                }
+            }
+        }))
+    }
+
+    fn spawn_progress(
+        &self,
+        consumers: &mut Vec<crate::Sender<Message>>,
+    ) -> Option<tokio::task::JoinHandle<()>> {
+        #[cfg(feature = "tui")]
+        let interval = match self.log_progress {
+            None if self.tui_fallback() => Some(progress::FALLBACK_INTERVAL),
+            x => x,
+        };
+        #[cfg(not(feature = "tui"))]
+        let interval = self.log_progress;
+        let interval = interval?;
+
+        let (tx, rx) = crate::channel();
+        consumers.push(tx);
+
+        let app = crate::state::App::new(&self.logical.scenarios);
+        Some(tokio::spawn(progress::run(app, rx, interval)))
+    }
+
+    fn spawn_summary(
+        &self,
+        consumers: &mut Vec<crate::Sender<Message>>,
+    ) -> tokio::task::JoinHandle<summary::Summary> {
+        let (tx, rx) = crate::channel();
+        consumers.push(tx);
+
+        let app = crate::state::App::new(&self.logical.scenarios);
+        tokio::spawn(summary::run(app, rx, self.slowest_iterations))
+    }
+
+    fn spawn_sinks(
+        &self,
+        consumers: &mut Vec<crate::Sender<Message>>,
+    ) -> Vec<tokio::task::JoinHandle<()>> {
+        let sinks = std::mem::take(&mut *self.sinks.lock().unwrap());
+        let scenarios: Vec<(String, Vec<String>)> = self
+            .logical
+            .scenarios
+            .iter()
+            .map(|scenario| {
+                let execs = scenario.execution_provider.iter();
+                let execs = execs.map(|x| x.config().to_string()).collect();
+                (scenario.label.to_string(), execs)
+            })
+            .collect();
+        let scenarios = Arc::new(scenarios);
+        sinks
+            .into_iter()
+            .map(|sink| {
+                let (tx, rx) = crate::channel();
+                consumers.push(tx);
+                let scenarios = scenarios.clone();
+                tokio::task::spawn_blocking(move || crate::tracing::sink::run(sink, &scenarios, rx))
+            })
+            .collect()
+    }
+
+    /// Whether the terminal UI was asked for but stdout cannot show it, in which case status
+    /// lines are printed instead.
+    #[cfg(feature = "tui")]
+    fn tui_fallback(&self) -> bool {
+        self.enable_tui && !crate::app::tui::supported()
+    }
+
     #[cfg(feature = "tui")]
     fn spawn_tui(
         &self,
+        consumers: &mut Vec<crate::Sender<Message>>,
     ) -> Option<std::thread::JoinHandle<Result<(), Box<dyn std::error::Error + Send + Sync>>>> {
-        use std::sync::{Arc, Mutex};
+        use std::sync::Mutex;
 
         if !self.enable_tui {
             return None;
         }
+        if self.tui_fallback() {
+            eprintln!(
+                "stdout is not a terminal, printing progress lines instead of the terminal UI"
+            );
+            return None;
+        }
 
         let (tx, rx) = crate::channel();
+        consumers.push(tx);
 
-        let tracer = crate::tracing::TracerLayer::new(tx);
-        let subscriber = tracing_subscriber::layer::SubscriberExt::with(
-            tracing_subscriber::Registry::default(),
-            tracer,
-        );
-
-        tracing::subscriber::set_global_default(subscriber).unwrap();
-
-        let app = Arc::new(Mutex::new(crate::app::App::new(&self.logical.scenarios)));
-        Some(std::thread::spawn(|| crate::app::tui::run(app, rx)))
+        let config = self.tui_config;
+        let app = crate::state::App::new(&self.logical.scenarios).history(config.history_len());
+        let app = Arc::new(Mutex::new(app));
+        let handle = self.handle.clone();
+        Some(std::thread::spawn(move || {
+            crate::app::tui::run(app, rx, handle, config)
+        }))
     }
 
     #[cfg(feature = "web")]
     fn spawn_web(
         &self,
+        consumers: &mut Vec<crate::Sender<Message>>,
     ) -> Option<tokio::task::JoinHandle<Result<(), Box<dyn std::error::Error + Send + Sync>>>> {
-        use std::sync::{Arc, Mutex};
+        use std::sync::Mutex;
 
         if !self.enable_web {
             return None;
         }
 
         let (tx, rx) = crate::channel();
+        consumers.push(tx);
 
-        let tracer = crate::tracing::TracerLayer::new(tx);
-        let subscriber = tracing_subscriber::layer::SubscriberExt::with(
-            tracing_subscriber::Registry::default(),
-            tracer,
-        );
-
-        tracing::subscriber::set_global_default(subscriber).unwrap();
-
-        let app = Arc::new(Mutex::new(crate::app::App::new(&self.logical.scenarios)));
-        Some(tokio::spawn(crate::app::web::run(app, rx)))
+        let app = crate::state::App::new(&self.logical.scenarios);
+        let summary = summary::Summary::new(app, self.slowest_iterations);
+        Some(tokio::spawn(crate::app::web::run(
+            Arc::new(Mutex::new(summary)),
+            rx,
+            self.handle.clone(),
+            self.web_config.clone(),
+        )))
     }
 }
 
+/// Stop the users of every executor of a scenario, see [`User::stop`](crate::user::User::stop).
+async fn stop_users(scenario: &mut [(usize, &logical::Executor, Box<dyn Executor + '_>)]) {
+    let stops = scenario.iter_mut().map(|(_, _, executor)| executor.stop());
+    futures::future::join_all(stops).await;
+}
+
 struct LogicalContext<'env> {
     scenarios: Vec<logical::Scenario<'env>>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub(crate) struct ExecutionRuntimeCtx {
     datastore: RuntimeDataStore,
+    spawner: UserSpawner,
 }
 
 impl ExecutionRuntimeCtx {
-    pub fn new() -> Self {
+    pub fn new(spawner: UserSpawner) -> Self {
         Self {
             datastore: RuntimeDataStore::default(),
+            spawner,
         }
     }
 
+    pub fn spawner(&self) -> UserSpawner {
+        self.spawner.clone()
+    }
+
     pub async fn modify(&mut self, f: &dyn DatastoreModifier) {
         f.init_store(&mut self.datastore).await;
     }
@@ -205,15 +1097,206 @@ impl ExecutionRuntimeCtx {
     }
 }
 
-async fn has_user_terminated<'s>(
+/// Sleep until wall-clock time `at`, returns immediately if it is in the past.
+async fn sleep_until(at: DateTime<Utc>) {
+    if let Ok(delay) = (at - Utc::now()).to_std() {
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Write `content` to a temporary file next to `path` and rename it into place,
+/// so that a crash never leaves a torn file and readers never see a partial one.
+#[cfg(feature = "serde")]
+pub(crate) async fn write_atomic(path: &std::path::Path, content: Vec<u8>) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    tokio::fs::write(&tmp, content).await?;
+    tokio::fs::rename(&tmp, path).await
+}
+
+/// Iterations and errors counted in raw throughput mode.
+#[derive(Default)]
+struct RawCounts {
+    iterations: AtomicU64,
+    errors: AtomicU64,
+}
+
+impl RawCounts {
+    fn print(&self, elapsed: std::time::Duration) {
+        let iterations = self.iterations.load(Ordering::Relaxed);
+        let errors = self.errors.load(Ordering::Relaxed);
+        println!(
+            "raw throughput: {iterations} iterations, {errors} errors in {elapsed:.2?} ({:.2}/s)",
+            iterations as f64 / elapsed.as_secs_f64()
+        );
+    }
+}
+
+async fn has_user_terminated(
     mut user_result_rx: tokio::sync::mpsc::UnboundedReceiver<Result<(), crate::error::Error>>,
+    counts: Option<&RawCounts>,
 ) -> bool {
     let mut results = Vec::with_capacity(128);
     while user_result_rx.recv_many(&mut results, 128).await > 0 {
-        if let Some(err) = results.iter().filter_map(|x| x.as_ref().err()).next() {
+        if let Some(counts) = counts {
+            let errors = results.iter().filter(|x| x.is_err()).count();
+            counts
+                .iterations
+                .fetch_add(results.len() as u64, Ordering::Relaxed);
+            counts.errors.fetch_add(errors as u64, Ordering::Relaxed);
+        }
+        // Failed iterations are counted by the tracer, only termination errors end the scenario.
+        if let Some(err) = results
+            .iter()
+            .filter_map(|x| x.as_ref().err().filter(|x| x.is_termination_err()))
+            .next()
+        {
             event!(name: "termination_error", target: CRATE_NAME, tracing::Level::INFO, err = %err);
             return true;
         }
+        results.clear();
     }
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use super::ScaleTarget;
+    use crate::{error::Error, prelude::*, user::AsyncUserBuilder};
+
+    async fn user_builder(_: &RuntimeDataStore) -> impl User {
+        || async { Ok(()) }
+    }
+
+    static FAILED: AtomicUsize = AtomicUsize::new(0);
+
+    async fn failing_user_builder(_: &RuntimeDataStore) -> impl User {
+        || async {
+            FAILED.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Err(Error::new("failed"))
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn failed_iterations_do_not_end_scenario() {
+        let execution = Execution::builder()
+            .with_user_builder(failing_user_builder)
+            .with_executor(Executor::Constant {
+                users: 1,
+                duration: Duration::from_millis(200),
+            });
+        let runner = Runner::new(vec![Scenario::new("scenario", execution)]).print_summary(false);
+        assert!(runner.run().await.is_ok());
+        assert!(FAILED.load(Ordering::Relaxed) > 5);
+    }
+
+    struct NoopUser;
+
+    impl User for NoopUser {
+        async fn call(&mut self) -> UserResult {
+            Ok(())
+        }
+    }
+
+    static BUILD_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Builds `users` users, then fails on every attempt.
+    struct LimitedBuilder {
+        users: usize,
+    }
+
+    #[async_trait::async_trait]
+    impl<'a> AsyncUserBuilder<'a> for LimitedBuilder {
+        type Output = NoopUser;
+
+        async fn build(&self, _: &'a RuntimeDataStore) -> Result<Self::Output, Error> {
+            if BUILD_ATTEMPTS.fetch_add(1, Ordering::Relaxed) < self.users {
+                Ok(NoopUser)
+            } else {
+                Err(Error::new("no more users"))
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn build_error_while_running() {
+        let execution = Execution::builder()
+            .with_user_builder(LimitedBuilder { users: 1 })
+            .with_build_retries(2)
+            .with_executor(Executor::RampingUser {
+                pre_allocate_users: 1,
+                stages: vec![(2, Duration::from_millis(100))],
+            });
+        let runner = Runner::new(vec![Scenario::new("scenario", execution)]).print_summary(false);
+        let err = runner.run().await.unwrap_err().to_string();
+        // The user built up front, then the first try and two retries of the second one.
+        assert_eq!(BUILD_ATTEMPTS.load(Ordering::Relaxed), 4);
+        assert!(
+            err.starts_with("scenario / RampingUser (1 stages)"),
+            "{err}"
+        );
+        assert!(err.contains("attempt 3, 1 users built"), "{err}");
+    }
+
+    static STARTED: AtomicUsize = AtomicUsize::new(0);
+    static STOPPED: AtomicUsize = AtomicUsize::new(0);
+
+    struct LifecycleUser;
+
+    impl User for LifecycleUser {
+        async fn call(&mut self) -> UserResult {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok(())
+        }
+
+        async fn start(&mut self) {
+            STARTED.fetch_add(1, Ordering::Relaxed);
+        }
+
+        async fn stop(&mut self) {
+            STOPPED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    async fn lifecycle_user_builder(_: &RuntimeDataStore) -> LifecycleUser {
+        LifecycleUser
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn scale_down_stops_users() {
+        let execution = Execution::builder()
+            .with_user_builder(lifecycle_user_builder)
+            .with_executor(Executor::RampingUser {
+                pre_allocate_users: 2,
+                stages: vec![(2, Duration::from_millis(600))],
+            });
+        let runner = Runner::new(vec![Scenario::new("scenario", execution)]).print_summary(false);
+        let handle = runner.handle();
+        let scale = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            handle.scale(0, 0, ScaleTarget::Users(1)).unwrap();
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            assert_eq!(STOPPED.load(Ordering::Relaxed), 1);
+            handle.scale(0, 0, ScaleTarget::Users(2)).unwrap();
+        });
+        runner.run().await.unwrap();
+        scale.await.unwrap();
+        // The retired user is started again when scaled back up, then every user is stopped.
+        assert_eq!(STARTED.load(Ordering::Relaxed), 3);
+        assert_eq!(STOPPED.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn existing_global_subscriber() {
+        let _ = tracing::subscriber::set_global_default(tracing_subscriber::Registry::default());
+        let execution = Execution::builder().with_user_builder(user_builder);
+        let runner = Runner::new(vec![Scenario::new("scenario", execution)]).print_summary(true);
+        assert!(runner.run().await.is_ok());
+    }
+}
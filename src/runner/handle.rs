@@ -0,0 +1,339 @@
+use std::sync::Arc;
+
+use crate::executor::{Pause, Scale};
+use crate::logical::Executor;
+use crate::tracing::{
+    scenario_metrics,
+    task_event::{metrics::MetricValue, MetricSetKey, Sample},
+    MetricRegistry,
+};
+
+/// Cloneable handle to a [`Runner`](super::Runner) for interacting with a run
+/// from other tasks while it is in progress.
+#[derive(Clone)]
+pub struct RunnerHandle {
+    inner: Arc<Inner>,
+}
+
+/// Cloneable read access to live metrics of every executor, for user code such as an
+/// adaptive controller or a custom reporter that polls metric values while the run is in progress.
+#[derive(Clone)]
+pub struct MetricsHandle {
+    inner: Arc<Inner>,
+}
+
+/// Current metric values of one executor.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExecutorMetrics {
+    pub scenario: String,
+    pub executor: String,
+    pub metrics: Vec<(MetricSetKey, MetricValue)>,
+}
+
+/// New target for a running executor, see [`RunnerHandle::scale`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleTarget {
+    /// Number of users of a [`RampingUser`](Executor::RampingUser) executor.
+    Users(usize),
+    /// Iterations started per time unit of the rate of an arrival rate executor.
+    Rate(usize),
+}
+
+struct Inner {
+    registry: Arc<MetricRegistry>,
+    /// Scenario names paired with names of their executors.
+    scenarios: std::sync::Mutex<Vec<(String, Vec<String>)>>,
+    pause: Arc<Pause>,
+    scales: std::sync::Mutex<Vec<Vec<ExecutorScale>>>,
+    #[cfg(feature = "serde")]
+    dump_path: std::sync::Mutex<Option<std::path::PathBuf>>,
+}
+
+/// Scaling gate of an executor.
+struct ExecutorScale {
+    /// Kind of target the executor follows, none when it cannot be scaled.
+    kind: Option<fn(usize) -> ScaleTarget>,
+    scale: Arc<Scale>,
+}
+
+impl RunnerHandle {
+    pub(crate) fn new(
+        registry: Arc<MetricRegistry>,
+        scenarios: &[crate::logical::Scenario<'_>],
+    ) -> Self {
+        let handle = Self {
+            inner: Arc::new(Inner {
+                registry,
+                scenarios: Default::default(),
+                pause: Default::default(),
+                scales: Default::default(),
+                #[cfg(feature = "serde")]
+                dump_path: Default::default(),
+            }),
+        };
+        handle.set_scenarios(scenarios);
+        handle
+    }
+
+    /// Refresh names after the scenarios of the runner changed.
+    pub(crate) fn set_scenarios(&self, scenarios: &[crate::logical::Scenario<'_>]) {
+        *self.inner.scenarios.lock().unwrap() = scenarios
+            .iter()
+            .map(|scenario| {
+                let execs = scenario
+                    .execution_provider
+                    .iter()
+                    .map(|exec| exec.config().to_string())
+                    .collect();
+                (scenario.label.to_string(), execs)
+            })
+            .collect();
+        *self.inner.scales.lock().unwrap() = scenarios
+            .iter()
+            .map(|scenario| {
+                scenario
+                    .execution_provider
+                    .iter()
+                    .map(|exec| {
+                        let kind: Option<fn(usize) -> ScaleTarget> = match exec.config() {
+                            Executor::RampingUser { .. } => Some(ScaleTarget::Users),
+                            Executor::ConstantArrivalRate { .. }
+                            | Executor::RampingArrivalRate { .. } => Some(ScaleTarget::Rate),
+                            _ => None,
+                        };
+                        ExecutorScale {
+                            kind,
+                            scale: Arc::default(),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    /// Stop users from starting new iterations until [`resume`](Self::resume) is called.
+    /// Iterations in flight run to completion, and executors bounded by a duration keep
+    /// counting it down while paused.
+    pub fn pause(&self) {
+        self.inner.pause.set(true);
+    }
+
+    /// Let users start new iterations again after a [`pause`](Self::pause).
+    pub fn resume(&self) {
+        self.inner.pause.set(false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.pause.is_paused()
+    }
+
+    /// Stop the run gracefully: users start no new iterations and later scenarios are
+    /// skipped, while iterations in flight run to completion before the summary is printed.
+    pub fn stop(&self) {
+        self.inner.pause.stop();
+    }
+
+    /// Stop the run right away, cancelling iterations in flight. The summary is still printed.
+    pub fn abort(&self) {
+        self.inner.pause.abort();
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.inner.pause.is_stopped()
+    }
+
+    #[cfg(feature = "web")]
+    pub(crate) fn registry(&self) -> Arc<MetricRegistry> {
+        self.inner.registry.clone()
+    }
+
+    pub(crate) fn pause_gate(&self) -> Arc<Pause> {
+        self.inner.pause.clone()
+    }
+
+    /// Change the number of users of a ramping user executor or the rate of an arrival rate
+    /// executor while it runs. The target replaces the one of every remaining stage.
+    /// Scenarios and executors are indexed in the order they were added to the runner.
+    pub fn scale(
+        &self,
+        scenario: usize,
+        executor: usize,
+        target: ScaleTarget,
+    ) -> Result<(), crate::error::Error> {
+        let scales = self.inner.scales.lock().unwrap();
+        let Some(ExecutorScale { kind, scale }) =
+            scales.get(scenario).and_then(|x| x.get(executor))
+        else {
+            return Err(crate::error::Error::new(format!(
+                "no executor {executor} in scenario {scenario}"
+            )));
+        };
+        let value = match target {
+            ScaleTarget::Users(value) | ScaleTarget::Rate(value) => value,
+        };
+        if kind.map(|kind| kind(value)) != Some(target) {
+            return Err(crate::error::Error::new(format!(
+                "executor {executor} of scenario {scenario} cannot be scaled to {target:?}"
+            )));
+        }
+        scale.set(value);
+        Ok(())
+    }
+
+    pub(crate) fn scale_gate(&self, scenario: usize, executor: usize) -> Arc<Scale> {
+        self.inner.scales.lock().unwrap()[scenario][executor]
+            .scale
+            .clone()
+    }
+
+    /// Handle for reading metrics of the run.
+    pub fn metrics(&self) -> MetricsHandle {
+        MetricsHandle {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl MetricsHandle {
+    /// Current values of every metric of every executor that has started.
+    pub fn snapshot(&self) -> Vec<ExecutorMetrics> {
+        let scenarios = self.inner.scenarios.lock().unwrap().clone();
+        let mut snapshot = Vec::new();
+        for (scenario_id, (scenario, execs)) in scenarios.into_iter().enumerate() {
+            for (exec_id, executor) in execs.into_iter().enumerate() {
+                let Some(metrics) = self.inner.registry.get(&(scenario_id, exec_id)) else {
+                    continue;
+                };
+                snapshot.push(ExecutorMetrics {
+                    scenario: scenario.clone(),
+                    executor,
+                    metrics: metrics.entries().collect(),
+                });
+            }
+        }
+        snapshot
+    }
+
+    /// Current values of every metric combined across all executors of a scenario,
+    /// see [`MetricSet::rollup`](crate::tracing::task_event::MetricSet::rollup).
+    pub fn scenario(&self, scenario: usize) -> Vec<(MetricSetKey, MetricValue)> {
+        scenario_metrics(&self.inner.registry, scenario)
+    }
+
+    /// Current values of the metric `name` of an executor, one entry for each attribute set.
+    /// Scenarios and executors are indexed in the order they were added to the runner.
+    pub fn get(
+        &self,
+        scenario: usize,
+        executor: usize,
+        name: &str,
+    ) -> Vec<(MetricSetKey, MetricValue)> {
+        self.inner
+            .registry
+            .get(&(scenario, executor))
+            .map(|metrics| {
+                metrics
+                    .entries()
+                    .filter(|(key, _)| key.name == name)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Samples of every metric of an executor over the retention period set with
+    /// [`MetricsConfig::history_retention`](crate::tracing::task_event::MetricsConfig::history_retention).
+    pub fn history(&self, scenario: usize, executor: usize) -> Vec<(MetricSetKey, Vec<Sample>)> {
+        self.inner
+            .registry
+            .get(&(scenario, executor))
+            .map(|metrics| metrics.history())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl RunnerHandle {
+    pub(crate) fn set_dump_path(&self, path: std::path::PathBuf) {
+        *self.inner.dump_path.lock().unwrap() = Some(path);
+    }
+
+    pub(crate) fn has_dump_path(&self) -> bool {
+        self.inner.dump_path.lock().unwrap().is_some()
+    }
+
+    /// Snapshot current metrics of every executor and write them as JSON to the path
+    /// configured with [`Runner::dump_stats_to`](super::Runner::dump_stats_to).
+    pub async fn dump(&self) -> Result<(), crate::error::Error> {
+        let Some(path) = self.inner.dump_path.lock().unwrap().clone() else {
+            return Err(crate::error::Error::new(
+                "no path configured for stats dump",
+            ));
+        };
+
+        let scenarios = self.inner.scenarios.lock().unwrap().clone();
+        let content =
+            serde_json::to_vec(&self.snapshot(&scenarios)).map_err(anyhow::Error::from)?;
+        super::write_atomic(&path, content)
+            .await
+            .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    fn snapshot<'a>(&self, scenarios: &'a [(String, Vec<String>)]) -> Snapshot<'a> {
+        let scenarios = scenarios
+            .iter()
+            .enumerate()
+            .map(|(scenario_id, (name, execs))| ScenarioSnapshot {
+                name,
+                executors: execs
+                    .iter()
+                    .enumerate()
+                    .map(|(exec_id, name)| ExecutorSnapshot {
+                        name,
+                        metrics: self
+                            .inner
+                            .registry
+                            .get(&(scenario_id, exec_id))
+                            .map(|metrics| metrics.entries().collect())
+                            .unwrap_or_default(),
+                        history: self.metrics().history(scenario_id, exec_id),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Snapshot {
+            time: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false),
+            scenarios,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct Snapshot<'a> {
+    time: String,
+    scenarios: Vec<ScenarioSnapshot<'a>>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct ScenarioSnapshot<'a> {
+    name: &'a str,
+    executors: Vec<ExecutorSnapshot<'a>>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct ExecutorSnapshot<'a> {
+    name: &'a str,
+    metrics: Vec<(
+        crate::tracing::task_event::MetricSetKey,
+        crate::tracing::task_event::metrics::MetricValue,
+    )>,
+    history: Vec<(
+        crate::tracing::task_event::MetricSetKey,
+        Vec<crate::tracing::task_event::Sample>,
+    )>,
+}
@@ -0,0 +1,163 @@
+//! Headless progress output, a compact status line per running executor printed
+//! every interval. Useful for CI logs and servers without a TTY.
+
+use std::time::Duration;
+
+use tdigest::TDigest;
+
+use crate::state::{App, ExecutorState};
+use crate::tracing::{
+    message::Message,
+    task_event::{metrics::MetricValue, MetricSetKey},
+    ERRORS_TOTAL,
+};
+
+/// Interval of the status lines printed in place of a terminal UI that is not supported,
+/// see [`tui::supported`](super::tui::supported).
+#[cfg(feature = "tui")]
+pub(crate) const FALLBACK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Iteration times observed for an executor since the last status line.
+#[derive(Debug, Default)]
+pub(crate) struct Window {
+    iterations: u64,
+    digest: Option<TDigest>,
+    buffer: Vec<f64>,
+}
+
+impl Window {
+    pub(crate) fn observe(&mut self, duration: Duration) {
+        self.iterations += 1;
+        self.buffer.push(duration.as_nanos() as f64);
+        if self.buffer.len() >= 4096 {
+            self.fold();
+        }
+    }
+
+    fn fold(&mut self) {
+        let values = std::mem::take(&mut self.buffer);
+        let digest = self.digest.take().unwrap_or_default();
+        self.digest = Some(digest.merge_unsorted(values));
+    }
+
+    /// Window of every iteration observed by `windows`.
+    pub(crate) fn merged<'a>(windows: impl IntoIterator<Item = &'a mut Window>) -> Window {
+        let mut iterations = 0;
+        let mut digests = Vec::new();
+        for window in windows {
+            if !window.buffer.is_empty() {
+                window.fold();
+            }
+            iterations += window.iterations;
+            digests.extend(window.digest.clone());
+        }
+        Window {
+            iterations,
+            digest: (!digests.is_empty()).then(|| TDigest::merge_digests(digests)),
+            buffer: Vec::new(),
+        }
+    }
+
+    fn p95(&mut self) -> Duration {
+        self.quantile(0.95)
+    }
+
+    pub(crate) fn quantile(&mut self, quantile: f64) -> Duration {
+        if !self.buffer.is_empty() {
+            self.fold();
+        }
+        let nanos = self
+            .digest
+            .as_ref()
+            .map(|x| x.estimate_quantile(quantile))
+            .unwrap_or_default();
+        // Microsecond precision is plenty for a status line.
+        Duration::from_micros((nanos / 1000.) as u64)
+    }
+}
+
+pub async fn run(mut app: App, mut rx: crate::Receiver<Message>, interval: Duration) {
+    let mut windows: Vec<Window> = Vec::new();
+    let mut last_tick = tokio::time::Instant::now();
+    let mut ticker = tokio::time::interval_at(last_tick + interval, interval);
+
+    loop {
+        tokio::select! {
+            message = rx.recv() => match message {
+                Some(Message::End) | None => break,
+                Some(message) => {
+                    match &message {
+                        Message::ScenarioChanged { .. } => windows.clear(),
+                        Message::Warning { warning, .. } => println!("warning: {warning}"),
+                        Message::TerminatedError { err } => println!("terminated: {err}"),
+                        Message::TaskTime { execution_id, duration, .. } => {
+                            if windows.len() <= *execution_id {
+                                windows.resize_with(execution_id + 1, Window::default);
+                            }
+                            windows[*execution_id].observe(*duration);
+                        }
+                        _ => (),
+                    }
+                    app.handle_message(message);
+                }
+            },
+            now = ticker.tick() => {
+                let elapsed = now - last_tick;
+                last_tick = now;
+                print_status(&app, &mut windows, elapsed);
+            }
+        }
+    }
+}
+
+fn print_status(app: &App, windows: &mut Vec<Window>, elapsed: Duration) {
+    let scenario = app.current_scenario();
+    windows.resize_with(scenario.execs().len(), Window::default);
+
+    for (exec, window) in scenario.execs().iter().zip(windows.iter_mut()) {
+        if exec.started() && !exec.ended() {
+            println!(
+                "[{:>6.0?}] {} / {}: users {}, {:.1} iters/s, p95 {:?}, errors {}",
+                exec.duration(),
+                scenario.name(),
+                exec.config(),
+                exec.users(),
+                window.iterations as f64 / elapsed.as_secs_f64(),
+                window.p95(),
+                exec.errors(),
+            );
+        }
+        *window = Window::default();
+    }
+}
+
+/// Metric name with its attributes, e.g. `latency{endpoint=/login}`.
+pub(crate) fn series(key: &MetricSetKey) -> String {
+    if key.attributes.is_empty() {
+        return key.name.to_string();
+    }
+    let attributes: Vec<_> = key
+        .attributes
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect();
+    format!("{}{{{}}}", key.name, attributes.join(","))
+}
+
+/// Failed iterations of an executor by error class, most frequent first.
+pub(super) fn error_classes(exec: &ExecutorState) -> Vec<(String, u64)> {
+    let mut classes: Vec<_> = exec
+        .metrics()
+        .iter()
+        .filter(|(key, _)| key.name == ERRORS_TOTAL)
+        .filter_map(|(key, values)| match values.back() {
+            Some(MetricValue::Counter(count)) => {
+                let class = key.attributes.first().map(|(_, x)| x.to_string());
+                Some((class.unwrap_or_default(), *count))
+            }
+            _ => None,
+        })
+        .collect();
+    classes.sort_by(|x, y| y.1.cmp(&x.1).then_with(|| x.0.cmp(&y.0)));
+    classes
+}
@@ -0,0 +1,224 @@
+//! Reports written once a run is over.
+//!
+//! Every [`Reporter`] registered with [`Runner::reporter`](super::Runner::reporter) is handed the
+//! final [`RunReport`], which makes it possible to add formats such as an internal dashboard
+//! without changes to this crate. The HTML, JUnit and Markdown reports of the runner are reporters as well,
+//! and [`chat`] posts a summary to Slack or Teams with the `reqwest` and `serde` features.
+//! With the `upload` feature, [`upload`] copies report files to object storage.
+
+use std::{collections::HashMap, time::Duration};
+
+use crate::{
+    runner::summary::{ErrorSummary, Summary},
+    thresholds::ThresholdResult,
+    tracing::{
+        message::IterationRecord,
+        scenario_metrics,
+        task_event::{metrics::MetricValue, MetricSet, MetricSetKey, Sample},
+        MetricRegistry,
+    },
+};
+
+#[cfg(all(feature = "reqwest", feature = "serde"))]
+pub mod chat;
+pub(crate) mod html;
+#[cfg(feature = "serde")]
+pub(crate) mod json;
+pub(crate) mod junit;
+#[cfg(feature = "serde")]
+pub(crate) mod k6;
+pub(crate) mod markdown;
+#[cfg(feature = "upload")]
+pub mod upload;
+
+/// Output of a run in a custom format, see [`Runner::reporter`](super::Runner::reporter).
+pub trait Reporter: Send + 'static {
+    fn report(
+        &mut self,
+        report: &RunReport,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Final results of a run.
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    pub scenarios: Vec<ScenarioReport>,
+    /// Metrics of every executor of every scenario combined.
+    pub metrics: Vec<(MetricSetKey, MetricValue)>,
+    /// Totals of counters and rates, and number of values observed by histograms and trends,
+    /// of every executor combined.
+    pub counts: Vec<(MetricSetKey, u64)>,
+    /// Time taken by every iteration of the run.
+    pub iteration_time: IterationTime,
+    pub thresholds: Vec<ThresholdResult>,
+    /// Index of the scenario that was running when a user terminated the run, with the error.
+    pub terminated: Option<(usize, String)>,
+    /// Slowest iterations of the run, slowest first, see
+    /// [`Runner::slowest_iterations`](super::Runner::slowest_iterations).
+    pub slowest: Vec<IterationRecord>,
+    /// Error messages of the run, most frequent first.
+    pub errors: Vec<ErrorSummary>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScenarioReport {
+    pub name: String,
+    pub executors: Vec<ExecutorReport>,
+    /// Metrics of every executor combined.
+    pub metrics: Vec<(MetricSetKey, MetricValue)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExecutorReport {
+    pub name: String,
+    /// False for executors the run ended before.
+    pub started: bool,
+    pub duration: Duration,
+    pub iterations: u64,
+    pub errors: u64,
+    pub iteration_time: IterationTime,
+    pub metrics: Vec<(MetricSetKey, MetricValue)>,
+    /// Samples of every metric over the retention period, see
+    /// [`MetricsConfig::history_retention`](crate::tracing::task_event::MetricsConfig::history_retention).
+    pub history: Vec<(MetricSetKey, Vec<Sample>)>,
+}
+
+/// Distribution of the time taken by iterations of an executor.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IterationTime {
+    pub min: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl ExecutorReport {
+    /// Iterations per second over the time the executor ran.
+    pub fn rate(&self) -> f64 {
+        match self.duration.as_secs_f64() {
+            x if x > 0. => self.iterations as f64 / x,
+            _ => 0.,
+        }
+    }
+
+    /// Fraction of iterations that returned an error.
+    pub fn error_rate(&self) -> f64 {
+        match self.iterations {
+            0 => 0.,
+            x => self.errors as f64 / x as f64,
+        }
+    }
+}
+
+impl RunReport {
+    /// Time the run took, the longest executor of every scenario added up.
+    pub fn duration(&self) -> Duration {
+        self.scenarios
+            .iter()
+            .filter_map(|x| x.executors.iter().map(|x| x.duration).max())
+            .sum()
+    }
+
+    /// Whether every threshold passed and no user terminated the run.
+    pub fn passed(&self) -> bool {
+        self.terminated.is_none() && self.thresholds.iter().all(|x| x.passed)
+    }
+
+    pub(crate) fn new(
+        summary: &mut Summary,
+        registry: &MetricRegistry,
+        thresholds: Vec<ThresholdResult>,
+    ) -> Self {
+        let mut scenarios = Vec::new();
+        for scenario_id in 0..summary.app().scenarios().len() {
+            let execs = summary.app().scenarios()[scenario_id].execs().len();
+            let mut executors = Vec::new();
+            for id in 0..execs {
+                let mut quantile = |q| summary.iteration_quantile(scenario_id, id, q);
+                let (p50, p90) = (quantile(0.5), quantile(0.9));
+                let (p95, p99) = (quantile(0.95), quantile(0.99));
+                let exec = &summary.app().scenarios()[scenario_id].execs()[id];
+                let (min, mean, max) = exec.iteration_times();
+                let metrics = registry.get(&(scenario_id, id));
+                executors.push(ExecutorReport {
+                    name: exec.config().to_string(),
+                    started: exec.started(),
+                    duration: exec.duration(),
+                    iterations: exec.iterations(),
+                    errors: exec.errors(),
+                    iteration_time: IterationTime {
+                        min,
+                        mean,
+                        p50,
+                        p90,
+                        p95,
+                        p99,
+                        max,
+                    },
+                    metrics: metrics
+                        .as_ref()
+                        .map(|x| x.entries().collect())
+                        .unwrap_or_default(),
+                    history: metrics.map(|x| x.history()).unwrap_or_default(),
+                });
+            }
+            scenarios.push(ScenarioReport {
+                name: summary.app().scenarios()[scenario_id].name().to_string(),
+                executors,
+                metrics: scenario_metrics(registry, scenario_id),
+            });
+        }
+
+        let sets: Vec<_> = registry.iter().map(|x| x.value().clone()).collect();
+        let mut counts: HashMap<MetricSetKey, u64> = HashMap::new();
+        for (key, count) in sets.iter().flat_map(|x| x.counts()) {
+            *counts.entry(key).or_default() += count;
+        }
+
+        let executors = scenarios.iter().flat_map(|x| &x.executors);
+        let executors: Vec<_> = executors.filter(|x| x.iterations > 0).collect();
+        let iterations: u64 = executors.iter().map(|x| x.iterations).sum();
+        let mut quantile = |q| summary.run_iteration_quantile(q);
+        let iteration_time = IterationTime {
+            min: executors
+                .iter()
+                .map(|x| x.iteration_time.min)
+                .min()
+                .unwrap_or_default(),
+            mean: match iterations {
+                0 => Duration::ZERO,
+                n => executors
+                    .iter()
+                    .map(|x| {
+                        x.iteration_time
+                            .mean
+                            .mul_f64(x.iterations as f64 / n as f64)
+                    })
+                    .sum(),
+            },
+            p50: quantile(0.5),
+            p90: quantile(0.9),
+            p95: quantile(0.95),
+            p99: quantile(0.99),
+            max: executors
+                .iter()
+                .map(|x| x.iteration_time.max)
+                .max()
+                .unwrap_or_default(),
+        };
+
+        Self {
+            metrics: MetricSet::rollup(sets.iter().map(|x| x.as_ref())),
+            counts: counts.into_iter().collect(),
+            iteration_time,
+            scenarios,
+            thresholds,
+            terminated: summary.terminated().map(|(id, err)| (id, err.to_string())),
+            slowest: summary.slowest().to_vec(),
+            errors: summary.errors(),
+        }
+    }
+}
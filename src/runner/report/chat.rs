@@ -0,0 +1,194 @@
+//! Notification posted to a Slack or Microsoft Teams incoming webhook once a run is over.
+//!
+//! The message leads with whether the run passed, followed by the throughput, error rate and p95
+//! iteration time of every executor, the outcome of every threshold and a link to the full report.
+//!
+//! ```no_run
+//! use rusher::runner::{report::chat::ChatNotifier, Runner};
+//!
+//! let runner = Runner::new(vec![]).report_html("report.html").reporter(
+//!     ChatNotifier::slack("https://hooks.slack.com/services/T000/B000/XXXX")
+//!         .report_url("https://ci.example.com/artifacts/report.html"),
+//! );
+//! ```
+
+use std::fmt::Write;
+
+use super::{Reporter, RunReport};
+use crate::tracing::sink::millis;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Service {
+    Slack,
+    Teams,
+}
+
+/// [`Reporter`] posting a summary of the run to a chat webhook.
+pub struct ChatNotifier {
+    client: reqwest::Client,
+    service: Service,
+    url: String,
+    report_url: Option<String>,
+    failures_only: bool,
+}
+
+impl ChatNotifier {
+    fn new(service: Service, url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            service,
+            url: url.into(),
+            report_url: None,
+            failures_only: false,
+        }
+    }
+
+    /// Post to the Slack incoming webhook at `url`.
+    pub fn slack(url: impl Into<String>) -> Self {
+        Self::new(Service::Slack, url)
+    }
+
+    /// Post to the Microsoft Teams incoming webhook at `url`.
+    pub fn teams(url: impl Into<String>) -> Self {
+        Self::new(Service::Teams, url)
+    }
+
+    /// Link to the uploaded report, such as the CI artifact of the
+    /// [`report_html`](crate::runner::Runner::report_html), added at the end of the message.
+    pub fn report_url(mut self, url: impl Into<String>) -> Self {
+        self.report_url = Some(url.into());
+        self
+    }
+
+    /// Post only when the run failed, a threshold failed or a user terminated the run.
+    pub fn failures_only(mut self, enable: bool) -> Self {
+        self.failures_only = enable;
+        self
+    }
+
+    /// Text of the message, in the markup of the service.
+    fn render(&self, report: &RunReport) -> String {
+        let bold = |text: &str| match self.service {
+            Service::Slack => format!("*{text}*"),
+            Service::Teams => format!("**{text}**"),
+        };
+
+        let mut text = match report.passed() {
+            true => bold("\u{2705} Load test passed"),
+            false => bold("\u{274c} Load test failed"),
+        };
+        text.push('\n');
+        if let Some((id, err)) = &report.terminated {
+            let scenario = report.scenarios.get(*id).map_or("", |x| x.name.as_str());
+            let _ = writeln!(text, "Terminated during {scenario}: {err}");
+        }
+        for scenario in &report.scenarios {
+            for exec in scenario.executors.iter().filter(|x| x.started) {
+                let _ = writeln!(
+                    text,
+                    "\u{2022} {} / {}: {} iterations ({:.1}/s), {:.2}% errors, p95 {:.2} ms",
+                    scenario.name,
+                    exec.name,
+                    exec.iterations,
+                    exec.rate(),
+                    exec.error_rate() * 100.,
+                    millis(exec.iteration_time.p95),
+                );
+            }
+        }
+        if !report.thresholds.is_empty() {
+            let _ = writeln!(text, "{}", bold("Thresholds"));
+        }
+        for result in &report.thresholds {
+            let outcome = if result.passed {
+                "\u{2705}"
+            } else {
+                "\u{274c}"
+            };
+            let _ = writeln!(text, "{outcome} {}", result.threshold);
+        }
+        if let Some(url) = &self.report_url {
+            let _ = match self.service {
+                Service::Slack => writeln!(text, "<{url}|View report>"),
+                Service::Teams => writeln!(text, "[View report]({url})"),
+            };
+        }
+        match self.service {
+            Service::Slack => text,
+            // Teams starts a new paragraph only on blank lines.
+            Service::Teams => text.replace('\n', "\n\n"),
+        }
+    }
+}
+
+impl Reporter for ChatNotifier {
+    fn report(
+        &mut self,
+        report: &RunReport,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.failures_only && report.passed() {
+            return Ok(());
+        }
+        let body = serde_json::json!({ "text": self.render(report) });
+        let request = self
+            .client
+            .post(self.url.as_str())
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send();
+        // Reporters run on a blocking thread of the runtime.
+        tokio::runtime::Handle::current()
+            .block_on(request)?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        runner::report::{ExecutorReport, ScenarioReport},
+        thresholds::ThresholdResult,
+    };
+
+    #[test]
+    fn render_failed_run() {
+        let report = RunReport {
+            scenarios: vec![ScenarioReport {
+                name: "checkout".to_string(),
+                executors: vec![ExecutorReport {
+                    name: "Once".to_string(),
+                    started: true,
+                    duration: std::time::Duration::from_secs(2),
+                    iterations: 10,
+                    errors: 1,
+                    iteration_time: Default::default(),
+                    metrics: vec![],
+                    history: vec![],
+                }],
+                metrics: vec![],
+            }],
+            metrics: vec![],
+            counts: vec![],
+            iteration_time: Default::default(),
+            thresholds: vec![ThresholdResult {
+                threshold: "errors: rate < 0.01".to_string(),
+                passed: false,
+                evaluated: true,
+            }],
+            terminated: None,
+            slowest: vec![],
+            errors: vec![],
+        };
+        let notifier = ChatNotifier::slack("http://localhost").report_url("http://ci/report.html");
+        assert_eq!(
+            notifier.render(&report),
+            "*\u{274c} Load test failed*\n\
+            \u{2022} checkout / Once: 10 iterations (5.0/s), 10.00% errors, p95 0.00 ms\n\
+            *Thresholds*\n\
+            \u{274c} errors: rate < 0.01\n\
+            <http://ci/report.html|View report>\n"
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! Single file HTML report of a run, drawn from the metric history kept by every executor.
+//! How far back the charts go is set with
+//! [`MetricsConfig::history_retention`](crate::tracing::task_event::MetricsConfig::history_retention).
+
+use std::{fmt::Write, path::PathBuf};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use super::{Reporter, RunReport};
+use crate::{
+    runner::{progress::series, summary::TOP_ERRORS},
+    tracing::{
+        sink::{fields, Field},
+        task_event::{metrics::MetricValue, MetricSetKey, Sample},
+        ERRORS_TOTAL, ITERATIONS_TOTAL,
+    },
+};
+
+const COLORS: [&str; 6] = [
+    "#4e79a7", "#e15759", "#59a14f", "#f28e2b", "#b07aa1", "#76b7b2",
+];
+const WIDTH: f64 = 640.;
+const HEIGHT: f64 = 200.;
+/// Space left of the plot for labels of the y axis.
+const MARGIN: f64 = 56.;
+
+const HEADER: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<title>rusher report</title>
+<style>
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h2 { margin-top: 2em; border-bottom: 1px solid #ccc; }
+h3 { font-size: 1em; margin-bottom: 0.2em; }
+svg text { font-size: 11px; fill: #555; }
+.legend span { margin-right: 1em; font-size: 0.9em; }
+table { border-collapse: collapse; margin-top: 1em; }
+td { padding: 2px 1em 2px 0; font-family: monospace; }
+</style>
+</head>
+<body>
+"#;
+
+/// Lines sharing the axes of a chart, each a list of (seconds since start, value) points.
+struct Chart {
+    title: String,
+    lines: Vec<(String, Vec<(f64, f64)>)>,
+}
+
+/// Writes the report to a file.
+pub(crate) struct HtmlReport(pub PathBuf);
+
+impl Reporter for HtmlReport {
+    fn report(
+        &mut self,
+        report: &RunReport,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        std::fs::write(&self.0, render(report))?;
+        Ok(())
+    }
+}
+
+/// Report with charts of every metric of each executor that ran, followed by their final values.
+pub(crate) fn render(report: &RunReport) -> String {
+    let now = Utc::now();
+    let mut html = String::from(HEADER);
+    let _ = writeln!(
+        html,
+        "<h1>Run report</h1>\n<p>Generated {}</p>",
+        now.to_rfc3339_opts(SecondsFormat::Secs, true)
+    );
+
+    for scenario in &report.scenarios {
+        for exec in scenario.executors.iter().filter(|x| x.started) {
+            let _ = writeln!(
+                html,
+                "<h2>{} / {}</h2>",
+                escape(&scenario.name),
+                escape(&exec.name)
+            );
+
+            let mut entries = exec.metrics.clone();
+            entries.sort_by_cached_key(|(key, _)| series(key));
+
+            // Close every series with its final value, the last sample may be up to a resolution old.
+            let mut history = exec.history.clone();
+            for (key, value) in &entries {
+                let sample = Sample {
+                    time: now,
+                    value: *value,
+                };
+                match history.iter_mut().find(|(x, _)| x == key) {
+                    Some((_, samples)) => samples.push(sample),
+                    None => history.push((key.clone(), vec![sample])),
+                }
+            }
+            history.sort_by_cached_key(|(key, _)| series(key));
+
+            for chart in charts(&history) {
+                chart.render(&mut html);
+            }
+
+            html.push_str("<table>\n");
+            for (key, value) in &entries {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td>{}</td></tr>",
+                    escape(&series(key)),
+                    escape(&value.to_string())
+                );
+            }
+            html.push_str("</table>\n");
+        }
+    }
+
+    if !report.errors.is_empty() {
+        html.push_str("<h2>Top errors</h2>\n<table>\n");
+        for error in report.errors.iter().take(TOP_ERRORS) {
+            let scenario = &report.scenarios[error.scenario_id];
+            let executor = error
+                .execution_id
+                .map(|id| format!(" / {}", scenario.executors[id].name))
+                .unwrap_or_default();
+            let _ = writeln!(
+                html,
+                "<tr><td>{}&times;</td><td>{}</td><td>{}{}</td><td>{} &ndash; {}</td></tr>",
+                error.count,
+                escape(&error.message),
+                escape(&scenario.name),
+                escape(&executor),
+                error.first.to_rfc3339_opts(SecondsFormat::Secs, true),
+                error.last.to_rfc3339_opts(SecondsFormat::Secs, true),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    if !report.slowest.is_empty() {
+        html.push_str("<h2>Slowest iterations</h2>\n<table>\n");
+        for record in &report.slowest {
+            let scenario = &report.scenarios[record.scenario_id];
+            let groups: Vec<_> = record
+                .groups
+                .iter()
+                .map(|x| format!("{} {:.2?}", x.name, x.duration))
+                .collect();
+            let _ = writeln!(
+                html,
+                "<tr><td>{:.2?}</td><td>{}</td><td>{} / {}</td><td>{}</td><td>{}</td></tr>",
+                record.duration,
+                record.time.to_rfc3339_opts(SecondsFormat::Millis, true),
+                escape(&scenario.name),
+                escape(&scenario.executors[record.execution_id].name),
+                escape(&groups.join(", ")),
+                escape(record.error.as_deref().unwrap_or_default()),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Throughput and errors from the built-in counters, then a chart for every other metric.
+fn charts(history: &[(MetricSetKey, Vec<Sample>)]) -> Vec<Chart> {
+    let start = history
+        .iter()
+        .filter_map(|(_, samples)| samples.first())
+        .map(|x| x.time)
+        .min()
+        .unwrap_or_else(Utc::now);
+    let secs = |time: DateTime<Utc>| (time - start).num_milliseconds() as f64 / 1000.;
+    let counter = |sample: &Sample| match sample.value {
+        MetricValue::Counter(x) => x as f64,
+        _ => 0.,
+    };
+
+    let mut throughput = Chart::new("Throughput (iterations/s)".to_string());
+    let mut errors = Chart::new("Errors".to_string());
+    let mut charts = Vec::new();
+    for (key, samples) in history {
+        match key.name {
+            ITERATIONS_TOTAL => {
+                let rates = samples
+                    .windows(2)
+                    .filter_map(|x| {
+                        let elapsed = secs(x[1].time) - secs(x[0].time);
+                        (elapsed > 0.)
+                            .then(|| (secs(x[1].time), (counter(&x[1]) - counter(&x[0])) / elapsed))
+                    })
+                    .collect();
+                throughput.lines.push((attributes(key), rates));
+            }
+            ERRORS_TOTAL => {
+                let counts = samples.iter().map(|x| (secs(x.time), counter(x))).collect();
+                errors.lines.push((attributes(key), counts));
+            }
+            _ => {
+                let unit = match samples.last().map(|x| x.value) {
+                    Some(
+                        MetricValue::GaugeDuration(_)
+                        | MetricValue::DurationHistogram(_)
+                        | MetricValue::DurationTrend(_),
+                    ) => " (ms)",
+                    _ => "",
+                };
+                let mut chart = Chart::new(format!("{}{unit}", series(key)));
+                for sample in samples {
+                    let fields = fields(&sample.value);
+                    // Percentiles are enough to follow a distribution over time.
+                    let percentiles = fields.iter().any(|(name, _)| *name == "p50");
+                    for (name, field) in fields {
+                        if percentiles && !name.starts_with('p') {
+                            continue;
+                        }
+                        let value = match field {
+                            Field::Float(x) => x,
+                            Field::Integer(x) => x as f64,
+                        };
+                        chart.push(name, (secs(sample.time), value));
+                    }
+                }
+                charts.push(chart);
+            }
+        }
+    }
+
+    [throughput, errors]
+        .into_iter()
+        .filter(|x| !x.lines.is_empty())
+        .chain(charts)
+        .collect()
+}
+
+impl Chart {
+    fn new(title: String) -> Self {
+        Self {
+            title,
+            lines: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, line: &str, point: (f64, f64)) {
+        match self.lines.iter_mut().find(|(x, _)| x == line) {
+            Some((_, points)) => points.push(point),
+            None => self.lines.push((line.to_string(), vec![point])),
+        }
+    }
+
+    fn render(&self, html: &mut String) {
+        let points = || self.lines.iter().flat_map(|(_, x)| x);
+        let x_max = points().map(|x| x.0).fold(0., f64::max).max(1.);
+        let y_min = points().map(|x| x.1).fold(0., f64::min);
+        let mut y_max = points().map(|x| x.1).fold(0., f64::max);
+        if y_max <= y_min {
+            y_max = y_min + 1.;
+        }
+        let x = |value: f64| MARGIN + value / x_max * (WIDTH - MARGIN - 8.);
+        let y = |value: f64| 8. + (y_max - value) / (y_max - y_min) * (HEIGHT - 32.);
+
+        let _ = writeln!(html, "<h3>{}</h3>", escape(&self.title));
+        let _ = writeln!(
+            html,
+            r#"<svg width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}">"#
+        );
+        for step in 0..=4 {
+            let value = y_min + (y_max - y_min) * step as f64 / 4.;
+            let _ = writeln!(
+                html,
+                r##"<line x1="{MARGIN}" x2="{}" y1="{y:.1}" y2="{y:.1}" stroke="#eee"/><text x="{}" y="{:.1}" text-anchor="end">{}</text>"##,
+                WIDTH - 8.,
+                MARGIN - 4.,
+                y(value) + 4.,
+                number(value),
+                y = y(value),
+            );
+        }
+        for step in 0..=4 {
+            let value = x_max * step as f64 / 4.;
+            let _ = writeln!(
+                html,
+                r#"<text x="{:.1}" y="{}" text-anchor="middle">{}s</text>"#,
+                x(value),
+                HEIGHT - 8.,
+                number(value),
+            );
+        }
+        for ((_, line), color) in self.lines.iter().zip(COLORS.iter().cycle()) {
+            if let [(px, py)] = line.as_slice() {
+                let _ = writeln!(
+                    html,
+                    r#"<circle cx="{:.1}" cy="{:.1}" r="3" fill="{color}"/>"#,
+                    x(*px),
+                    y(*py)
+                );
+                continue;
+            }
+            let points: Vec<_> = line
+                .iter()
+                .map(|(px, py)| format!("{:.1},{:.1}", x(*px), y(*py)))
+                .collect();
+            let _ = writeln!(
+                html,
+                r#"<polyline fill="none" stroke="{color}" stroke-width="1.5" points="{}"/>"#,
+                points.join(" ")
+            );
+        }
+        html.push_str("</svg>\n<div class=\"legend\">");
+        for ((name, _), color) in self.lines.iter().zip(COLORS.iter().cycle()) {
+            let _ = write!(
+                html,
+                r#"<span><span style="color:{color}">&#9632;</span> {}</span>"#,
+                escape(name)
+            );
+        }
+        html.push_str("</div>\n");
+    }
+}
+
+/// Attributes of a series as a line label, e.g. `result=ok`.
+fn attributes(key: &MetricSetKey) -> String {
+    if key.attributes.is_empty() {
+        return key.name.to_string();
+    }
+    let attributes: Vec<_> = key
+        .attributes
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect();
+    attributes.join(",")
+}
+
+fn number(value: f64) -> String {
+    if value.abs() >= 100. || value.fract() == 0. {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracing::task_event::{metrics::MetricType, Value};
+
+    #[test]
+    fn throughput_from_iteration_counter() {
+        let start = Utc::now();
+        let key = MetricSetKey {
+            name: ITERATIONS_TOTAL,
+            metric_type: MetricType::Counter,
+            attributes: vec![("result", Value::String("ok".to_string()))],
+        };
+        let samples = [(0, 0), (1, 10), (2, 30)]
+            .into_iter()
+            .map(|(secs, count)| Sample {
+                time: start + chrono::Duration::seconds(secs),
+                value: MetricValue::Counter(count),
+            })
+            .collect();
+
+        let charts = charts(&[(key, samples)]);
+        assert_eq!(charts.len(), 1);
+        assert_eq!(charts[0].lines[0].0, "result=ok");
+        assert_eq!(charts[0].lines[0].1, [(1., 10.), (2., 20.)]);
+
+        let mut html = String::new();
+        charts[0].render(&mut html);
+        assert!(html.contains("<polyline"));
+    }
+}
@@ -0,0 +1,110 @@
+//! Outcome of a run as a single line of JSON, printed last so that shell pipelines can read
+//! it with `tail -n 1 | jq` instead of parsing the summary, and the full report served by
+//! the web server.
+
+#[cfg(feature = "web")]
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Value};
+
+use super::{IterationTime, RunReport};
+use crate::tracing::sink::millis;
+
+pub(crate) fn result_line(report: &RunReport) -> String {
+    result(report).to_string()
+}
+
+/// Outcome of the run followed by the results of every executor and its error messages,
+/// `final` is false while the run is in progress.
+#[cfg(feature = "web")]
+pub(crate) fn document(report: &RunReport, ended: bool) -> Value {
+    let mut document = result(report);
+    document["final"] = json!(ended);
+    document["scenarios"] = report
+        .scenarios
+        .iter()
+        .map(|scenario| {
+            let executors: Vec<_> = scenario
+                .executors
+                .iter()
+                .map(|exec| {
+                    json!({
+                        "name": exec.name,
+                        "started": exec.started,
+                        "duration_secs": exec.duration.as_secs_f64(),
+                        "iterations": exec.iterations,
+                        "iterations_per_sec": exec.rate(),
+                        "errors": exec.errors,
+                        "error_rate": exec.error_rate(),
+                        "iteration_time_ms": iteration_time(exec.iteration_time),
+                        "metrics": exec.metrics,
+                    })
+                })
+                .collect();
+            json!({
+                "name": scenario.name,
+                "executors": executors,
+                "metrics": scenario.metrics,
+            })
+        })
+        .collect();
+    document["metrics"] = json!(report.metrics);
+    document["error_messages"] = report
+        .errors
+        .iter()
+        .map(|error| {
+            json!({
+                "scenario": report.scenarios.get(error.scenario_id).map(|x| &x.name),
+                "executor": error.execution_id,
+                "message": error.message,
+                "count": error.count,
+                "first": time(error.first),
+                "last": time(error.last),
+            })
+        })
+        .collect();
+    document["slowest"] = json!(report.slowest);
+    document
+}
+
+#[cfg(feature = "web")]
+fn time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, false)
+}
+
+fn iteration_time(time: IterationTime) -> Value {
+    json!({
+        "avg": millis(time.mean),
+        "p50": millis(time.p50),
+        "p90": millis(time.p90),
+        "p95": millis(time.p95),
+        "p99": millis(time.p99),
+        "max": millis(time.max),
+    })
+}
+
+fn result(report: &RunReport) -> Value {
+    let executors = report.scenarios.iter().flat_map(|x| &x.executors);
+    let (iterations, errors) = executors.fold((0, 0), |(iterations, errors), x| {
+        (iterations + x.iterations, errors + x.errors)
+    });
+    let duration = report.duration().as_secs_f64();
+    let ratio = |x: u64, y: f64| if y > 0. { x as f64 / y } else { 0. };
+    let time = report.iteration_time;
+    let terminated = report.terminated.as_ref().map(|(id, err)| {
+        json!({
+            "scenario": report.scenarios.get(*id).map(|x| &x.name),
+            "error": err,
+        })
+    });
+    json!({
+        "status": if report.passed() { "pass" } else { "fail" },
+        "thresholds": report.thresholds,
+        "terminated": terminated,
+        "duration_secs": duration,
+        "iterations": iterations,
+        "iterations_per_sec": ratio(iterations, duration),
+        "errors": errors,
+        "error_rate": ratio(errors, iterations as f64),
+        "iteration_time_ms": iteration_time(time),
+    })
+}
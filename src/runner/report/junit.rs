@@ -0,0 +1,147 @@
+//! JUnit XML report of a run for CI servers. Every scenario is a test suite with a test case for
+//! each of its executors, and thresholds are test cases of a suite of their own.
+//!
+//! Executors fail when a user terminated the run while their scenario was running, executors
+//! that never started are skipped.
+
+use std::{fmt::Write, path::PathBuf};
+
+use super::{Reporter, RunReport};
+
+/// Writes the report to a file.
+pub(crate) struct JunitReport(pub PathBuf);
+
+impl Reporter for JunitReport {
+    fn report(
+        &mut self,
+        report: &RunReport,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        std::fs::write(&self.0, render(report))?;
+        Ok(())
+    }
+}
+
+struct Case {
+    name: String,
+    time: f64,
+    outcome: Outcome,
+    output: String,
+}
+
+enum Outcome {
+    Passed,
+    Failed(String),
+    Skipped,
+}
+
+fn render(report: &RunReport) -> String {
+    let mut suites: Vec<(String, Vec<Case>)> = Vec::new();
+    for (scenario_id, scenario) in report.scenarios.iter().enumerate() {
+        let terminated = report
+            .terminated
+            .as_ref()
+            .filter(|(id, _)| *id == scenario_id)
+            .map(|(_, err)| err);
+        let cases = scenario
+            .executors
+            .iter()
+            .map(|exec| {
+                let outcome = match terminated {
+                    _ if !exec.started => Outcome::Skipped,
+                    Some(err) => Outcome::Failed(format!("terminated: {err}")),
+                    None => Outcome::Passed,
+                };
+                Case {
+                    name: exec.name.clone(),
+                    time: exec.duration.as_secs_f64(),
+                    outcome,
+                    output: format!("iterations {}, errors {}", exec.iterations, exec.errors),
+                }
+            })
+            .collect();
+        suites.push((scenario.name.clone(), cases));
+    }
+
+    if !report.thresholds.is_empty() {
+        let cases = report
+            .thresholds
+            .iter()
+            .map(|result| Case {
+                name: result.threshold.clone(),
+                time: 0.,
+                outcome: match result.passed {
+                    true => Outcome::Passed,
+                    false => Outcome::Failed("threshold failed".to_string()),
+                },
+                output: String::new(),
+            })
+            .collect();
+        suites.push(("thresholds".to_string(), cases));
+    }
+
+    let failures = |cases: &[Case]| {
+        cases
+            .iter()
+            .filter(|x| matches!(x.outcome, Outcome::Failed(_)))
+            .count()
+    };
+    let skipped = |cases: &[Case]| {
+        cases
+            .iter()
+            .filter(|x| matches!(x.outcome, Outcome::Skipped))
+            .count()
+    };
+    let time = |cases: &[Case]| cases.iter().map(|x| x.time).sum::<f64>();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        r#"<testsuites name="rusher" tests="{}" failures="{}" time="{:.3}">"#,
+        suites.iter().map(|(_, x)| x.len()).sum::<usize>(),
+        suites.iter().map(|(_, x)| failures(x)).sum::<usize>(),
+        suites.iter().map(|(_, x)| time(x)).sum::<f64>(),
+    );
+    for (name, cases) in &suites {
+        let _ = writeln!(
+            xml,
+            r#"  <testsuite name="{}" tests="{}" failures="{}" skipped="{}" time="{:.3}">"#,
+            escape(name),
+            cases.len(),
+            failures(cases),
+            skipped(cases),
+            time(cases),
+        );
+        for case in cases {
+            let _ = write!(
+                xml,
+                r#"    <testcase classname="{}" name="{}" time="{:.3}">"#,
+                escape(name),
+                escape(&case.name),
+                case.time,
+            );
+            match &case.outcome {
+                Outcome::Passed => (),
+                Outcome::Failed(message) => {
+                    let _ = write!(xml, r#"<failure message="{}"/>"#, escape(message));
+                }
+                Outcome::Skipped => xml.push_str("<skipped/>"),
+            }
+            if !case.output.is_empty() {
+                let _ = write!(xml, "<system-out>{}</system-out>", escape(&case.output));
+            }
+            xml.push_str("</testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,208 @@
+//! Summary of a run in the schema of k6's `--summary-export`, so that dashboards and scripts
+//! built around k6 results can read rusher runs unchanged.
+//!
+//! Metrics are combined over every executor. Built-in metrics take the names k6 uses:
+//! [`ITERATIONS_TOTAL`] is `iterations`, the time of iterations is `iteration_duration`,
+//! [`HTTP_STATUS`] is `http_reqs` and [`CHECKS`] is `checks` along with a check per name in
+//! `root_group`. Other metrics keep their name, followed by their attributes such as
+//! `latency{route:/login}`. Durations are in milliseconds and thresholds are listed under their
+//! metric, `true` meaning that the threshold failed as in k6.
+
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+
+use serde_json::{json, Map, Value};
+
+use super::{IterationTime, Reporter, RunReport};
+use crate::tracing::{
+    sink::millis,
+    task_event::{metrics::MetricValue, MetricSetKey},
+    CHECKS, HTTP_STATUS, ITERATIONS_TOTAL,
+};
+
+/// Writes the summary to a file.
+pub(crate) struct K6Report(pub PathBuf);
+
+impl Reporter for K6Report {
+    fn report(
+        &mut self,
+        report: &RunReport,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        std::fs::write(&self.0, serde_json::to_vec_pretty(&render(report))?)?;
+        Ok(())
+    }
+}
+
+fn render(report: &RunReport) -> Value {
+    let seconds = report.duration().as_secs_f64();
+    let rate = |count: u64| match seconds {
+        x if x > 0. => count as f64 / x,
+        _ => 0.,
+    };
+    let counts: HashMap<_, _> = report.counts.iter().cloned().collect();
+
+    let mut metrics = Map::new();
+    let mut iterations = 0;
+    let mut requests = 0;
+    let mut checks: Vec<(String, (u64, u64))> = Vec::new();
+    for (key, value) in &report.metrics {
+        match (key.name, value) {
+            (ITERATIONS_TOTAL, MetricValue::Counter(x)) => iterations += x,
+            (HTTP_STATUS, MetricValue::Counter(x)) => requests += x,
+            (CHECKS, MetricValue::Counter(x)) => {
+                let attribute = |name| {
+                    key.attributes
+                        .iter()
+                        .find(|(x, _)| *x == name)
+                        .map(|(_, x)| x.to_string())
+                };
+                let name = attribute("check").unwrap_or_default();
+                let index = match checks.iter().position(|(x, _)| *x == name) {
+                    Some(index) => index,
+                    None => {
+                        checks.push((name, (0, 0)));
+                        checks.len() - 1
+                    }
+                };
+                match attribute("result").as_deref() {
+                    Some("pass") => checks[index].1 .0 += x,
+                    _ => checks[index].1 .1 += x,
+                }
+            }
+            _ => {
+                let count = counts.get(key).copied().unwrap_or_default();
+                metrics.insert(name(key), metric(value, count, rate));
+            }
+        }
+    }
+    metrics.insert(
+        "iterations".to_string(),
+        json!({"count": iterations, "rate": rate(iterations)}),
+    );
+    if iterations > 0 {
+        metrics.insert(
+            "iteration_duration".to_string(),
+            trend(report.iteration_time),
+        );
+    }
+    if requests > 0 {
+        metrics.insert(
+            "http_reqs".to_string(),
+            json!({"count": requests, "rate": rate(requests)}),
+        );
+    }
+    if !checks.is_empty() {
+        let (passes, fails) = checks
+            .iter()
+            .fold((0, 0), |(p, f), (_, (x, y))| (p + x, f + y));
+        metrics.insert("checks".to_string(), ratio(passes, fails));
+    }
+
+    for result in &report.thresholds {
+        let (metric, condition) = result
+            .threshold
+            .split_once(": ")
+            .unwrap_or((&result.threshold, ""));
+        let metric = match metric {
+            ITERATIONS_TOTAL => "iterations",
+            HTTP_STATUS => "http_reqs",
+            x => x,
+        };
+        let entry = metrics
+            .entry(metric.to_string())
+            .or_insert_with(|| json!({}));
+        entry["thresholds"][condition] = (!result.passed).into();
+    }
+
+    let checks: Map<_, _> = checks
+        .into_iter()
+        .map(|(name, (passes, fails))| {
+            let check = json!({
+                "name": name,
+                "path": format!("::{name}"),
+                "passes": passes,
+                "fails": fails,
+            });
+            (name, check)
+        })
+        .collect();
+    json!({
+        "root_group": {"name": "", "path": "", "groups": {}, "checks": checks},
+        "metrics": metrics,
+    })
+}
+
+/// Name of a metric followed by its attributes, as k6 names submetrics.
+fn name(key: &MetricSetKey) -> String {
+    if key.attributes.is_empty() {
+        return key.name.to_string();
+    }
+    let attributes: Vec<_> = key
+        .attributes
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}"))
+        .collect();
+    format!("{}{{{}}}", key.name, attributes.join(","))
+}
+
+fn metric(value: &MetricValue, count: u64, rate: impl Fn(u64) -> f64) -> Value {
+    let stats = |avg: f64, min: f64, (p50, p90, p95, p99): (f64, f64, f64, f64), max: f64| {
+        json!({
+            "avg": avg,
+            "min": min,
+            "med": p50,
+            "max": max,
+            "p(90)": p90,
+            "p(95)": p95,
+            "p(99)": p99,
+        })
+    };
+    let durations = |(p50, p90, p95, p99): (Duration, Duration, Duration, Duration)| {
+        (millis(p50), millis(p90), millis(p95), millis(p99))
+    };
+    let gauge = |x: f64| json!({"value": x, "min": x, "max": x});
+    let avg = |sum: f64| match count {
+        0 => 0.,
+        n => sum / n as f64,
+    };
+    match *value {
+        MetricValue::Counter(x) => json!({"count": x, "rate": rate(x)}),
+        MetricValue::GaugeF64(x) => gauge(x),
+        MetricValue::GaugeI64(x) => gauge(x as f64),
+        MetricValue::GaugeU64(x) => gauge(x as f64),
+        MetricValue::GaugeDuration(x) => gauge(millis(x)),
+        MetricValue::Histogram((p, sum, (min, max))) => stats(avg(sum), min, p, max),
+        MetricValue::DurationHistogram((p, sum, (min, max))) => {
+            stats(avg(millis(sum)), millis(min), durations(p), millis(max))
+        }
+        MetricValue::Trend(x) => stats(x.mean, x.min, x.percentiles, x.max),
+        MetricValue::DurationTrend(x) => stats(
+            millis(x.mean),
+            millis(x.min),
+            durations(x.percentiles),
+            millis(x.max),
+        ),
+        MetricValue::Rate((x, _)) => json!({"count": count, "rate": x}),
+        MetricValue::Bytes((x, (per_second, _))) => json!({"count": x, "rate": per_second}),
+        MetricValue::Ratio((trues, total)) => ratio(trues, total - trues),
+    }
+}
+
+fn trend(time: IterationTime) -> Value {
+    json!({
+        "avg": millis(time.mean),
+        "min": millis(time.min),
+        "med": millis(time.p50),
+        "max": millis(time.max),
+        "p(90)": millis(time.p90),
+        "p(95)": millis(time.p95),
+        "p(99)": millis(time.p99),
+    })
+}
+
+fn ratio(passes: u64, fails: u64) -> Value {
+    let value = match passes + fails {
+        0 => 0.,
+        x => passes as f64 / x as f64,
+    };
+    json!({"passes": passes, "fails": fails, "value": value})
+}
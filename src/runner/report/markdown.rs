@@ -0,0 +1,150 @@
+//! Markdown report of a run, a table of every executor followed by threshold outcomes.
+//! Compact enough to paste into a pull request comment or a wiki page.
+//!
+//! A report of an earlier run can be given as a baseline, numbers of executors found in both
+//! are then followed by their change relative to the baseline, e.g. `12.40 (+3.2%)`.
+
+use std::{collections::HashMap, fmt::Write, path::PathBuf};
+
+use super::{Reporter, RunReport};
+use crate::tracing::sink::millis;
+
+const HEADER: &str = "| scenario | executor | iterations | iterations/s | errors (%) | avg (ms) | p95 (ms) | p99 (ms) |";
+
+/// Numeric columns of every executor keyed by scenario and executor name.
+type Rows = HashMap<(String, String), Vec<f64>>;
+
+/// Writes the report to a file, compared with the report at the baseline path if any.
+pub(crate) struct MarkdownReport {
+    pub path: PathBuf,
+    pub baseline: Option<PathBuf>,
+}
+
+impl Reporter for MarkdownReport {
+    fn report(
+        &mut self,
+        report: &RunReport,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let baseline =
+            self.baseline
+                .as_ref()
+                .and_then(|path| match std::fs::read_to_string(path) {
+                    Ok(content) => Some(content),
+                    Err(err) => {
+                        tracing::warn!("failed to read baseline {}: {err}", path.display());
+                        None
+                    }
+                });
+        std::fs::write(&self.path, render(report, baseline.as_deref()))?;
+        Ok(())
+    }
+}
+
+fn render(report: &RunReport, baseline: Option<&str>) -> String {
+    let baseline = baseline.map(parse).unwrap_or_default();
+
+    let mut markdown = String::from("## Load test results\n\n");
+    markdown.push_str(HEADER);
+    markdown.push_str("\n|---|---|--:|--:|--:|--:|--:|--:|\n");
+    for scenario in &report.scenarios {
+        for exec in scenario.executors.iter().filter(|x| x.started) {
+            let time = exec.iteration_time;
+            let values = [
+                exec.iterations as f64,
+                exec.rate(),
+                exec.error_rate() * 100.,
+                millis(time.mean),
+                millis(time.p95),
+                millis(time.p99),
+            ];
+            let (scenario, executor) = (escape(&scenario.name), escape(&exec.name));
+            let previous = baseline.get(&(scenario.clone(), executor.clone()));
+            let _ = write!(markdown, "| {scenario} | {executor} |");
+            for (column, value) in values.iter().enumerate() {
+                let delta = previous
+                    .and_then(|x| x.get(column))
+                    .and_then(|previous| delta(*previous, *value));
+                let value = match column {
+                    0 => format!("{value:.0}"),
+                    _ => format!("{value:.2}"),
+                };
+                match delta {
+                    Some(delta) => {
+                        let _ = write!(markdown, " {value} ({delta}) |");
+                    }
+                    None => {
+                        let _ = write!(markdown, " {value} |");
+                    }
+                }
+            }
+            markdown.push('\n');
+        }
+    }
+
+    if !report.thresholds.is_empty() {
+        markdown.push_str("\n### Thresholds\n\n");
+        for result in &report.thresholds {
+            let outcome = if result.passed {
+                "passed"
+            } else {
+                "**failed**"
+            };
+            let _ = writeln!(markdown, "- `{}`: {outcome}", result.threshold);
+        }
+    }
+    markdown
+}
+
+/// Rows of the table of an earlier report, deltas that followed its numbers are ignored.
+fn parse(report: &str) -> Rows {
+    report
+        .lines()
+        .filter_map(|line| line.strip_prefix("| ")?.strip_suffix(" |"))
+        .filter_map(|line| {
+            let cells: Vec<_> = line.split(" | ").collect();
+            let [scenario, executor, numbers @ ..] = cells.as_slice() else {
+                return None;
+            };
+            let values = numbers
+                .iter()
+                .map(|x| x.split(' ').next()?.parse().ok())
+                .collect::<Option<Vec<f64>>>()?;
+            Some(((scenario.to_string(), executor.to_string()), values))
+        })
+        .collect()
+}
+
+/// Relative change from `previous` to `current`, e.g. `+3.2%`.
+fn delta(previous: f64, current: f64) -> Option<String> {
+    if previous == 0. {
+        return None;
+    }
+    Some(format!("{:+.1}%", (current - previous) / previous * 100.))
+}
+
+/// Pipes would end a table cell early.
+fn escape(value: &str) -> String {
+    value.replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{delta, parse};
+
+    #[test]
+    fn parse_baseline() {
+        let report = "## Load test results\n\n\
+            | scenario | executor | iterations | iterations/s | errors (%) | avg (ms) | p95 (ms) | p99 (ms) |\n\
+            |---|---|--:|--:|--:|--:|--:|--:|\n\
+            | checkout | Once | 1 (+0.0%) | 250.00 | 0.00 | 4.00 | 4.00 | 4.00 |\n";
+
+        let rows = parse(report);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(
+            rows[&("checkout".to_string(), "Once".to_string())],
+            [1., 250., 0., 4., 4., 4.]
+        );
+        assert_eq!(delta(4., 5.).as_deref(), Some("+25.0%"));
+        assert_eq!(delta(0., 5.), None);
+    }
+}
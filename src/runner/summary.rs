@@ -0,0 +1,366 @@
+//! Summary of every executor printed once the run is over, with totals, error rates,
+//! percentiles of iteration time and the final value of custom metrics.
+
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{DateTime, Utc};
+
+use super::progress::{error_classes, series, Window};
+use crate::state::{App, ExecutorState};
+use crate::tracing::{
+    message::{IterationRecord, Message},
+    CHECKS, ERRORS_TOTAL, HTTP_STATUS, ITERATIONS_TOTAL,
+};
+
+/// State of the run along with the iteration times of every executor, final once the run ends.
+pub struct Summary {
+    app: App,
+    times: Vec<Vec<Window>>,
+    /// Scenario running, errors are counted against it.
+    scenario: usize,
+    terminated: Option<(usize, String)>,
+    /// Slowest first.
+    slowest: Vec<IterationRecord>,
+    slowest_len: usize,
+    errors: HashMap<(usize, Option<usize>, String), ErrorSummary>,
+    ended: bool,
+}
+
+/// Errors of an executor with the same message once numbers are masked.
+#[derive(Debug, Clone)]
+pub struct ErrorSummary {
+    pub scenario_id: usize,
+    pub execution_id: Option<usize>,
+    /// message with every number replaced by `N`, truncated to [`MAX_ERROR_LEN`] characters
+    pub message: String,
+    pub count: u64,
+    pub first: DateTime<Utc>,
+    pub last: DateTime<Utc>,
+}
+
+/// Longest error message kept, in characters.
+pub const MAX_ERROR_LEN: usize = 200;
+
+/// Distinct messages kept over the run, messages seen after that are left out.
+const MAX_ERROR_MESSAGES: usize = 1000;
+
+/// Number of error messages printed and shown in the HTML report.
+pub const TOP_ERRORS: usize = 10;
+
+/// Collect the state of the run until it ends, keeping the `slowest` iterations
+/// if the tracing layer sends [`Message::Iteration`]s.
+pub async fn run(app: App, mut rx: crate::Receiver<Message>, slowest: usize) -> Summary {
+    let mut summary = Summary::new(app, slowest);
+    while let Some(message) = rx.recv().await {
+        summary.handle_message(message);
+        if summary.ended {
+            break;
+        }
+    }
+    summary
+}
+
+impl Summary {
+    pub fn new(app: App, slowest: usize) -> Self {
+        Self {
+            app,
+            times: Vec::new(),
+            scenario: 0,
+            terminated: None,
+            slowest: Vec::with_capacity(slowest + 1),
+            slowest_len: slowest,
+            errors: HashMap::new(),
+            ended: false,
+        }
+    }
+
+    pub fn handle_message(&mut self, message: Message) {
+        let scenario = self.scenario;
+        match &message {
+            Message::End => {
+                self.ended = true;
+                return;
+            }
+            Message::Iteration(record)
+                if self.slowest.len() < self.slowest_len
+                    || self
+                        .slowest
+                        .last()
+                        .is_some_and(|x| x.duration < record.duration) =>
+            {
+                let slow = &mut self.slowest;
+                let index = slow.partition_point(|x| x.duration >= record.duration);
+                slow.insert(index, record.clone());
+                slow.truncate(self.slowest_len);
+            }
+            Message::ScenarioChanged { scenario_id } => self.scenario = *scenario_id,
+            Message::Error { execution_id, err } => {
+                let message = normalize(err);
+                let now = Utc::now();
+                let key = (scenario, *execution_id, message);
+                if let Some(summary) = self.errors.get_mut(&key) {
+                    summary.count += 1;
+                    summary.last = now;
+                } else if self.errors.len() < MAX_ERROR_MESSAGES {
+                    let summary = ErrorSummary {
+                        scenario_id: scenario,
+                        execution_id: *execution_id,
+                        message: key.2.clone(),
+                        count: 1,
+                        first: now,
+                        last: now,
+                    };
+                    self.errors.insert(key, summary);
+                }
+            }
+            Message::TerminatedError { err } => self.terminated = Some((scenario, err.clone())),
+            Message::TaskTime {
+                execution_id,
+                scenario_id,
+                duration,
+            } => {
+                let times = &mut self.times;
+                if times.len() <= *scenario_id {
+                    times.resize_with(scenario_id + 1, Vec::new);
+                }
+                let execs = &mut times[*scenario_id];
+                if execs.len() <= *execution_id {
+                    execs.resize_with(execution_id + 1, Window::default);
+                }
+                execs[*execution_id].observe(*duration);
+            }
+            _ => (),
+        }
+        self.app.handle_message(message);
+    }
+
+    /// Whether [`Message::End`] was received, the state is final.
+    pub fn ended(&self) -> bool {
+        self.ended
+    }
+
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    /// Index of the scenario that was running when a user terminated the run, with the error.
+    pub fn terminated(&self) -> Option<(usize, &str)> {
+        self.terminated
+            .as_ref()
+            .map(|(id, err)| (*id, err.as_str()))
+    }
+
+    /// Slowest iterations of the run, slowest first.
+    pub fn slowest(&self) -> &[IterationRecord] {
+        &self.slowest
+    }
+
+    /// Error messages of the run, most frequent first.
+    pub fn errors(&self) -> Vec<ErrorSummary> {
+        let mut errors: Vec<_> = self.errors.values().cloned().collect();
+        errors.sort_by(|x, y| y.count.cmp(&x.count).then_with(|| x.first.cmp(&y.first)));
+        errors
+    }
+
+    /// Estimated quantile of the iteration time of an executor.
+    pub fn iteration_quantile(
+        &mut self,
+        scenario: usize,
+        executor: usize,
+        quantile: f64,
+    ) -> Duration {
+        self.times
+            .get_mut(scenario)
+            .and_then(|x| x.get_mut(executor))
+            .map(|x| x.quantile(quantile))
+            .unwrap_or_default()
+    }
+
+    /// Estimated quantile of the iteration time over every executor of the run.
+    pub fn run_iteration_quantile(&mut self, quantile: f64) -> Duration {
+        Window::merged(self.times.iter_mut().flatten()).quantile(quantile)
+    }
+
+    pub fn print(&mut self) {
+        for (scenario_id, scenario) in self.app.scenarios().iter().enumerate() {
+            for (id, exec) in scenario.execs().iter().enumerate() {
+                if !exec.started() {
+                    continue;
+                }
+                let window = self.times.get_mut(scenario_id).and_then(|x| x.get_mut(id));
+                println!("{} / {}", scenario.name(), exec.config());
+                print_exec(exec, window);
+            }
+
+            if scenario.execs().iter().filter(|x| x.started()).count() > 1 {
+                println!("{}: all executors", scenario.name());
+                let mut metrics: Vec<_> = scenario
+                    .metrics()
+                    .iter()
+                    .map(|(key, value)| (series(key), value.to_string()))
+                    .collect();
+                metrics.sort();
+                for (series, value) in metrics {
+                    row(&series, value);
+                }
+            }
+        }
+
+        let errors = self.errors();
+        if !errors.is_empty() {
+            println!("top errors");
+        }
+        for error in errors.iter().take(TOP_ERRORS) {
+            let first = error.first.format("%H:%M:%S");
+            let last = error.last.format("%H:%M:%S");
+            row(
+                &format!("{}\u{d7}", error.count),
+                format!("{} ({first} - {last})", error.message),
+            );
+        }
+
+        if !self.slowest.is_empty() {
+            println!("slowest iterations");
+        }
+        for record in &self.slowest {
+            let scenario = &self.app.scenarios()[record.scenario_id];
+            let mut details = vec![format!(
+                "{} / {} at {}",
+                scenario.name(),
+                scenario.execs()[record.execution_id].config(),
+                record.time.format("%H:%M:%S%.3f"),
+            )];
+            details.extend(
+                record
+                    .groups
+                    .iter()
+                    .map(|x| format!("{} {:.2?}", x.name, x.duration)),
+            );
+            details.extend(record.error.as_ref().map(|x| format!("error: {x}")));
+            row(&format!("{:.2?}", record.duration), details.join(", "));
+        }
+
+        for result in self.app.thresholds() {
+            let outcome = match (result.passed, result.evaluated) {
+                (true, _) => "passed",
+                (false, true) => "FAILED",
+                (false, false) => "FAILED, no data",
+            };
+            println!("threshold {}: {outcome}", result.threshold);
+        }
+    }
+}
+
+fn print_exec(exec: &ExecutorState, window: Option<&mut Window>) {
+    let elapsed = exec.duration().as_secs_f64();
+    let iterations = exec.iterations();
+    let rate = if elapsed > 0. {
+        iterations as f64 / elapsed
+    } else {
+        0.
+    };
+    let error_rate = if iterations > 0 {
+        exec.errors() as f64 / iterations as f64 * 100.
+    } else {
+        0.
+    };
+
+    row("duration", format!("{:.0?}", exec.duration()));
+    row("iterations", format!("{iterations} ({rate:.1}/s)"));
+    row("errors", format!("{} ({error_rate:.2}%)", exec.errors()));
+    for (class, count) in error_classes(exec) {
+        row("", format!("{count}\u{d7} {class}"));
+    }
+
+    if iterations > 0 {
+        let (min, avg, max) = exec.iteration_times();
+        let (p50, p95, p99) = match window {
+            Some(window) => (
+                window.quantile(0.5),
+                window.quantile(0.95),
+                window.quantile(0.99),
+            ),
+            None => Default::default(),
+        };
+        row(
+            "iteration time",
+            format!(
+                "min {min:.2?}, avg {avg:.2?}, p50 {p50:.2?}, p95 {p95:.2?}, p99 {p99:.2?}, max {max:.2?}"
+            ),
+        );
+    }
+
+    let status_classes = exec.status_classes();
+    if !status_classes.is_empty() {
+        let classes: Vec<_> = status_classes
+            .iter()
+            .map(|(class, count)| format!("{class}={count}"))
+            .collect();
+        row("http", classes.join(" "));
+    }
+    for check in exec.checks() {
+        row(
+            &format!("check {:?}", check.name),
+            format!(
+                "{:.2}% passed ({}/{})",
+                check.pass_rate() * 100.,
+                check.passes,
+                check.passes + check.fails,
+            ),
+        );
+    }
+
+    // Metrics recorded by users, built in ones are already covered above.
+    let mut metrics: Vec<_> = exec
+        .metrics()
+        .iter()
+        .filter(|(key, _)| {
+            ![ITERATIONS_TOTAL, ERRORS_TOTAL, CHECKS, HTTP_STATUS].contains(&key.name)
+        })
+        .filter_map(|(key, values)| Some((series(key), values.back()?.to_string())))
+        .collect();
+    metrics.sort();
+    for (series, value) in metrics {
+        row(&series, value);
+    }
+}
+
+fn row(label: &str, value: impl std::fmt::Display) {
+    println!("  {label:<24} {value}");
+}
+
+/// Error message with numbers such as ids and ports masked, so that otherwise equal
+/// errors are counted together. Long messages are truncated.
+fn normalize(err: &str) -> String {
+    let mut message = String::new();
+    let mut len = 0;
+    let mut number = false;
+    for c in err.split_whitespace().collect::<Vec<_>>().join(" ").chars() {
+        if len == MAX_ERROR_LEN {
+            message.push('\u{2026}');
+            break;
+        }
+        let digit = c.is_ascii_digit();
+        if !(digit && number) {
+            message.push(if digit { 'N' } else { c });
+            len += 1;
+        }
+        number = digit;
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize, MAX_ERROR_LEN};
+
+    #[test]
+    fn normalize_error() {
+        assert_eq!(
+            normalize("order 1234 failed:\n  status 503"),
+            "order N failed: status N"
+        );
+        let long = normalize(&"x".repeat(500));
+        assert_eq!(long.chars().count(), MAX_ERROR_LEN + 1);
+    }
+}
@@ -0,0 +1,201 @@
+//! Webhooks notified of the lifecycle of a run, so that chat-ops and incident tooling can react
+//! to load tests without polling.
+//!
+//! Every event is posted as a JSON object with an `event` field:
+//! - `run_start` with the names of every scenario and executor,
+//! - `threshold_breach` whenever a threshold starts failing, with the threshold and the scenario,
+//! - `run_end` with a summary of every executor, the outcome of thresholds and whether the run `passed`.
+//!
+//! ```no_run
+//! use rusher::runner::{webhook::Webhook, Runner};
+//!
+//! let runner = Runner::new(vec![]).webhook(
+//!     Webhook::new("https://hooks.example.com/load-tests").header("Authorization", "Bearer token"),
+//! );
+//! ```
+
+use std::collections::HashSet;
+
+use chrono::Utc;
+
+use super::{Reporter, RunReport};
+use crate::tracing::{message::Message, sink::MetricSink};
+
+/// Events of a run a [`Webhook`] is notified of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WebhookEvent {
+    RunStart,
+    RunEnd,
+    ThresholdBreach,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::RunStart => "run_start",
+            WebhookEvent::RunEnd => "run_end",
+            WebhookEvent::ThresholdBreach => "threshold_breach",
+        }
+    }
+}
+
+/// Endpoint notified of run events, register it with [`Runner::webhook`](super::Runner::webhook).
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    client: reqwest::Client,
+    url: String,
+    headers: Vec<(String, String)>,
+    events: HashSet<WebhookEvent>,
+    scenarios: Vec<(String, Vec<String>)>,
+    scenario: usize,
+    failing: HashSet<String>,
+}
+
+impl Webhook {
+    /// Post every event to `url`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            headers: Vec::new(),
+            events: HashSet::from([
+                WebhookEvent::RunStart,
+                WebhookEvent::RunEnd,
+                WebhookEvent::ThresholdBreach,
+            ]),
+            scenarios: Vec::new(),
+            scenario: 0,
+            failing: HashSet::new(),
+        }
+    }
+
+    /// Header sent with every request, such as `Authorization`.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Post only these events, every event by default.
+    pub fn events(mut self, events: impl IntoIterator<Item = WebhookEvent>) -> Self {
+        self.events = events.into_iter().collect();
+        self
+    }
+
+    fn notifies(&self, event: WebhookEvent) -> bool {
+        self.events.contains(&event)
+    }
+
+    fn post(&self, event: WebhookEvent, mut body: serde_json::Value) -> reqwest::Result<()> {
+        body["event"] = event.as_str().into();
+        body["time"] = Utc::now().to_rfc3339().into();
+        let mut request = self.client.post(self.url.as_str());
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        let request = request
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send();
+        // Sinks and reporters run on a blocking thread of the runtime.
+        tokio::runtime::Handle::current()
+            .block_on(request)?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+impl MetricSink for Webhook {
+    fn start(&mut self, scenarios: &[(String, Vec<String>)]) {
+        self.scenarios = scenarios.to_vec();
+        if self.notifies(WebhookEvent::RunStart) {
+            let scenarios: Vec<_> = scenarios
+                .iter()
+                .map(|(name, executors)| serde_json::json!({"name": name, "executors": executors}))
+                .collect();
+            let body = serde_json::json!({ "scenarios": scenarios });
+            let _ = self.post(WebhookEvent::RunStart, body);
+        }
+    }
+
+    fn handle(&mut self, message: &Message) {
+        match message {
+            Message::ScenarioChanged { scenario_id } => self.scenario = *scenario_id,
+            Message::Thresholds { results } => {
+                for result in results {
+                    if result.passed {
+                        self.failing.remove(&result.threshold);
+                    } else if self.failing.insert(result.threshold.clone())
+                        && self.notifies(WebhookEvent::ThresholdBreach)
+                    {
+                        let scenario = self.scenarios.get(self.scenario).map(|x| &x.0);
+                        let body = serde_json::json!({
+                            "threshold": result.threshold,
+                            "scenario": scenario,
+                        });
+                        let _ = self.post(WebhookEvent::ThresholdBreach, body);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Reporter for Webhook {
+    fn report(
+        &mut self,
+        report: &RunReport,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.notifies(WebhookEvent::RunEnd) {
+            self.post(WebhookEvent::RunEnd, summary(report))?;
+        }
+        Ok(())
+    }
+}
+
+/// Summary of a run as JSON, the payload of the `run_end` event.
+pub(crate) fn summary(report: &RunReport) -> serde_json::Value {
+    let millis = |x: std::time::Duration| x.as_secs_f64() * 1000.;
+    let scenarios: Vec<_> = report
+        .scenarios
+        .iter()
+        .map(|scenario| {
+            let executors: Vec<_> = scenario
+                .executors
+                .iter()
+                .map(|exec| {
+                    let time = &exec.iteration_time;
+                    serde_json::json!({
+                        "name": exec.name,
+                        "started": exec.started,
+                        "duration_secs": exec.duration.as_secs_f64(),
+                        "iterations": exec.iterations,
+                        "iterations_per_sec": exec.rate(),
+                        "errors": exec.errors,
+                        "error_rate": exec.error_rate(),
+                        "iteration_time_ms": {
+                            "avg": millis(time.mean),
+                            "p50": millis(time.p50),
+                            "p95": millis(time.p95),
+                            "p99": millis(time.p99),
+                            "max": millis(time.max),
+                        },
+                    })
+                })
+                .collect();
+            serde_json::json!({"name": scenario.name, "executors": executors})
+        })
+        .collect();
+    let terminated = report.terminated.as_ref().map(|(id, err)| {
+        serde_json::json!({
+            "scenario": report.scenarios.get(*id).map(|x| &x.name),
+            "error": err,
+        })
+    });
+    serde_json::json!({
+        "passed": report.passed(),
+        "terminated": terminated,
+        "thresholds": report.thresholds,
+        "scenarios": scenarios,
+    })
+}
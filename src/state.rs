@@ -0,0 +1,552 @@
+//! State of a run rebuilt from the messages of the tracer, read by the summary, progress
+//! lines, reports and the interactive views of the `tui` and `web` features.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    logical::Executor,
+    thresholds::ThresholdResult,
+    tracing::{
+        message::Message,
+        task_event::{metrics::MetricValue, MetricSetKey, Value},
+        CHECKS, HTTP_STATUS, ITERATIONS_TOTAL,
+    },
+};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ExecutorState {
+    pub(crate) ended: bool,
+    pub(crate) config: Executor,
+    pub(crate) users: u64,
+    pub(crate) max_users: u64,
+    pub(crate) iterations: u64,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) total_iteration: Option<u64>,
+    pub(crate) prior_duration: Duration,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_to_rfc3339_opts",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub(crate) start_time: Option<DateTime<Utc>>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) total_duration: Option<Duration>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) stage: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    stage_duration: Option<Duration>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) stages: Option<usize>,
+    pub(crate) task_min_time: Duration,
+    pub(crate) task_max_time: Duration,
+    pub(crate) task_total_time: Duration,
+    pub(crate) errors: u64,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_metric"))]
+    pub(crate) metrics: HashMap<MetricSetKey, VecDeque<MetricValue>>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_window_metric"))]
+    window_metrics: HashMap<MetricSetKey, MetricValue>,
+    delta_interval: Duration,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_deltas"))]
+    deltas: HashMap<MetricSetKey, u64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) sample_times: VecDeque<Duration>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) users_history: VecDeque<u64>,
+}
+
+impl ExecutorState {
+    pub fn duration(&self) -> Duration {
+        let Some(start_time) = self.start_time else {
+            return self.prior_duration;
+        };
+        self.prior_duration + (Utc::now() - start_time).abs().to_std().unwrap()
+    }
+
+    pub fn config(&self) -> &Executor {
+        &self.config
+    }
+
+    pub fn ended(&self) -> bool {
+        self.ended
+    }
+
+    pub fn users(&self) -> u64 {
+        self.users
+    }
+
+    /// Number of iterations completed.
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// Number of iterations that returned an error.
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    /// Shortest, mean and longest time taken by an iteration.
+    pub fn iteration_times(&self) -> (Duration, Duration, Duration) {
+        let mean = match self.iterations {
+            0 => Duration::ZERO,
+            n => self.task_total_time.div_f64(n as f64),
+        };
+        (self.task_min_time, mean, self.task_max_time)
+    }
+
+    /// Returns true once the executor has started running.
+    pub fn started(&self) -> bool {
+        self.start_time.is_some() || self.ended
+    }
+
+    /// Estimated time left before the executor ends, extrapolated from the progress of its
+    /// duration or iterations. None while unknown, such as before the first iteration of an
+    /// executor running a number of iterations.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.ended {
+            return Some(Duration::ZERO);
+        }
+        if !self.started() {
+            return self.config.duration();
+        }
+        let elapsed = self.duration();
+        let by_duration = self
+            .total_duration
+            .map(|total| total.saturating_sub(elapsed));
+        let by_iterations = match self.total_iteration {
+            Some(total) if self.iterations > 0 => {
+                let left = total.saturating_sub(self.iterations);
+                Some(elapsed.mul_f64(left as f64 / self.iterations as f64))
+            }
+            _ => None,
+        };
+        match (by_duration, by_iterations) {
+            // Executors with both end at whichever comes first.
+            (Some(x), Some(y)) => Some(x.min(y)),
+            (x, y) => x.or(y),
+        }
+    }
+
+    pub fn metrics(&self) -> &HashMap<MetricSetKey, VecDeque<MetricValue>> {
+        &self.metrics
+    }
+
+    /// Latest values of metrics over the rolling window.
+    pub fn window_metrics(&self) -> &HashMap<MetricSetKey, MetricValue> {
+        &self.window_metrics
+    }
+
+    /// Time since the run started at each of the updates whose samples are kept in
+    /// [`metrics`](Self::metrics), oldest first. The last samples of a metric line up with the
+    /// last of these, metrics first seen in later updates have fewer samples.
+    pub fn sample_times(&self) -> &VecDeque<Duration> {
+        &self.sample_times
+    }
+
+    /// Active users at each of the [`sample_times`](Self::sample_times).
+    pub fn users_history(&self) -> &VecDeque<u64> {
+        &self.users_history
+    }
+
+    /// Change of counter totals and histogram counts with the latest update,
+    /// along with the time since the update before it.
+    pub fn deltas(&self) -> (Duration, &HashMap<MetricSetKey, u64>) {
+        (self.delta_interval, &self.deltas)
+    }
+
+    /// Outcomes of every [`check!`](crate::check) recorded by this executor, ordered by name.
+    pub fn checks(&self) -> Vec<CheckSummary> {
+        let mut checks: BTreeMap<String, CheckSummary> = BTreeMap::new();
+        for (key, values) in self.metrics.iter().filter(|(key, _)| key.name == CHECKS) {
+            let Some(MetricValue::Counter(count)) = values.back() else {
+                continue;
+            };
+            let attribute = |name: &str| {
+                key.attributes
+                    .iter()
+                    .find(|(x, _)| *x == name)
+                    .map(|(_, value)| value)
+            };
+            let Some(Value::String(name)) = attribute("check") else {
+                continue;
+            };
+            let summary = checks.entry(name.clone()).or_insert_with(|| CheckSummary {
+                name: name.clone(),
+                passes: 0,
+                fails: 0,
+            });
+            match attribute("result") {
+                Some(Value::String(result)) if result == "pass" => summary.passes += count,
+                _ => summary.fails += count,
+            }
+        }
+        checks.into_values().collect()
+    }
+
+    /// Number of iterations that succeeded and that failed, from the `result` attribute of
+    /// [`ITERATIONS_TOTAL`].
+    pub fn iteration_results(&self) -> (u64, u64) {
+        let (mut ok, mut failed) = (0, 0);
+        for (key, values) in self
+            .metrics
+            .iter()
+            .filter(|(key, _)| key.name == ITERATIONS_TOTAL)
+        {
+            let Some(MetricValue::Counter(count)) = values.back() else {
+                continue;
+            };
+            match key.attributes.iter().find(|(x, _)| *x == "result") {
+                Some((_, Value::String(result))) if result == "ok" => ok += count,
+                _ => failed += count,
+            }
+        }
+        (ok, failed)
+    }
+
+    /// Fraction of iterations that failed, between 0 and 1, see [`iteration_results`](Self::iteration_results).
+    pub fn error_rate(&self) -> f64 {
+        match self.iteration_results() {
+            (0, 0) => 0.,
+            (ok, failed) => failed as f64 / (ok + failed) as f64,
+        }
+    }
+
+    /// Number of HTTP responses by status class such as `2xx` or `5xx`, ordered by class.
+    /// See [`record_status`](crate::client::record_status).
+    pub fn status_classes(&self) -> Vec<(String, u64)> {
+        let mut classes: BTreeMap<String, u64> = BTreeMap::new();
+        for (key, values) in self
+            .metrics
+            .iter()
+            .filter(|(key, _)| key.name == HTTP_STATUS)
+        {
+            let Some(MetricValue::Counter(count)) = values.back() else {
+                continue;
+            };
+            let Some((_, code)) = key.attributes.iter().find(|(x, _)| *x == "code") else {
+                continue;
+            };
+            let class = match code.to_string().chars().next() {
+                Some(x) => format!("{x}xx"),
+                None => continue,
+            };
+            *classes.entry(class).or_default() += count;
+        }
+        classes.into_iter().collect()
+    }
+}
+
+/// Number of times a check passed and failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckSummary {
+    pub name: String,
+    pub passes: u64,
+    pub fails: u64,
+}
+
+impl CheckSummary {
+    /// Fraction of outcomes that passed, between 0 and 1.
+    pub fn pass_rate(&self) -> f64 {
+        let total = self.passes + self.fails;
+        if total == 0 {
+            return 0.;
+        }
+        self.passes as f64 / total as f64
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub(crate) name: String,
+    pub(crate) execs: Vec<ExecutorState>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_window_metric"))]
+    metrics: HashMap<MetricSetKey, MetricValue>,
+}
+
+impl Scenario {
+    pub fn new_from_scenario(scenario: &crate::logical::Scenario<'_>) -> Self {
+        let name = scenario.label.to_string();
+        let execs = scenario
+            .execution_provider
+            .iter()
+            .map(|exec| ExecutorState {
+                ended: false,
+                config: exec.config().clone(),
+                users: Default::default(),
+                max_users: Default::default(),
+                iterations: Default::default(),
+                total_iteration: Default::default(),
+                prior_duration: Default::default(),
+                start_time: Default::default(),
+                total_duration: Default::default(),
+                stage: Default::default(),
+                stage_duration: Default::default(),
+                stages: Default::default(),
+                task_min_time: Default::default(),
+                task_max_time: Default::default(),
+                task_total_time: Default::default(),
+                errors: Default::default(),
+                metrics: Default::default(),
+                window_metrics: Default::default(),
+                delta_interval: Default::default(),
+                deltas: Default::default(),
+                sample_times: Default::default(),
+                users_history: Default::default(),
+            })
+            .collect();
+
+        Self {
+            name,
+            execs,
+            metrics: HashMap::new(),
+        }
+    }
+
+    pub fn exec_names(&self) -> impl Iterator<Item = String> + '_ {
+        self.execs.iter().map(|x| x.config.to_string())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn execs(&self) -> &[ExecutorState] {
+        &self.execs
+    }
+
+    /// Metrics of all executors of the scenario combined, such as the p95 of a histogram
+    /// over every value any executor observed. Gauges are not included.
+    pub fn metrics(&self) -> &HashMap<MetricSetKey, MetricValue> {
+        &self.metrics
+    }
+
+    /// Estimated time left before every executor of the scenario ended, see [`ExecutorState::eta`].
+    pub fn eta(&self) -> Option<Duration> {
+        self.execs
+            .iter()
+            .try_fold(Duration::ZERO, |eta, exec| Some(eta.max(exec.eta()?)))
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct App {
+    pub(crate) current_scenario: usize,
+    pub(crate) scenarios: Vec<Scenario>,
+    pub(crate) thresholds: Vec<ThresholdResult>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    history: usize,
+    /// Start of the first executor of the run.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    start_time: Option<DateTime<Utc>>,
+}
+
+/// Samples of every metric kept by default, see [`App::history`].
+pub const DEFAULT_HISTORY: usize = 20;
+
+impl App {
+    pub fn new<'a, T>(scenarios: T) -> Self
+    where
+        T: IntoIterator<Item = &'a crate::logical::Scenario<'a>>,
+    {
+        let scenarios = scenarios
+            .into_iter()
+            .map(|scenario| Scenario::new_from_scenario(scenario))
+            .collect();
+
+        Self {
+            current_scenario: 0,
+            scenarios,
+            thresholds: Vec::new(),
+            history: DEFAULT_HISTORY,
+            start_time: None,
+        }
+    }
+
+    /// Keep the last `samples` values of every metric, [`DEFAULT_HISTORY`] by default.
+    pub fn history(mut self, samples: usize) -> Self {
+        self.history = samples.max(1);
+        self
+    }
+
+    pub fn scenarios(&self) -> &[Scenario] {
+        &self.scenarios
+    }
+
+    /// Latest outcome of every threshold of the run.
+    pub fn thresholds(&self) -> &[ThresholdResult] {
+        &self.thresholds
+    }
+
+    pub fn current_scenario(&self) -> &Scenario {
+        &self.scenarios[self.current_scenario]
+    }
+
+    /// Index of the running scenario in [`scenarios`](Self::scenarios).
+    pub fn current_scenario_id(&self) -> usize {
+        self.current_scenario
+    }
+
+    /// Estimated time left before the run ends, the [`Scenario::eta`] of the running scenario
+    /// and of those after it added up.
+    pub fn eta(&self) -> Option<Duration> {
+        self.scenarios[self.current_scenario..]
+            .iter()
+            .map(Scenario::eta)
+            .sum()
+    }
+
+    pub fn current_scenario_mut(&mut self) -> &mut Scenario {
+        &mut self.scenarios[self.current_scenario]
+    }
+
+    pub fn handle_message(&mut self, message: Message) {
+        match message {
+            Message::ScenarioChanged { scenario_id } => {
+                self.current_scenario = scenario_id;
+            }
+            Message::Thresholds { results } => {
+                self.thresholds = results;
+            }
+            Message::ScenarioUpdate { id, metrics } => {
+                self.scenarios[id].metrics = metrics.into_iter().collect();
+            }
+            Message::TaskTime {
+                execution_id: id,
+                duration,
+                ..
+            } => {
+                let exec = &mut self.current_scenario_mut().execs[id];
+                exec.iterations += 1;
+                exec.task_max_time = exec.task_max_time.max(duration);
+                if exec.task_min_time == Duration::ZERO {
+                    exec.task_min_time = duration;
+                } else {
+                    exec.task_min_time = exec.task_min_time.min(duration);
+                }
+                exec.task_total_time += duration;
+            }
+            Message::ExecutorUpdate {
+                id,
+                users,
+                max_users,
+                total_iteration,
+                total_duration,
+                stage,
+                stages,
+                stage_duration,
+                metrics,
+                window_metrics,
+                interval,
+                deltas,
+            } => {
+                let history = self.history;
+                let elapsed = self
+                    .start_time
+                    .and_then(|x| (Utc::now() - x).to_std().ok())
+                    .unwrap_or_default();
+                let exec = &mut self.current_scenario_mut().execs[id];
+                exec.users = users;
+                exec.max_users = max_users;
+                exec.total_duration = total_duration;
+                exec.total_iteration = total_iteration;
+                exec.stage = stage;
+                exec.stages = stages;
+                exec.stage_duration = stage_duration;
+                metrics.into_iter().for_each(|(key, value)| {
+                    let entry = exec.metrics.entry(key).or_default();
+                    if entry.len() >= history {
+                        entry.pop_front();
+                    }
+                    entry.push_back(value)
+                });
+                if exec.sample_times.len() >= history {
+                    exec.sample_times.pop_front();
+                    exec.users_history.pop_front();
+                }
+                exec.sample_times.push_back(elapsed);
+                exec.users_history.push_back(users);
+                exec.window_metrics.extend(window_metrics);
+                exec.delta_interval = interval;
+                exec.deltas = deltas.into_iter().collect();
+            }
+            Message::ExecutorStart {
+                id,
+                start_time,
+                prior_executor_duration,
+            } => {
+                self.start_time.get_or_insert(start_time);
+                let exec = &mut self.current_scenario_mut().execs[id];
+                exec.start_time = Some(start_time);
+                exec.prior_duration = prior_executor_duration;
+            }
+            Message::ExecutorEnd { id } => {
+                let exec = &mut self.current_scenario_mut().execs[id];
+                if let Some(start_time) = exec.start_time {
+                    exec.prior_duration += (Utc::now() - start_time).abs().to_std().unwrap()
+                }
+                exec.start_time = None;
+                exec.ended = true
+            }
+            Message::Error {
+                execution_id: Some(id),
+                ..
+            } => {
+                self.current_scenario_mut().execs[id].errors += 1;
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+pub fn serialize_to_rfc3339_opts<S: serde::Serializer>(
+    t: &Option<DateTime<Utc>>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(
+        &t.as_ref()
+            .map(|x| x.to_rfc3339_opts(chrono::SecondsFormat::Millis, false)),
+        s,
+    )
+}
+
+#[cfg(feature = "serde")]
+fn serialize_metric<S: serde::Serializer>(
+    t: &HashMap<MetricSetKey, VecDeque<MetricValue>>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeSeq as _;
+    let mut seq = s.serialize_seq(Some(t.len()))?;
+    for entry in t.iter() {
+        seq.serialize_element(&entry)?;
+    }
+    seq.end()
+}
+
+#[cfg(feature = "serde")]
+fn serialize_window_metric<S: serde::Serializer>(
+    t: &HashMap<MetricSetKey, MetricValue>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    s.collect_seq(t.iter())
+}
+
+#[cfg(feature = "serde")]
+fn serialize_deltas<S: serde::Serializer>(
+    t: &HashMap<MetricSetKey, u64>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    s.collect_seq(t.iter())
+}
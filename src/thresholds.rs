@@ -0,0 +1,542 @@
+//! Pass/fail conditions on metrics, evaluated while the run is in progress.
+//!
+//! A threshold is declared on a metric name with a condition of the form `<stat> <op> <value>`.
+//!
+//! ```
+//! use rusher::thresholds::Threshold;
+//!
+//! let latency: Threshold = "http_req_duration: p95 < 500ms".parse().unwrap();
+//! let checks = Threshold::new("checks", "rate > 0.99").unwrap().abort_on_fail(true);
+//! ```
+//!
+//! Supported stats are
+//! * `count` - sum of a counter across all of its attributes, number of samples of a trend or ratio, or bytes transferred.
+//! * `rate` - fraction of a counter with a `result` attribute of `pass` or `ok`, such as [`CHECKS`](crate::tracing::CHECKS)
+//!   or [`ITERATIONS_TOTAL`](crate::tracing::ITERATIONS_TOTAL). For a rate metric this is the per second rate over the run,
+//!   for a bytes metric the throughput in bytes per second and for a ratio metric the fraction of `true` values.
+//! * `value` - current value of a gauge.
+//! * `avg` - of a trend.
+//! * `min`, `max` - of a histogram or trend.
+//! * `p50`, `p90`, `p95`, `p99` - of a histogram or trend.
+//!
+//! Durations are compared in milliseconds, the value of a condition can carry a unit of
+//! `ns`, `us`, `ms`, `s` or `m`. Apart from `count` and `rate` of counters, the condition has to hold
+//! for every attribute set of the metric in every executor.
+//!
+//! A threshold fails when its metric was never recorded, such as a misspelled name, and when
+//! its stat does not apply to the type of the metric, such as `avg` of a histogram.
+
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+
+use tokio::sync::Notify;
+
+use crate::{
+    error::Error,
+    tracing::task_event::{
+        metrics::{ratio, MetricType, MetricValue, TrendValue},
+        MetricSetKey, Value,
+    },
+};
+
+/// Condition on a named metric.
+#[derive(Debug, Clone)]
+pub struct Threshold {
+    metric: String,
+    condition: Condition,
+    abort_on_fail: bool,
+}
+
+impl Threshold {
+    /// Threshold on `metric` with a condition such as `p95 < 500ms`.
+    pub fn new(metric: impl Into<String>, condition: &str) -> Result<Self, Error> {
+        Ok(Self {
+            metric: metric.into(),
+            condition: condition.parse()?,
+            abort_on_fail: false,
+        })
+    }
+
+    /// Stop the run as soon as this threshold fails.
+    pub fn abort_on_fail(mut self, abort_on_fail: bool) -> Self {
+        self.abort_on_fail = abort_on_fail;
+        self
+    }
+
+    pub fn metric(&self) -> &str {
+        &self.metric
+    }
+
+    /// Whether the condition holds for metrics of an executor.
+    /// Returns `None` when the metric was not recorded yet, false when the stat of the condition
+    /// does not apply to the type of the metric.
+    fn holds(&self, metrics: &[(MetricSetKey, MetricValue)]) -> Option<bool> {
+        let series = metrics.iter().filter(|(key, _)| key.name == self.metric);
+        if series
+            .clone()
+            .any(|(key, _)| !self.condition.stat.applies_to(key.metric_type))
+        {
+            return Some(false);
+        }
+        let values: Vec<f64> = match self.condition.stat {
+            Stat::Count | Stat::Rate
+                if series
+                    .clone()
+                    .any(|(_, value)| matches!(value, MetricValue::Counter(_))) =>
+            {
+                counter_stat(self.condition.stat, series)
+                    .into_iter()
+                    .collect()
+            }
+            stat => series.filter_map(|(_, value)| stat.of(value)).collect(),
+        };
+
+        if values.is_empty() {
+            return None;
+        }
+        Some(values.into_iter().all(|x| self.condition.holds(x)))
+    }
+}
+
+impl std::fmt::Display for Threshold {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.metric, self.condition)
+    }
+}
+
+impl FromStr for Threshold {
+    type Err = Error;
+
+    /// Parse a threshold of the form `metric: condition`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (metric, condition) = s.split_once(':').ok_or_else(|| {
+            Error::new(format!(
+                "invalid threshold `{s}`, expected `metric: condition`"
+            ))
+        })?;
+        Self::new(metric.trim(), condition)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stat {
+    Count,
+    Rate,
+    Value,
+    Avg,
+    Min,
+    Max,
+    P50,
+    P90,
+    P95,
+    P99,
+}
+
+impl Stat {
+    /// Whether a metric of type `metric_type` can have a value for this stat.
+    fn applies_to(self, metric_type: MetricType) -> bool {
+        use MetricType::*;
+        match self {
+            Stat::Count => matches!(metric_type, Counter | Trend | Bytes | Ratio),
+            Stat::Rate => matches!(metric_type, Counter | Rate | Bytes | Ratio),
+            Stat::Value => metric_type == Gauge,
+            Stat::Avg => metric_type == Trend,
+            Stat::Min | Stat::Max | Stat::P50 | Stat::P90 | Stat::P95 | Stat::P99 => {
+                matches!(metric_type, Histogram | Trend)
+            }
+        }
+    }
+
+    /// Stat of a single series, durations are in milliseconds.
+    fn of(self, value: &MetricValue) -> Option<f64> {
+        fn millis(x: &Duration) -> f64 {
+            x.as_secs_f64() * 1000.
+        }
+
+        fn percentile<T: Copy>(stat: Stat, (p50, p90, p95, p99): (T, T, T, T)) -> Option<T> {
+            match stat {
+                Stat::P50 => Some(p50),
+                Stat::P90 => Some(p90),
+                Stat::P95 => Some(p95),
+                Stat::P99 => Some(p99),
+                _ => None,
+            }
+        }
+
+        fn trend<T: Copy>(stat: Stat, trend: &TrendValue<T>) -> Option<T> {
+            match stat {
+                Stat::Avg => Some(trend.mean),
+                Stat::Min => Some(trend.min),
+                Stat::Max => Some(trend.max),
+                _ => percentile(stat, trend.percentiles),
+            }
+        }
+
+        match (self, value) {
+            (Stat::Value, MetricValue::GaugeF64(x)) => Some(*x),
+            (Stat::Value, MetricValue::GaugeI64(x)) => Some(*x as f64),
+            (Stat::Value, MetricValue::GaugeU64(x)) => Some(*x as f64),
+            (Stat::Value, MetricValue::GaugeDuration(x)) => Some(millis(x)),
+            (Stat::Rate, MetricValue::Rate((total, _))) => Some(*total),
+            (Stat::Count, MetricValue::Bytes((total, _))) => Some(*total as f64),
+            (Stat::Rate, MetricValue::Bytes((_, (rate, _)))) => Some(*rate),
+            (Stat::Count, MetricValue::Ratio((_, total))) => Some(*total as f64),
+            (Stat::Rate, MetricValue::Ratio((passed, total))) => Some(ratio(*passed, *total)),
+            (Stat::Count, MetricValue::Trend(x)) => Some(x.count as f64),
+            (Stat::Count, MetricValue::DurationTrend(x)) => Some(x.count as f64),
+            (Stat::Min, MetricValue::Histogram((_, _, (min, _)))) => Some(*min),
+            (Stat::Max, MetricValue::Histogram((_, _, (_, max)))) => Some(*max),
+            (Stat::Min, MetricValue::DurationHistogram((_, _, (min, _)))) => Some(millis(min)),
+            (Stat::Max, MetricValue::DurationHistogram((_, _, (_, max)))) => Some(millis(max)),
+            (_, MetricValue::Histogram((percentiles, ..))) => percentile(self, *percentiles),
+            (_, MetricValue::DurationHistogram((percentiles, ..))) => {
+                percentile(self, *percentiles).map(|x| millis(&x))
+            }
+            (_, MetricValue::Trend(x)) => trend(self, x),
+            (_, MetricValue::DurationTrend(x)) => trend(self, x).map(|x| millis(&x)),
+            _ => None,
+        }
+    }
+}
+
+/// `count` or `rate` of a counter summed over all of its series.
+fn counter_stat<'a>(
+    stat: Stat,
+    series: impl Iterator<Item = &'a (MetricSetKey, MetricValue)>,
+) -> Option<f64> {
+    let (mut total, mut passed) = (0u64, 0u64);
+    for (key, value) in series {
+        let MetricValue::Counter(count) = value else {
+            continue;
+        };
+        total += count;
+        let result = key.attributes.iter().find(|(name, _)| *name == "result");
+        if let Some((_, Value::String(result))) = result {
+            if result == "pass" || result == "ok" {
+                passed += count;
+            }
+        }
+    }
+
+    match stat {
+        Stat::Count => Some(total as f64),
+        _ if total == 0 => None,
+        _ => Some(passed as f64 / total as f64),
+    }
+}
+
+impl FromStr for Stat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "count" => Self::Count,
+            "rate" => Self::Rate,
+            "value" => Self::Value,
+            "avg" => Self::Avg,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            "p50" => Self::P50,
+            "p90" => Self::P90,
+            "p95" => Self::P95,
+            "p99" => Self::P99,
+            _ => return Err(Error::new(format!("unknown threshold stat `{s}`"))),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+}
+
+impl Op {
+    fn as_str(self) -> &'static str {
+        match self {
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Eq => "==",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Condition {
+    stat: Stat,
+    op: Op,
+    value: f64,
+    /// Value as written by the user, for display.
+    raw: String,
+}
+
+impl Condition {
+    fn holds(&self, x: f64) -> bool {
+        match self.op {
+            Op::Lt => x < self.value,
+            Op::Le => x <= self.value,
+            Op::Gt => x > self.value,
+            Op::Ge => x >= self.value,
+            Op::Eq => x == self.value,
+        }
+    }
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stat = format!("{:?}", self.stat).to_lowercase();
+        write!(f, "{} {} {}", stat, self.op.as_str(), self.raw)
+    }
+}
+
+impl FromStr for Condition {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::new(format!("invalid threshold condition `{}`", s.trim()));
+
+        let mut parts = s.split_whitespace();
+        let (Some(stat), Some(op), Some(raw), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+
+        let op = match op {
+            "<" => Op::Lt,
+            "<=" => Op::Le,
+            ">" => Op::Gt,
+            ">=" => Op::Ge,
+            "==" => Op::Eq,
+            _ => return Err(invalid()),
+        };
+
+        let split = raw
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+            .unwrap_or(raw.len());
+        let (number, unit) = raw.split_at(split);
+        let number: f64 = number.parse().map_err(|_| invalid())?;
+        let scale = match unit {
+            "" | "ms" => 1.,
+            "ns" => 1e-6,
+            "us" | "µs" => 1e-3,
+            "s" => 1e3,
+            "m" => 60e3,
+            _ => return Err(invalid()),
+        };
+
+        Ok(Self {
+            stat: stat.parse()?,
+            op,
+            value: number * scale,
+            raw: raw.to_string(),
+        })
+    }
+}
+
+/// Outcome of a threshold.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ThresholdResult {
+    pub threshold: String,
+    pub passed: bool,
+    /// False while the metric of the threshold was never recorded, the threshold fails then.
+    pub evaluated: bool,
+}
+
+/// Thresholds of a run along with their latest outcome, shared by the runner and the tracing layer.
+#[derive(Debug, Default)]
+pub(crate) struct Thresholds {
+    thresholds: Vec<Threshold>,
+    /// Latest outcome of every threshold for each (scenario id, executor id).
+    outcomes: Mutex<Vec<HashMap<(usize, usize), bool>>>,
+    abort_reason: OnceLock<String>,
+    abort: Notify,
+}
+
+impl Thresholds {
+    pub fn new(thresholds: Vec<Threshold>) -> Self {
+        Self {
+            outcomes: Mutex::new(vec![HashMap::new(); thresholds.len()]),
+            thresholds,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.thresholds.is_empty()
+    }
+
+    /// Evaluate thresholds against current metrics of an executor.
+    /// Returns the results when the outcome of any threshold changed.
+    pub fn evaluate(
+        &self,
+        executor: (usize, usize),
+        metrics: &[(MetricSetKey, MetricValue)],
+    ) -> Option<Vec<ThresholdResult>> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut outcomes = self.outcomes.lock().unwrap();
+        let mut changed = false;
+        for (threshold, outcome) in self.thresholds.iter().zip(outcomes.iter_mut()) {
+            let Some(passed) = threshold.holds(metrics) else {
+                continue;
+            };
+            if outcome.insert(executor, passed) != Some(passed) {
+                changed = true;
+            }
+            if !passed
+                && threshold.abort_on_fail
+                && self.abort_reason.set(threshold.to_string()).is_ok()
+            {
+                self.abort.notify_waiters();
+            }
+        }
+
+        changed.then(|| self.collect(&outcomes))
+    }
+
+    pub fn results(&self) -> Vec<ThresholdResult> {
+        self.collect(&self.outcomes.lock().unwrap())
+    }
+
+    fn collect(&self, outcomes: &[HashMap<(usize, usize), bool>]) -> Vec<ThresholdResult> {
+        self.thresholds
+            .iter()
+            .zip(outcomes)
+            .map(|(threshold, outcome)| ThresholdResult {
+                threshold: threshold.to_string(),
+                passed: !outcome.is_empty() && outcome.values().all(|x| *x),
+                evaluated: !outcome.is_empty(),
+            })
+            .collect()
+    }
+
+    /// Resolves with the failed threshold once a threshold with `abort_on_fail` fails.
+    pub async fn aborted(&self) -> &str {
+        loop {
+            let notified = self.abort.notified();
+            if let Some(reason) = self.abort_reason.get() {
+                return reason;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracing::task_event::metrics::MetricType;
+
+    fn key(name: &'static str, metric_type: MetricType, result: Option<&str>) -> MetricSetKey {
+        MetricSetKey {
+            name,
+            metric_type,
+            attributes: result
+                .map(|x| ("result", Value::String(x.to_string())))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let threshold: Threshold = "http_req_duration: p95 < 500ms".parse().unwrap();
+        assert_eq!(threshold.metric(), "http_req_duration");
+        assert_eq!(threshold.condition.stat, Stat::P95);
+        assert_eq!(threshold.condition.value, 500.);
+        assert_eq!(threshold.to_string(), "http_req_duration: p95 < 500ms");
+
+        assert_eq!(
+            Threshold::new("x", "max <= 2s").unwrap().condition.value,
+            2000.
+        );
+        assert!(Threshold::new("x", "p42 < 1").is_err());
+        assert!(Threshold::new("x", "p95 ~ 1").is_err());
+        assert!("p95 < 1".parse::<Threshold>().is_err());
+    }
+
+    #[test]
+    fn evaluate() {
+        let latency = Threshold::new("latency", "p95 < 500ms").unwrap();
+        let checks = Threshold::new("checks", "rate > 0.9").unwrap();
+        let thresholds = Thresholds::new(vec![latency, checks]);
+
+        let p = |ms| Duration::from_millis(ms);
+        let metrics = vec![
+            (
+                key("latency", MetricType::Histogram, None),
+                MetricValue::DurationHistogram((
+                    (p(100), p(200), p(300), p(400)),
+                    p(1000),
+                    (p(50), p(450)),
+                )),
+            ),
+            (
+                key("checks", MetricType::Counter, Some("pass")),
+                MetricValue::Counter(8),
+            ),
+            (
+                key("checks", MetricType::Counter, Some("fail")),
+                MetricValue::Counter(2),
+            ),
+        ];
+
+        let results = thresholds.evaluate((0, 0), &metrics).unwrap();
+        assert!(results[0].passed);
+        assert!(!results[1].passed);
+        // Nothing changed since the last evaluation.
+        assert!(thresholds.evaluate((0, 0), &metrics).is_none());
+    }
+
+    #[test]
+    fn fail_without_data() {
+        let thresholds = Thresholds::new(vec![
+            Threshold::new("latncy", "p95 < 500ms").unwrap(),
+            Threshold::new("checks", "count > 0").unwrap(),
+        ]);
+        let metrics = vec![(
+            key("checks", MetricType::Counter, Some("pass")),
+            MetricValue::Counter(1),
+        )];
+        thresholds.evaluate((0, 0), &metrics);
+
+        let results = thresholds.results();
+        assert!(!results[0].passed);
+        assert!(!results[0].evaluated);
+        assert!(results[1].passed);
+    }
+
+    #[test]
+    fn fail_unsupported_stat() {
+        let p = |ms| Duration::from_millis(ms);
+        let metrics = vec![
+            (
+                key("latency", MetricType::Histogram, None),
+                MetricValue::DurationHistogram(((p(1), p(1), p(1), p(1)), p(1), (p(1), p(1)))),
+            ),
+            (
+                key("requests", MetricType::Counter, None),
+                MetricValue::Counter(1),
+            ),
+        ];
+        for (metric, condition) in [("latency", "avg < 500ms"), ("requests", "value > 0")] {
+            let thresholds = Thresholds::new(vec![Threshold::new(metric, condition).unwrap()]);
+            let results = thresholds.evaluate((0, 0), &metrics).unwrap();
+            assert!(!results[0].passed, "{metric}: {condition}");
+            assert!(results[0].evaluated);
+        }
+    }
+}
@@ -1,16 +1,25 @@
+pub mod filter;
+pub mod log_file;
 pub mod message;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod sink;
 pub mod task_event;
 
 use std::{
     collections::HashMap,
-    ops::ControlFlow,
     str::FromStr,
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
 use chrono::{DateTime, Utc};
-use message::Message;
-use task_event::{metrics::MetricType, MetricSet, TaskEvent, TaskSpanData};
+use filter::MetricFilter;
+use message::{GroupTiming, IterationRecord, Message};
+use task_event::{
+    metrics::{MetricType, MetricValue},
+    Attribute, MetricSet, MetricSetKey, MetricsConfig, TaskEvent, TaskSpanData, OVERFLOW_ATTRIBUTE,
+};
 use tracing::{
     field::{Field, Visit},
     span::{self, Id},
@@ -22,11 +31,12 @@ use tracing_subscriber::{
     Layer,
 };
 
-use crate::{CRATE_NAME, SPAN_EXEC, SPAN_SCENARIO, SPAN_TASK, USER_TASK};
+use crate::{thresholds::Thresholds, CRATE_NAME, SPAN_EXEC, SPAN_SCENARIO, SPAN_TASK, USER_TASK};
 
 #[derive(Debug, Default)]
 struct ErrorVisitor {
     err: String,
+    class: Option<String>,
 }
 
 /// Tracked data that is associated with a task
@@ -36,6 +46,23 @@ struct TaskData {
     execution_id: usize,
     execution_span_id: Id,
     instant: Instant,
+    /// user call of this task returned an error
+    failed: bool,
+    /// index of the user running this task, only tracked when its executor tags metrics with it
+    user_id: Option<u64>,
+    /// error returned by the user call and groups completed within it, only tracked for iteration records
+    error: Option<String>,
+    groups: Vec<GroupTiming>,
+}
+
+impl tracing::field::Visit for TaskData {
+    fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == USER_ID {
+            self.user_id = Some(value);
+        }
+    }
 }
 
 /// Tracked data associated with span of an execution.
@@ -50,11 +77,54 @@ struct ExecutionData {
     stage: Option<usize>,
     stage_duration: Option<Duration>,
     total_stages: Option<usize>,
-    metrics: MetricSet,
+    /// Interval that [`ITERATION_DURATION`] is corrected against for coordinated omission.
+    expected_interval: Option<Duration>,
+    /// Correct against the arrival interval of the running stage.
+    arrival_correction: bool,
+    /// Tag user metrics with the [`USER_ID`] of the user that emitted them.
+    user_ids: bool,
+    metrics: Arc<MetricSet>,
+    /// Counts sent with the previous update and when it was sent.
+    last_counts: Mutex<(Instant, HashMap<MetricSetKey, u64>)>,
+    /// When window values were last computed.
+    last_window: Mutex<Instant>,
+}
+
+impl ExecutionData {
+    /// Change of every count since the previous update, along with the time elapsed since then.
+    /// Counts that did not change are left out.
+    fn deltas(&self) -> (Duration, Vec<(MetricSetKey, u64)>) {
+        let mut last = self.last_counts.lock().unwrap();
+        let now = Instant::now();
+        let interval = now - last.0;
+        last.0 = now;
+        let deltas = self
+            .metrics
+            .counts()
+            .filter_map(|(key, count)| {
+                let previous = last.1.insert(key.clone(), count).unwrap_or_default();
+                let delta = count.saturating_sub(previous);
+                (delta > 0).then_some((key, delta))
+            })
+            .collect();
+        (interval, deltas)
+    }
+
+    /// Values over the last window if none were computed within the last [`ROLLUP_INTERVAL`],
+    /// rebuilding the window of every metric is too costly to do for every iteration.
+    fn window_if_due(&self) -> Vec<(MetricSetKey, MetricValue)> {
+        let mut last = self.last_window.lock().unwrap();
+        if last.elapsed() < ROLLUP_INTERVAL {
+            return Vec::new();
+        }
+        *last = Instant::now();
+        self.metrics.window_entries().collect()
+    }
 }
 
 impl From<&ExecutionData> for Message {
     fn from(value: &ExecutionData) -> Self {
+        let (interval, deltas) = value.deltas();
         Message::ExecutorUpdate {
             id: value.id,
             users: value.users,
@@ -65,6 +135,9 @@ impl From<&ExecutionData> for Message {
             stages: value.total_stages,
             stage_duration: value.stage_duration,
             metrics: value.metrics.entries().collect(),
+            window_metrics: value.window_if_due(),
+            interval,
+            deltas,
         }
     }
 }
@@ -94,6 +167,18 @@ impl tracing::field::Visit for ExecutionData {
             "duration" => self.duration = Duration::from_secs(value),
             "total_duration" => self.total_duration = Some(Duration::from_secs(value)),
             "total_iteration" => self.total_iteration = Some(value),
+            "expected_interval" => self.expected_interval = Some(Duration::from_nanos(value)),
+            "arrival_interval" if self.arrival_correction => {
+                self.expected_interval = Some(Duration::from_nanos(value)).filter(|x| !x.is_zero())
+            }
+            _ => (),
+        }
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        match field.name() {
+            "user_ids" => self.user_ids = value,
+            "arrival_correction" => self.arrival_correction = value,
             _ => (),
         }
     }
@@ -114,6 +199,13 @@ impl Visit for ErrorVisitor {
             self.err = format!("{:?}", value)
         }
     }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "class" => self.class = Some(value.to_string()),
+            _ => self.record_debug(field, &value),
+        }
+    }
 }
 
 pub trait Sender {
@@ -132,17 +224,132 @@ impl Sender for tokio::sync::broadcast::Sender<Message> {
     }
 }
 
+/// Fan out every message to all the senders.
+impl Sender for Vec<crate::Sender<Message>> {
+    fn send(&self, message: Message) {
+        for sender in self {
+            let _ = sender.send(message.clone());
+        }
+    }
+}
+
+/// Built-in counter of completed iterations of an executor, with a `result` attribute of `ok` or `error`.
+pub const ITERATIONS_TOTAL: &str = "iterations_total";
+
+/// Built-in histogram of the duration of iterations corrected for coordinated omission, recorded
+/// for executors created with
+/// [`Execution::with_omission_correction`](crate::logical::Execution::with_omission_correction).
+pub const ITERATION_DURATION: &str = "iteration_duration";
+
+/// Counter of [`check!`](crate::check) outcomes, with a `check` attribute naming the check and a `result` attribute of `pass` or `fail`.
+pub const CHECKS: &str = "checks";
+
+/// Counter of HTTP responses, with the status in a `code` attribute and the route in a `route` attribute.
+/// See [`record_status`](crate::client::record_status).
+pub const HTTP_STATUS: &str = "http_status";
+
+/// Built-in counter of failed iterations of an executor, with a `class` attribute from [`Error::class`](crate::error::Error::class).
+pub const ERRORS_TOTAL: &str = "errors_total";
+
+/// Counter of failed iterations called again by [`Retry`](crate::user::retry::Retry), with a
+/// `class` attribute from [`Error::class`](crate::error::Error::class).
+pub const RETRIES_TOTAL: &str = "retries_total";
+
+/// Counter of user calls failed with [`Error::Timeout`](crate::error::Error::Timeout) by
+/// [`Timeout`](crate::user::timeout::Timeout).
+pub const TIMEOUTS_TOTAL: &str = "timeouts_total";
+
+/// Histogram of the duration of groups opened with [`group`](crate::user::group).
+pub const GROUP_DURATION: &str = "group_duration";
+
+/// Attribute holding the name of a group, nested groups are joined with `::` such as `checkout::login`.
+pub const GROUP: &str = "group";
+
+/// Histogram of the duration of calls of the behaviors of a
+/// [`WeightedChoice`](crate::user::choice::WeightedChoice).
+pub const BEHAVIOR_DURATION: &str = "behavior_duration";
+
+/// Attribute holding the name of the behavior picked by a
+/// [`WeightedChoice`](crate::user::choice::WeightedChoice), added to metrics emitted within it.
+pub const BEHAVIOR: &str = "behavior";
+
+/// Attribute holding the index of the user within its executor, added to user metrics of executors
+/// created with [`Execution::with_user_id_attribute`](crate::logical::Execution::with_user_id_attribute).
+pub const USER_ID: &str = "user_id";
+
+/// Minimum time between two roll-ups of a scenario sent while it runs.
+const ROLLUP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Metric sets of every executor, keyed by scenario id and executor id.
+pub(crate) type MetricRegistry = dashmap::DashMap<(usize, usize), Arc<MetricSet>>;
+
 // Tracing layer that tracks and generates message based on this crate's tracing events
 pub struct TracerLayer<T: Sender> {
     // current_scenario: Mutex<String>,
     stats_sender: T,
+    registry: Arc<MetricRegistry>,
+    metrics_config: MetricsConfig,
+    thresholds: Arc<Thresholds>,
+    /// When the previous scenario roll-up was sent.
+    last_rollup: Mutex<Instant>,
+    filter: MetricFilter,
+    /// Send a [`Message::Iteration`] for every iteration.
+    iterations: bool,
 }
 
 impl<T: Sender> TracerLayer<T> {
     pub fn new(sender: T) -> Self {
         Self {
             stats_sender: sender,
+            registry: Arc::default(),
+            metrics_config: MetricsConfig::default(),
+            thresholds: Arc::default(),
+            last_rollup: Mutex::new(Instant::now()),
+            filter: MetricFilter::default(),
+            iterations: false,
+        }
+    }
+
+    /// Send a [`Message::Iteration`] with the outcome and group timings of every iteration.
+    pub fn with_iterations(mut self, enable: bool) -> Self {
+        self.iterations = enable;
+        self
+    }
+
+    /// Record only the user metrics allowed by `filter`, others are dropped before aggregation.
+    pub fn with_filter(mut self, filter: MetricFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Limits applied to metrics of every executor.
+    pub fn with_metrics_config(mut self, config: MetricsConfig) -> Self {
+        self.metrics_config = config;
+        self
+    }
+
+    /// Use a shared registry for executor metrics. Metric sets already present in
+    /// the registry are picked up by their executor instead of starting empty.
+    pub(crate) fn with_registry(mut self, registry: Arc<MetricRegistry>) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Evaluate thresholds against executor metrics after every iteration.
+    pub(crate) fn with_thresholds(mut self, thresholds: Arc<Thresholds>) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Roll-up of a scenario if none was sent within the last [`ROLLUP_INTERVAL`].
+    fn rollup_if_due(&self, scenario_id: usize) -> Option<Message> {
+        let mut last = self.last_rollup.lock().unwrap();
+        if last.elapsed() < ROLLUP_INTERVAL {
+            return None;
         }
+        *last = Instant::now();
+        drop(last);
+        Some(scenario_rollup(&self.registry, scenario_id))
     }
 }
 
@@ -176,10 +383,10 @@ impl<T: Sender + 'static, S: tracing::Subscriber + for<'a> LookupSpan<'a>> Layer
 
         match span.name() {
             SPAN_TASK => {
-                create_task_span(&span);
+                create_task_span(attr, &span);
             }
             SPAN_EXEC => {
-                let message = create_exec_span(attr, &span);
+                let message = create_exec_span(attr, &span, &self.registry, self.metrics_config);
                 self.stats_sender.send(message);
             }
             SPAN_SCENARIO => {
@@ -193,12 +400,19 @@ impl<T: Sender + 'static, S: tracing::Subscriber + for<'a> LookupSpan<'a>> Layer
 
     fn on_event(&self, event: &tracing::Event<'_>, ctx: tracing_subscriber::layer::Context<'_, S>) {
         if event.metadata().target() == USER_TASK {
-            handle_user_event(event, &ctx);
+            if let Some(message) = handle_user_event(event, &ctx, &self.filter) {
+                self.stats_sender.send(message);
+            }
             return;
         }
         if event.metadata().target() == CRATE_NAME {
             match event.metadata().name() {
                 "runner_exit" => {
+                    if !self.thresholds.is_empty() {
+                        self.stats_sender.send(Message::Thresholds {
+                            results: self.thresholds.results(),
+                        });
+                    }
                     self.stats_sender.send(Message::End);
                     return;
                 }
@@ -212,7 +426,39 @@ impl<T: Sender + 'static, S: tracing::Subscriber + for<'a> LookupSpan<'a>> Layer
                 "error" => {
                     let mut err = ErrorVisitor::default();
                     event.record(&mut err);
-                    self.stats_sender.send(Message::Error { err: err.err });
+                    let span = ctx.event_span(event);
+                    if let Some(task) = span
+                        .as_ref()
+                        .and_then(|span| span.scope().find(|x| x.name() == SPAN_TASK))
+                    {
+                        if let Some(data) = task.extensions_mut().get_mut::<TaskData>() {
+                            data.failed = true;
+                            if self.iterations {
+                                data.error = Some(err.err.clone());
+                            }
+                        }
+                    }
+                    let exec = span.and_then(|span| span.scope().find(|x| x.name() == SPAN_EXEC));
+                    let execution_id = exec.and_then(|exec| {
+                        let ext = exec.extensions();
+                        let data = ext.get::<ExecutionData>()?;
+                        if let Some(class) = err.class.take() {
+                            let overflow = data.metrics.update(TaskEvent::new(
+                                ERRORS_TOTAL,
+                                MetricType::Counter,
+                                vec![("class", task_event::Value::String(class))],
+                                task_event::Value::UnsignedNumber(1),
+                            ));
+                            if let Some(name) = overflow {
+                                self.stats_sender.send(cardinality_warning(data.id, name));
+                            }
+                        }
+                        Some(data.id)
+                    });
+                    self.stats_sender.send(Message::Error {
+                        execution_id,
+                        err: err.err,
+                    });
                     return;
                 }
                 _ => {}
@@ -244,21 +490,46 @@ impl<T: Sender + 'static, S: tracing::Subscriber + for<'a> LookupSpan<'a>> Layer
         let Some(span) = ctx.span(&id) else { return };
 
         if span.metadata().name() == SPAN_EXEC {
-            let message = close_exec_span(span);
-            self.stats_sender.send(message);
+            for message in close_exec_span(span, &self.registry) {
+                self.stats_sender.send(message);
+            }
             return;
         }
 
         if span.metadata().name() == SPAN_TASK {
+            let record = self.iterations.then(|| iteration_record(&span));
             let messages = close_task_span(span, &ctx);
-            for message in messages {
+            let thresholds = match &messages {
+                [Message::TaskTime {
+                    scenario_id,
+                    execution_id,
+                    ..
+                }, Message::ExecutorUpdate { metrics, .. }] => self
+                    .thresholds
+                    .evaluate((*scenario_id, *execution_id), metrics)
+                    .map(|results| Message::Thresholds { results }),
+                _ => None,
+            };
+            let rollup = match &messages {
+                [Message::TaskTime { scenario_id, .. }, _] => self.rollup_if_due(*scenario_id),
+                _ => None,
+            };
+            for message in messages
+                .into_iter()
+                .chain(record)
+                .chain(thresholds)
+                .chain(rollup)
+            {
                 self.stats_sender.send(message);
             }
             return;
         }
 
         if span.metadata().target() == USER_TASK {
-            close_task_child_span(span, &ctx);
+            if let Some(message) = close_task_child_span(span, &ctx, &self.filter, self.iterations)
+            {
+                self.stats_sender.send(message);
+            }
         }
     }
 }
@@ -281,6 +552,8 @@ fn create_scenario_span<S: for<'a> LookupSpan<'a>>(
 fn create_exec_span<'a, S: LookupSpan<'a>>(
     attr: &span::Attributes,
     span: &SpanRef<'a, S>,
+    registry: &MetricRegistry,
+    config: MetricsConfig,
 ) -> Message {
     let mut visitor = ExecutionData {
         id: usize::MAX,
@@ -292,20 +565,29 @@ fn create_exec_span<'a, S: LookupSpan<'a>>(
         total_stages: None,
         stage: None,
         stage_duration: None,
-        metrics: MetricSet::default(),
+        expected_interval: None,
+        arrival_correction: false,
+        user_ids: false,
+        metrics: Arc::default(),
+        last_counts: Mutex::new((Instant::now(), HashMap::new())),
+        last_window: Mutex::new(Instant::now()),
     };
     attr.values().record(&mut visitor);
     let id = visitor.id;
-    let mut extentions = span.extensions_mut();
-    extentions.insert(visitor);
 
     let start_time = Utc::now();
 
     let scenario = span.parent().unwrap();
     let mut scenario = scenario.extensions_mut();
+    let scenario = scenario.get_mut::<ScenarioData>().unwrap();
+    visitor.metrics = registry
+        .entry((scenario.id, id))
+        .or_insert_with(|| Arc::new(MetricSet::new(config)))
+        .clone();
+    let mut extentions = span.extensions_mut();
+    extentions.insert(visitor);
+
     let scenario_data = scenario
-        .get_mut::<ScenarioData>()
-        .unwrap()
         .executor_timings
         .entry(id)
         .or_insert_with(|| ExecutorTimings {
@@ -322,7 +604,7 @@ fn create_exec_span<'a, S: LookupSpan<'a>>(
     }
 }
 
-fn create_task_span<'a, S: LookupSpan<'a>>(span: &SpanRef<'a, S>) {
+fn create_task_span<'a, S: LookupSpan<'a>>(attr: &span::Attributes, span: &SpanRef<'a, S>) {
     let Some(exec_span) = span.parent() else {
         return;
     };
@@ -336,21 +618,35 @@ fn create_task_span<'a, S: LookupSpan<'a>>(span: &SpanRef<'a, S>) {
         .expect("exec parent is scenario")
         .id;
 
-    let execution_id = exec_span
-        .extensions()
-        .get::<ExecutionData>()
-        .expect("task parent is exec")
-        .id;
+    let (execution_id, user_ids) = {
+        let ext = exec_span.extensions();
+        let data = ext.get::<ExecutionData>().expect("task parent is exec");
+        (data.id, data.user_ids)
+    };
 
     let execution_span_id = exec_span.id();
 
-    let mut extentions = span.extensions_mut();
-    extentions.insert(TaskData {
+    let mut data = TaskData {
+        failed: false,
         instant: Instant::now(),
         scenario_id,
         execution_id,
         execution_span_id,
-    });
+        user_id: None,
+        error: None,
+        groups: Vec::new(),
+    };
+    if user_ids {
+        attr.record(&mut data);
+    }
+    span.extensions_mut().insert(data);
+}
+
+/// [`USER_ID`] attribute of the task a span belongs to, if its executor tags metrics with it.
+fn user_id_attribute<'a, S: LookupSpan<'a>>(span: &SpanRef<'a, S>) -> Option<Attribute> {
+    let task = span.scope().find(|x| x.name() == SPAN_TASK)?;
+    let user_id = task.extensions().get::<TaskData>()?.user_id?;
+    Some((USER_ID, task_event::Value::UnsignedNumber(user_id)))
 }
 
 fn create_task_child_span<'a, S: LookupSpan<'a>>(span: &SpanRef<'a, S>, attr: &span::Attributes) {
@@ -376,14 +672,13 @@ fn create_task_child_span<'a, S: LookupSpan<'a>>(span: &SpanRef<'a, S>, attr: &s
 fn handle_user_event<S: Subscriber + for<'a> LookupSpan<'a>>(
     event: &tracing::Event,
     ctx: &tracing_subscriber::layer::Context<S>,
-) -> ControlFlow<(), ()> {
+    filter: &MetricFilter,
+) -> Option<Message> {
     if event.metadata().target() != USER_TASK {
-        return ControlFlow::Break(());
+        return None;
     }
 
-    let Some(parent) = ctx.current_span().id().and_then(|id| ctx.span(id)) else {
-        return ControlFlow::Break(());
-    };
+    let parent = ctx.current_span().id().and_then(|id| ctx.span(id))?;
 
     let attributes: Vec<_> = parent
         .scope()
@@ -401,31 +696,28 @@ fn handle_user_event<S: Subscriber + for<'a> LookupSpan<'a>>(
         })
         .collect();
 
-    let Some(exec_span) = parent.scope().find(|span| span.name() == SPAN_EXEC) else {
-        return ControlFlow::Break(());
-    };
+    let exec_span = parent.scope().find(|span| span.name() == SPAN_EXEC)?;
 
-    let Some((name, ty_str)) = event.metadata().name().split_once('.') else {
-        return ControlFlow::Break(());
-    };
+    let (name, ty_str) = event.metadata().name().split_once('.')?;
 
     let Ok(metric_type) = MetricType::from_str(ty_str) else {
-        return ControlFlow::Break(());
+        return None;
     };
 
-    let mut task_event = TaskEvent::new(
-        name,
-        metric_type,
-        attributes.into_iter().rev().flatten().collect(),
-        task_event::Value::Number(0),
-    );
+    let mut attributes = inherit_attributes(attributes.into_iter().rev());
+    attributes.extend(user_id_attribute(&parent));
+    let mut task_event =
+        TaskEvent::new(name, metric_type, attributes, task_event::Value::Number(0));
     event.record(&mut task_event);
+    if !filter.is_empty() && !filter.allows(task_event.key()) {
+        return None;
+    }
 
     let data = exec_span.extensions();
     let data = data.get::<ExecutionData>().unwrap();
-    data.metrics.update(task_event);
-
-    ControlFlow::Continue(())
+    data.metrics
+        .update(task_event)
+        .map(|name| cardinality_warning(data.id, name))
 }
 
 fn handle_crate_execution_event<S: Subscriber + for<'a> LookupSpan<'a>>(
@@ -443,7 +735,10 @@ fn handle_crate_execution_event<S: Subscriber + for<'a> LookupSpan<'a>>(
     Some(Message::from(&*exec_data))
 }
 
-fn close_exec_span<S: Subscriber + for<'a> LookupSpan<'a>>(span: SpanRef<S>) -> Message {
+fn close_exec_span<S: Subscriber + for<'a> LookupSpan<'a>>(
+    span: SpanRef<S>,
+    registry: &MetricRegistry,
+) -> [Message; 2] {
     let exec_id = span.extensions().get::<ExecutionData>().unwrap().id;
     let scenario = span.parent().unwrap();
     let mut scenario = scenario.extensions_mut();
@@ -452,7 +747,30 @@ fn close_exec_span<S: Subscriber + for<'a> LookupSpan<'a>>(span: SpanRef<S>) ->
         .executor_timings
         .entry(exec_id)
         .and_modify(|x| x.prior_duration += (Utc::now() - x.start_time).abs().to_std().unwrap());
-    Message::ExecutorEnd { id: exec_id }
+    [
+        Message::ExecutorEnd { id: exec_id },
+        scenario_rollup(registry, scenario.id),
+    ]
+}
+
+fn scenario_rollup(registry: &MetricRegistry, scenario_id: usize) -> Message {
+    Message::ScenarioUpdate {
+        id: scenario_id,
+        metrics: scenario_metrics(registry, scenario_id),
+    }
+}
+
+/// Metrics of every executor of a scenario combined.
+pub(crate) fn scenario_metrics(
+    registry: &MetricRegistry,
+    scenario_id: usize,
+) -> Vec<(MetricSetKey, MetricValue)> {
+    let sets: Vec<_> = registry
+        .iter()
+        .filter(|x| x.key().0 == scenario_id)
+        .map(|x| x.value().clone())
+        .collect();
+    MetricSet::rollup(sets.iter().map(|x| x.as_ref()))
 }
 
 fn close_task_span<'a, S: Subscriber + for<'lookup> LookupSpan<'lookup>>(
@@ -461,15 +779,34 @@ fn close_task_span<'a, S: Subscriber + for<'lookup> LookupSpan<'lookup>>(
 ) -> [Message; 2] {
     let extention = span.extensions();
     let task_data = extention.get::<TaskData>().unwrap();
+    let duration = task_data.instant.elapsed();
     let m1 = Message::TaskTime {
         execution_id: task_data.execution_id,
         scenario_id: task_data.scenario_id,
-        duration: task_data.instant.elapsed(),
+        duration,
     };
 
     let exec = ctx.span(&task_data.execution_span_id).unwrap();
     let ext = exec.extensions();
     let exec_data = ext.get::<ExecutionData>().unwrap();
+    let result = if task_data.failed { "error" } else { "ok" };
+    let _ = exec_data.metrics.update(TaskEvent::new(
+        ITERATIONS_TOTAL,
+        MetricType::Counter,
+        vec![("result", task_event::Value::String(result.to_string()))],
+        task_event::Value::UnsignedNumber(1),
+    ));
+    if exec_data.expected_interval.is_some() {
+        let _ = exec_data.metrics.update_corrected(
+            TaskEvent::new(
+                ITERATION_DURATION,
+                MetricType::Histogram,
+                vec![],
+                duration.into(),
+            ),
+            exec_data.expected_interval,
+        );
+    }
     let m2 = Message::from(exec_data);
     [m1, m2]
 }
@@ -477,7 +814,9 @@ fn close_task_span<'a, S: Subscriber + for<'lookup> LookupSpan<'lookup>>(
 fn close_task_child_span<'a, S: Subscriber + for<'lookup> LookupSpan<'lookup>>(
     span: SpanRef<S>,
     ctx: &tracing_subscriber::layer::Context<S>,
-) {
+    filter: &MetricFilter,
+    iterations: bool,
+) -> Option<Message> {
     let extention = span.extensions();
     let task_inner_span = extention.get::<TaskSpanData>().unwrap();
 
@@ -501,22 +840,93 @@ fn close_task_child_span<'a, S: Subscriber + for<'lookup> LookupSpan<'lookup>>(
         .collect();
 
     attributes.reverse();
+    let mut attributes = inherit_attributes(attributes);
+    attributes.extend(user_id_attribute(&span));
+    let duration = task_inner_span.start_time.elapsed();
+    if iterations && span.name() == GROUP_DURATION {
+        record_group(&span, &attributes, duration);
+    }
     let event = TaskEvent::new(
         span.name(),
         MetricType::Histogram,
-        attributes.into_iter().flatten().collect(),
-        task_inner_span.start_time.elapsed().into(),
+        attributes,
+        duration.into(),
     );
+    if !filter.is_empty() && !filter.allows(event.key()) {
+        return None;
+    }
 
     let task_span = span
         .scope()
         .find(|x| x.metadata().name() == SPAN_EXEC)
         .unwrap();
     let task_span = ctx.span(&task_span.id()).unwrap();
-    task_span
-        .extensions()
-        .get::<ExecutionData>()
-        .unwrap()
-        .metrics
-        .update(event);
+    let data = task_span.extensions();
+    let data = data.get::<ExecutionData>().unwrap();
+    data.metrics
+        .update(event)
+        .map(|name| cardinality_warning(data.id, name))
+}
+
+/// Record on the task a group belongs to that the group ended.
+fn record_group<'a, S: LookupSpan<'a>>(
+    span: &SpanRef<'a, S>,
+    attributes: &[Attribute],
+    duration: Duration,
+) {
+    let Some(task) = span.scope().find(|x| x.name() == SPAN_TASK) else {
+        return;
+    };
+    let Some((_, name)) = attributes.iter().find(|(x, _)| *x == GROUP) else {
+        return;
+    };
+    let mut extensions = task.extensions_mut();
+    if let Some(data) = extensions.get_mut::<TaskData>() {
+        data.groups.push(GroupTiming {
+            name: name.to_string(),
+            duration,
+        });
+    }
+}
+
+/// Outcome of the iteration of a task span that is closing.
+fn iteration_record<'a, S: LookupSpan<'a>>(span: &SpanRef<'a, S>) -> Message {
+    let mut extensions = span.extensions_mut();
+    let data = extensions.get_mut::<TaskData>().unwrap();
+    let duration = data.instant.elapsed();
+    Message::Iteration(IterationRecord {
+        time: Utc::now() - duration,
+        scenario_id: data.scenario_id,
+        execution_id: data.execution_id,
+        user_id: data.user_id,
+        duration,
+        result: if data.failed { "error" } else { "ok" },
+        error: data.error.take(),
+        groups: std::mem::take(&mut data.groups),
+    })
+}
+
+/// Attributes of nested spans, given outermost first. An attribute of an inner span replaces
+/// the one of the same name from its parents, except for [`GROUP`] whose values are joined into a path.
+fn inherit_attributes(spans: impl IntoIterator<Item = Vec<Attribute>>) -> Vec<Attribute> {
+    let mut attributes: Vec<Attribute> = Vec::new();
+    for (name, value) in spans.into_iter().flatten() {
+        match attributes.iter_mut().find(|(x, _)| *x == name) {
+            Some((_, existing)) if name == GROUP => {
+                *existing = task_event::Value::String(format!("{existing}::{value}"))
+            }
+            Some((_, existing)) => *existing = value,
+            None => attributes.push((name, value)),
+        }
+    }
+    attributes
+}
+
+fn cardinality_warning(execution_id: usize, name: &str) -> Message {
+    Message::Warning {
+        execution_id: Some(execution_id),
+        warning: format!(
+            "metric `{name}` is over its cardinality limit, new attribute sets are recorded as `{OVERFLOW_ATTRIBUTE}`"
+        ),
+    }
 }
@@ -0,0 +1,117 @@
+//! Rules selecting which metrics emitted from user tasks are recorded.
+//!
+//! Events dropped by a filter are discarded before they reach a metric set, which saves the cost
+//! of aggregating metrics nobody looks at, such as the per request metrics of the bundled clients.
+//! Built-in `iterations_total` and `errors_total` counters are always recorded.
+//!
+//! ```
+//! use rusher::tracing::filter::MetricFilter;
+//!
+//! let filter = MetricFilter::default()
+//!     .include_prefix("checkout")
+//!     .exclude_attribute("endpoint", Some("/health"));
+//! ```
+
+use std::borrow::Cow;
+
+use super::task_event::MetricSetKey;
+
+#[derive(Debug, Clone)]
+enum Rule {
+    Prefix(Cow<'static, str>),
+    /// attribute name and the value it must have, any value if None
+    Attribute(Cow<'static, str>, Option<String>),
+}
+
+impl Rule {
+    fn matches(&self, key: &MetricSetKey) -> bool {
+        match self {
+            Rule::Prefix(prefix) => key.name.starts_with(prefix.as_ref()),
+            Rule::Attribute(name, value) => key.attributes.iter().any(|(x, v)| {
+                x == name && value.as_ref().is_none_or(|value| v.to_string() == *value)
+            }),
+        }
+    }
+}
+
+/// Include and exclude rules for user metrics.
+///
+/// Without include rules every metric is included, otherwise a metric has to match at least one of them.
+/// A metric matching any exclude rule is dropped, even if it is also included.
+#[derive(Debug, Clone, Default)]
+pub struct MetricFilter {
+    include: Vec<Rule>,
+    exclude: Vec<Rule>,
+}
+
+impl MetricFilter {
+    /// Include metrics whose name starts with `prefix`.
+    pub fn include_prefix(mut self, prefix: impl Into<Cow<'static, str>>) -> Self {
+        self.include.push(Rule::Prefix(prefix.into()));
+        self
+    }
+
+    /// Drop metrics whose name starts with `prefix`.
+    pub fn exclude_prefix(mut self, prefix: impl Into<Cow<'static, str>>) -> Self {
+        self.exclude.push(Rule::Prefix(prefix.into()));
+        self
+    }
+
+    /// Include metrics with the attribute `name`, set to `value` if one is given.
+    pub fn include_attribute(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        value: Option<&str>,
+    ) -> Self {
+        self.include
+            .push(Rule::Attribute(name.into(), value.map(str::to_string)));
+        self
+    }
+
+    /// Drop metrics with the attribute `name`, set to `value` if one is given.
+    pub fn exclude_attribute(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        value: Option<&str>,
+    ) -> Self {
+        self.exclude
+            .push(Rule::Attribute(name.into(), value.map(str::to_string)));
+        self
+    }
+
+    /// Returns true if the metric should be recorded.
+    pub fn allows(&self, key: &MetricSetKey) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|x| x.matches(key)))
+            && !self.exclude.iter().any(|x| x.matches(key))
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MetricFilter;
+    use crate::tracing::task_event::{metrics::MetricType, MetricSetKey, Value};
+
+    fn key(name: &'static str, endpoint: &str) -> MetricSetKey {
+        MetricSetKey {
+            name,
+            metric_type: MetricType::Counter,
+            attributes: vec![("endpoint", Value::String(endpoint.to_string()))],
+        }
+    }
+
+    #[test]
+    fn include_and_exclude() {
+        let filter = MetricFilter::default()
+            .include_prefix("checkout")
+            .exclude_attribute("endpoint", Some("/health"));
+
+        assert!(filter.allows(&key("checkout_step", "/cart")));
+        assert!(!filter.allows(&key("checkout_step", "/health")));
+        assert!(!filter.allows(&key("status", "/cart")));
+        assert!(MetricFilter::default().allows(&key("status", "/health")));
+    }
+}
@@ -0,0 +1,112 @@
+//! Log file receiving `tracing` events of users while the runner owns the global subscriber.
+//!
+//! The runner installs its own subscriber to collect metrics, so events logged by users and
+//! the crates they call are otherwise lost, and the terminal UI leaves no room to print them.
+//! A [`LogFile`] registered with [`Runner::log_file`](crate::runner::Runner::log_file) writes
+//! every event that is not a metric of this crate to a file rotated over time, along with the
+//! errors returned by users.
+//!
+//! ```
+//! use rusher::tracing::log_file::{LogFile, Rotation};
+//! use tracing::level_filters::LevelFilter;
+//!
+//! let log_file = LogFile::new("logs", "run.log")
+//!     .rotation(Rotation::HOURLY)
+//!     .max_files(24)
+//!     .level(LevelFilter::DEBUG);
+//! ```
+
+use std::path::PathBuf;
+
+use tracing::{level_filters::LevelFilter, Metadata};
+use tracing_subscriber::{filter::filter_fn, Layer, Registry};
+
+pub use tracing_appender::rolling::Rotation;
+
+use crate::{error::Error, CRATE_NAME, USER_TASK};
+
+/// Rotated log file for user events, see the [module](self) documentation.
+#[derive(Debug, Clone)]
+pub struct LogFile {
+    directory: PathBuf,
+    prefix: String,
+    rotation: Rotation,
+    max_files: Option<usize>,
+    level: LevelFilter,
+    json: bool,
+}
+
+impl LogFile {
+    /// Write to files in `directory` named `prefix` followed by the date of the period they cover.
+    pub fn new(directory: impl Into<PathBuf>, prefix: impl Into<String>) -> Self {
+        Self {
+            directory: directory.into(),
+            prefix: prefix.into(),
+            rotation: Rotation::DAILY,
+            max_files: None,
+            level: LevelFilter::INFO,
+            json: false,
+        }
+    }
+
+    /// How often a new file is started, daily by default.
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Number of files kept, older ones are deleted as new ones are started. All are kept by default.
+    pub fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files = Some(max_files);
+        self
+    }
+
+    /// Most verbose level written, `INFO` by default. Errors of users are written at any level.
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Write every event as a JSON object on a line of its own instead of plain text.
+    pub fn json(mut self, enable: bool) -> Self {
+        self.json = enable;
+        self
+    }
+
+    /// Layer writing to the file, created along with its directory if missing.
+    pub(crate) fn layer(&self) -> Result<Box<dyn Layer<Registry> + Send + Sync>, Error> {
+        let mut builder = tracing_appender::rolling::Builder::new()
+            .rotation(self.rotation.clone())
+            .filename_prefix(&self.prefix);
+        if let Some(max_files) = self.max_files {
+            builder = builder.max_log_files(max_files);
+        }
+        let appender = builder.build(&self.directory).map_err(|err| {
+            Error::new(format!(
+                "failed to open log file in {}: {err}",
+                self.directory.display()
+            ))
+        })?;
+
+        let level = self.level;
+        let filter = filter_fn(move |metadata| logged(metadata, level));
+        let layer = tracing_subscriber::fmt::layer()
+            .with_writer(appender)
+            .with_ansi(false);
+        Ok(match self.json {
+            true => layer.json().with_filter(filter).boxed(),
+            false => layer.with_filter(filter).boxed(),
+        })
+    }
+}
+
+/// Whether an event is written, which leaves out metrics and spans of this crate.
+fn logged(metadata: &Metadata<'_>, level: LevelFilter) -> bool {
+    match metadata.target() {
+        USER_TASK => false,
+        CRATE_NAME => {
+            metadata.is_event() && matches!(metadata.name(), "error" | "termination_error")
+        }
+        _ => metadata.level() <= &level,
+    }
+}
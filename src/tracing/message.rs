@@ -3,6 +3,7 @@ use std::time::Duration;
 use chrono::{DateTime, Utc};
 
 use super::task_event::{metrics::MetricValue, MetricSetKey};
+use crate::thresholds::ThresholdResult;
 
 /// Output Message genenerated by this tracing layer
 #[derive(Debug, Clone)]
@@ -33,23 +34,94 @@ pub enum Message {
         stage_duration: Option<Duration>,
         #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
         stages: Option<usize>,
+        /// cumulative values since the executor started
         metrics: Vec<(MetricSetKey, MetricValue)>,
+        /// values over the last [`WINDOW`](super::task_event::metrics::WINDOW) only, computed at most
+        /// once a second and empty in the updates in between
+        window_metrics: Vec<(MetricSetKey, MetricValue)>,
+        /// time since the previous update of this executor
+        interval: Duration,
+        /// change of counter totals and histogram counts over `interval`, unchanged ones are left out
+        deltas: Vec<(MetricSetKey, u64)>,
     },
     ExecutorEnd {
         id: usize,
     },
     Error {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        execution_id: Option<usize>,
         err: String,
     },
+    Warning {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        execution_id: Option<usize>,
+        warning: String,
+    },
     TerminatedError {
         err: String,
     },
     ScenarioChanged {
         scenario_id: usize,
     },
+    /// Metrics of all executors of a scenario combined, see [`MetricSet::rollup`](super::task_event::MetricSet::rollup).
+    /// Sent at most once a second while the scenario runs and whenever one of its executors ends.
+    ScenarioUpdate {
+        id: usize,
+        metrics: Vec<(MetricSetKey, MetricValue)>,
+    },
+    /// Outcome of a single iteration, only sent when a sink asks for them with
+    /// [`MetricSink::iterations`](super::sink::MetricSink::iterations) or the runner
+    /// keeps the [`slowest_iterations`](crate::runner::Runner::slowest_iterations).
+    Iteration(IterationRecord),
+    /// Sent whenever the outcome of a threshold changes and once more before [`Message::End`].
+    Thresholds {
+        results: Vec<ThresholdResult>,
+    },
     End,
 }
 
+/// Outcome and timings of a single call of a user.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct IterationRecord {
+    /// when the iteration started
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_to_rfc3339_opts"))]
+    pub time: DateTime<Utc>,
+    pub scenario_id: usize,
+    pub execution_id: usize,
+    /// index of the user, only tracked when its executor tags metrics with it
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub user_id: Option<u64>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "duration_ms", serialize_with = "serialize_millis")
+    )]
+    pub duration: Duration,
+    /// `ok` or `error`, same as the `result` attribute of [`ITERATIONS_TOTAL`](super::ITERATIONS_TOTAL)
+    pub result: &'static str,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub error: Option<String>,
+    /// groups completed within the iteration, in the order they ended
+    pub groups: Vec<GroupTiming>,
+}
+
+/// Duration of a [`group`](crate::user::group), nested groups are named by their path such as `checkout::login`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GroupTiming {
+    pub name: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "duration_ms", serialize_with = "serialize_millis")
+    )]
+    pub duration: Duration,
+}
+
+#[cfg(feature = "serde")]
+fn serialize_millis<S: serde::Serializer>(t: &Duration, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_f64(t.as_secs_f64() * 1000.)
+}
+
 #[cfg(feature = "serde")]
 pub fn serialize_to_rfc3339_opts<S: serde::Serializer>(
     t: &DateTime<Utc>,
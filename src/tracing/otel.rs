@@ -0,0 +1,181 @@
+//! Export of user iterations as OpenTelemetry traces.
+//!
+//! Every call of a user becomes a root span named `iteration`, tagged with the scenario, the executor
+//! and the [`USER_ID`](super::USER_ID) of the user. Spans opened within the call, such as [`group`](crate::user::group)s,
+//! are exported as its children. Iterations that return an error have an error status.
+//!
+//! Spans are handed to the tracer given to [`Runner::export_traces`](crate::runner::Runner::export_traces),
+//! which decides where they go through its tracer provider, such as an OTLP exporter to Jaeger or Tempo.
+
+use std::borrow::Cow;
+
+use opentelemetry::{
+    global::BoxedTracer,
+    trace::{Status, TraceContextExt, Tracer},
+    Context, KeyValue,
+};
+use tracing::{
+    field::{Field, Visit},
+    span,
+};
+use tracing_subscriber::{layer, registry::LookupSpan, Layer};
+
+use crate::{CRATE_NAME, SPAN_EXEC, SPAN_SCENARIO, SPAN_TASK, USER_TASK};
+
+use super::GROUP;
+
+/// Name of the span exported for each iteration.
+pub const ITERATION_SPAN: &str = "iteration";
+
+/// Tracing layer exporting user iterations through an OpenTelemetry tracer.
+pub struct TraceExporter {
+    tracer: BoxedTracer,
+}
+
+impl TraceExporter {
+    pub fn new(tracer: BoxedTracer) -> Self {
+        Self { tracer }
+    }
+}
+
+/// Name of a scenario or an executor span.
+struct Name(String);
+
+/// OpenTelemetry span of an iteration or of a span within it.
+struct Exported(Context);
+
+#[derive(Default)]
+struct Fields {
+    name: Option<String>,
+    attributes: Vec<KeyValue>,
+}
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.record(field, format!("{value:?}"));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, value.to_string());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.attributes.push(KeyValue::new(field.name(), value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.attributes
+            .push(KeyValue::new(field.name(), value as i64));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.attributes.push(KeyValue::new(field.name(), value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.attributes.push(KeyValue::new(field.name(), value));
+    }
+}
+
+impl Fields {
+    fn record(&mut self, field: &Field, value: String) {
+        if field.name() == "name" {
+            self.name = Some(value.clone());
+        }
+        self.attributes.push(KeyValue::new(field.name(), value));
+    }
+}
+
+/// Error message of an error event of [`CRATE_NAME`].
+#[derive(Default)]
+struct ErrorMessage(Option<String>);
+
+impl Visit for ErrorMessage {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "err" {
+            self.0 = Some(format!("{value:?}"));
+        }
+    }
+}
+
+impl<S: tracing::Subscriber + for<'a> LookupSpan<'a>> Layer<S> for TraceExporter {
+    fn on_new_span(&self, attr: &span::Attributes<'_>, id: &span::Id, ctx: layer::Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut fields = Fields::default();
+        attr.record(&mut fields);
+
+        let target = span.metadata().target();
+        if target == CRATE_NAME && matches!(span.name(), SPAN_SCENARIO | SPAN_EXEC) {
+            if let Some(name) = fields.name {
+                span.extensions_mut().insert(Name(name));
+            }
+            return;
+        }
+
+        let (name, parent, attributes) = if target == CRATE_NAME && span.name() == SPAN_TASK {
+            let mut attributes = fields.attributes;
+            for (key, ancestor) in ["executor", "scenario"]
+                .into_iter()
+                .zip(span.scope().skip(1))
+            {
+                if let Some(Name(name)) = ancestor.extensions().get::<Name>() {
+                    attributes.push(KeyValue::new(key, name.clone()));
+                }
+            }
+            let name = Cow::Borrowed(ITERATION_SPAN);
+            (name, Context::new(), attributes)
+        } else if target == USER_TASK {
+            let Some(parent) = span
+                .parent()
+                .and_then(|x| x.extensions().get::<Exported>().map(|x| x.0.clone()))
+            else {
+                return;
+            };
+            // Groups are named after the group rather than the histogram they record.
+            let name = fields
+                .attributes
+                .iter()
+                .find(|x| x.key.as_str() == GROUP)
+                .map_or(Cow::Borrowed(span.name()), |x| {
+                    Cow::Owned(x.value.as_str().into_owned())
+                });
+            (name, parent, fields.attributes)
+        } else {
+            return;
+        };
+
+        let builder = self.tracer.span_builder(name).with_attributes(attributes);
+        let exported = self.tracer.build_with_context(builder, &parent);
+        span.extensions_mut()
+            .insert(Exported(parent.with_span(exported)));
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, ctx: layer::Context<'_, S>) {
+        let metadata = event.metadata();
+        if metadata.target() != CRATE_NAME || metadata.name() != "error" {
+            return;
+        }
+        let Some(task) = ctx
+            .event_scope(event)
+            .and_then(|mut scope| scope.find(|x| x.name() == SPAN_TASK))
+        else {
+            return;
+        };
+        let extensions = task.extensions();
+        let Some(Exported(cx)) = extensions.get::<Exported>() else {
+            return;
+        };
+        let mut message = ErrorMessage::default();
+        event.record(&mut message);
+        cx.span()
+            .set_status(Status::error(message.0.unwrap_or_default()));
+    }
+
+    fn on_close(&self, id: span::Id, ctx: layer::Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let exported = span.extensions_mut().remove::<Exported>();
+        if let Some(Exported(cx)) = exported {
+            cx.span().end();
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Extension point for sending metrics to backends outside of this crate.
+//!
+//! A sink receives the same [`Message`]s as the terminal and web UIs, which carry executor
+//! metrics, scenario roll-ups and threshold results as they change. Register sinks with
+//! [`Runner::sink`](crate::runner::Runner::sink). Sinks for [`statsd`], [`influx`], [`graphite`] and [`csv`] are bundled,
+//! along with `ndjson` for records of every iteration and `live` for a file of executor updates
+//! with the `serde` feature, `annotations` for Grafana annotations or webhooks at scenario
+//! and stage boundaries with `reqwest` and `serde`, and `push` for streaming messages out to a
+//! remote WebSocket collector with the `push` feature.
+//!
+//! ```
+//! use rusher::tracing::{message::Message, sink::MetricSink};
+//!
+//! struct Stdout;
+//!
+//! impl MetricSink for Stdout {
+//!     fn handle(&mut self, message: &Message) {
+//!         if let Message::ExecutorUpdate { id, metrics, .. } = message {
+//!             for (key, value) in metrics {
+//!                 println!("{id} {} {}", key.name, value.to_string());
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+
+#[cfg(all(feature = "reqwest", feature = "serde"))]
+pub mod annotations;
+pub mod csv;
+pub mod graphite;
+pub mod influx;
+#[cfg(feature = "serde")]
+pub mod live;
+#[cfg(feature = "serde")]
+pub mod ndjson;
+#[cfg(feature = "push")]
+pub mod push;
+pub mod statsd;
+
+use std::time::Duration;
+
+use super::{
+    message::Message,
+    task_event::metrics::{ratio, MetricValue},
+};
+
+/// Receiver of every message generated by the tracing layer.
+///
+/// Each sink runs on a blocking thread of its own, so it may write to files or sockets
+/// directly. A slow sink delays only itself, its messages are queued meanwhile.
+pub trait MetricSink: Send + 'static {
+    /// Called once before the first message with the name of every scenario and of its executors,
+    /// so that the ids carried by messages can be named. Ids index these lists.
+    fn start(&mut self, _scenarios: &[(String, Vec<String>)]) {}
+
+    /// Called for every message in the order they were generated, up to and including [`Message::End`].
+    fn handle(&mut self, message: &Message);
+
+    /// Called once after the last message, to flush anything that is buffered.
+    fn finish(&mut self) {}
+
+    /// Whether the sink wants a [`Message::Iteration`] for every iteration. Recording them has a cost,
+    /// so they are only generated when at least one sink asks for them.
+    fn iterations(&self) -> bool {
+        false
+    }
+}
+
+impl<F: FnMut(&Message) + Send + 'static> MetricSink for F {
+    fn handle(&mut self, message: &Message) {
+        self(message)
+    }
+}
+
+/// Milliseconds of a duration, as most metric backends expect.
+pub(crate) fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.
+}
+
+/// Value of a metric field, as sent to backends that tell integers and floats apart.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Field {
+    Float(f64),
+    Integer(u64),
+}
+
+impl std::fmt::Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Field::Float(x) => write!(f, "{x}"),
+            Field::Integer(x) => write!(f, "{x}"),
+        }
+    }
+}
+
+/// Fields of a metric value such as its percentiles, durations are in milliseconds.
+pub(crate) fn fields(value: &MetricValue) -> Vec<(&'static str, Field)> {
+    use Field::{Float, Integer};
+
+    let percentiles = |(p50, p90, p95, p99): (f64, f64, f64, f64)| {
+        [
+            ("p50", Float(p50)),
+            ("p90", Float(p90)),
+            ("p95", Float(p95)),
+            ("p99", Float(p99)),
+        ]
+    };
+    let duration_percentiles = |(p50, p90, p95, p99): (Duration, Duration, Duration, Duration)| {
+        percentiles((millis(p50), millis(p90), millis(p95), millis(p99)))
+    };
+
+    match *value {
+        MetricValue::Counter(x) => vec![("value", Integer(x))],
+        MetricValue::GaugeF64(x) => vec![("value", Float(x))],
+        MetricValue::GaugeI64(x) => vec![("value", Float(x as f64))],
+        MetricValue::GaugeU64(x) => vec![("value", Float(x as f64))],
+        MetricValue::GaugeDuration(x) => vec![("value", Float(millis(x)))],
+        MetricValue::Histogram((x, sum, (min, max))) => {
+            let mut fields = percentiles(x).to_vec();
+            fields.extend([
+                ("sum", Float(sum)),
+                ("min", Float(min)),
+                ("max", Float(max)),
+            ]);
+            fields
+        }
+        MetricValue::DurationHistogram((x, sum, (min, max))) => {
+            let mut fields = duration_percentiles(x).to_vec();
+            fields.extend([
+                ("sum", Float(millis(sum))),
+                ("min", Float(millis(min))),
+                ("max", Float(millis(max))),
+            ]);
+            fields
+        }
+        MetricValue::Trend(x) => {
+            let mut fields = percentiles(x.percentiles).to_vec();
+            fields.extend([
+                ("count", Integer(x.count)),
+                ("mean", Float(x.mean)),
+                ("stddev", Float(x.stddev)),
+                ("min", Float(x.min)),
+                ("max", Float(x.max)),
+            ]);
+            fields
+        }
+        MetricValue::DurationTrend(x) => {
+            let mut fields = duration_percentiles(x.percentiles).to_vec();
+            fields.extend([
+                ("count", Integer(x.count)),
+                ("mean", Float(millis(x.mean))),
+                ("stddev", Float(millis(x.stddev))),
+                ("min", Float(millis(x.min))),
+                ("max", Float(millis(x.max))),
+            ]);
+            fields
+        }
+        MetricValue::Rate((rate, window)) => vec![("rate", Float(rate)), ("window", Float(window))],
+        MetricValue::Bytes((total, (rate, window))) => vec![
+            ("total", Integer(total)),
+            ("rate", Float(rate)),
+            ("window", Float(window)),
+        ],
+        MetricValue::Ratio((passed, total)) => vec![
+            ("passed", Integer(passed)),
+            ("total", Integer(total)),
+            ("rate", Float(ratio(passed, total))),
+        ],
+    }
+}
+
+/// Feed messages to `sink` until the run ends or the tracing layer is dropped.
+pub(crate) fn run(
+    mut sink: Box<dyn MetricSink>,
+    scenarios: &[(String, Vec<String>)],
+    mut rx: crate::Receiver<Message>,
+) {
+    sink.start(scenarios);
+    while let Some(message) = rx.blocking_recv() {
+        let end = matches!(message, Message::End);
+        sink.handle(&message);
+        if end {
+            break;
+        }
+    }
+    sink.finish();
+}
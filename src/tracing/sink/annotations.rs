@@ -0,0 +1,222 @@
+//! Sink marking scenario and stage boundaries on dashboards.
+//!
+//! An event is sent when a scenario starts or ends and when a ramping executor moves on to its
+//! next stage, either as a Grafana annotation, which shows up as a vertical marker on every
+//! panel of the dashboards it matches, or as JSON to any other webhook.
+//!
+//! ```no_run
+//! use rusher::tracing::sink::annotations::AnnotationSink;
+//!
+//! let sink = AnnotationSink::grafana("http://localhost:3000", "glsa_token").tags(["nightly"]);
+//! ```
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use super::MetricSink;
+use crate::tracing::message::Message;
+
+/// Tag added to every Grafana annotation.
+pub const TAG: &str = "rusher";
+
+enum Target {
+    Grafana { url: String, token: String },
+    Webhook { url: String },
+}
+
+/// Boundary of a run marked by [`AnnotationSink`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    ScenarioStart,
+    ScenarioEnd,
+    Stage,
+}
+
+impl Event {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Event::ScenarioStart => "scenario_start",
+            Event::ScenarioEnd => "scenario_end",
+            Event::Stage => "stage",
+        }
+    }
+}
+
+/// [`MetricSink`] posting an annotation at scenario and stage boundaries.
+pub struct AnnotationSink {
+    client: reqwest::Client,
+    target: Target,
+    tags: Vec<String>,
+    scenarios: Vec<(String, Vec<String>)>,
+    scenario: Option<usize>,
+    /// Current stage of every executor, keyed by scenario and executor.
+    stages: HashMap<(usize, usize), usize>,
+}
+
+impl AnnotationSink {
+    fn new(target: Target) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            target,
+            tags: vec![TAG.to_string()],
+            scenarios: Vec::new(),
+            scenario: None,
+            stages: HashMap::new(),
+        }
+    }
+
+    /// Post annotations to the Grafana server at `url`, such as `http://localhost:3000`,
+    /// authenticated with a service account `token`.
+    pub fn grafana(url: &str, token: impl Into<String>) -> Self {
+        Self::new(Target::Grafana {
+            url: format!("{}/api/annotations", url.trim_end_matches('/')),
+            token: token.into(),
+        })
+    }
+
+    /// Post every event to `url` as a JSON object with `event`, `time`, `scenario`,
+    /// `executor`, `stage` and `text` fields.
+    pub fn webhook(url: impl Into<String>) -> Self {
+        Self::new(Target::Webhook { url: url.into() })
+    }
+
+    /// Tags added to Grafana annotations along with [`TAG`], the event and the scenario,
+    /// which dashboards may filter annotations by.
+    pub fn tags(mut self, tags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    fn scenario_name(&self, scenario: usize) -> String {
+        self.scenarios
+            .get(scenario)
+            .map_or_else(|| scenario.to_string(), |x| x.0.clone())
+    }
+
+    fn executor_name(&self, scenario: usize, executor: usize) -> String {
+        self.scenarios
+            .get(scenario)
+            .and_then(|x| x.1.get(executor))
+            .map_or_else(|| executor.to_string(), Clone::clone)
+    }
+
+    /// Request body of an event.
+    fn body(
+        &self,
+        event: Event,
+        scenario: usize,
+        executor: Option<usize>,
+        stage: Option<(usize, Option<usize>)>,
+    ) -> serde_json::Value {
+        let now = Utc::now();
+        let scenario_name = self.scenario_name(scenario);
+        let executor_name = executor.map(|x| self.executor_name(scenario, x));
+        let text = match (event, &executor_name, stage) {
+            (Event::Stage, Some(executor), Some((stage, stages))) => {
+                let stages = stages.map_or(String::new(), |x| format!("/{x}"));
+                format!("{scenario_name} / {executor}: stage {}{stages}", stage + 1)
+            }
+            (Event::ScenarioEnd, ..) => format!("scenario {scenario_name} ended"),
+            _ => format!("scenario {scenario_name} started"),
+        };
+        match self.target {
+            Target::Grafana { .. } => {
+                let mut tags = self.tags.clone();
+                tags.extend([event.as_str().to_string(), scenario_name]);
+                serde_json::json!({
+                    "time": now.timestamp_millis(),
+                    "tags": tags,
+                    "text": text,
+                })
+            }
+            Target::Webhook { .. } => serde_json::json!({
+                "event": event.as_str(),
+                "time": now.to_rfc3339(),
+                "scenario": scenario_name,
+                "executor": executor_name,
+                "stage": stage.map(|(x, _)| x),
+                "text": text,
+            }),
+        }
+    }
+
+    fn send(
+        &self,
+        event: Event,
+        scenario: usize,
+        executor: Option<usize>,
+        stage: Option<(usize, Option<usize>)>,
+    ) {
+        let body = self.body(event, scenario, executor, stage).to_string();
+        let request = match &self.target {
+            Target::Grafana { url, token } => self
+                .client
+                .post(url.as_str())
+                .header("Authorization", format!("Bearer {token}")),
+            Target::Webhook { url } => self.client.post(url.as_str()),
+        };
+        let request = request
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send();
+        // Sinks run on a blocking thread of the runtime.
+        let _ = tokio::runtime::Handle::current().block_on(request);
+    }
+}
+
+impl MetricSink for AnnotationSink {
+    fn start(&mut self, scenarios: &[(String, Vec<String>)]) {
+        self.scenarios = scenarios.to_vec();
+    }
+
+    fn handle(&mut self, message: &Message) {
+        match message {
+            Message::ScenarioChanged { scenario_id } => {
+                if let Some(previous) = self.scenario.replace(*scenario_id) {
+                    self.send(Event::ScenarioEnd, previous, None, None);
+                }
+                self.send(Event::ScenarioStart, *scenario_id, None, None);
+            }
+            Message::ExecutorUpdate {
+                id,
+                stage: Some(stage),
+                stages,
+                ..
+            } => {
+                let Some(scenario) = self.scenario else {
+                    return;
+                };
+                if self.stages.insert((scenario, *id), *stage) != Some(*stage) {
+                    self.send(Event::Stage, scenario, Some(*id), Some((*stage, *stages)));
+                }
+            }
+            Message::End => {
+                if let Some(scenario) = self.scenario.take() {
+                    self.send(Event::ScenarioEnd, scenario, None, None);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn annotation_body() {
+        let mut sink = AnnotationSink::grafana("http://localhost:3000/", "token").tags(["ci"]);
+        sink.start(&[("checkout".to_string(), vec!["Ramping".to_string()])]);
+        let body = sink.body(Event::Stage, 0, Some(0), Some((1, Some(3))));
+        assert_eq!(body["text"], "checkout / Ramping: stage 2/3");
+        assert_eq!(
+            body["tags"],
+            serde_json::json!(["rusher", "ci", "stage", "checkout"])
+        );
+        assert!(
+            matches!(&sink.target, Target::Grafana { url, .. } if url == "http://localhost:3000/api/annotations")
+        );
+    }
+}
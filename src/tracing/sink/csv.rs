@@ -0,0 +1,182 @@
+//! Sink writing metrics as CSV rows.
+//!
+//! Current values of every metric are written once per flush interval, one row per value with the
+//! columns `time,scenario,executor,metric,attributes,value`. Metrics with several values such as
+//! histograms get a row for each, with the value appended to the metric name such as `latency.p95`.
+//! Attributes are joined as `name=value` pairs separated by `;`, and durations are in milliseconds.
+//!
+//! ```no_run
+//! use rusher::tracing::sink::csv::CsvSink;
+//!
+//! let sink = CsvSink::file("metrics.csv").unwrap();
+//! ```
+
+use std::{
+    collections::HashMap,
+    io::Write,
+    time::{Duration, Instant},
+};
+
+use chrono::{SecondsFormat, Utc};
+
+use super::{fields, MetricSink};
+use crate::tracing::{
+    message::Message,
+    task_event::{metrics::MetricValue, MetricSetKey},
+};
+
+const HEADER: &str = "time,scenario,executor,metric,attributes,value";
+
+/// [`MetricSink`] writing rows of CSV.
+pub struct CsvSink {
+    output: Box<dyn Write + Send>,
+    flush_interval: Duration,
+    last_flush: Instant,
+    scenario: usize,
+    /// Latest values of every executor, keyed by scenario and executor.
+    metrics: HashMap<(usize, usize), Vec<(MetricSetKey, MetricValue)>>,
+}
+
+impl CsvSink {
+    /// Write rows to a file at `path`, replacing its content.
+    pub fn file(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = std::fs::File::create(path)?;
+        Self::writer(std::io::BufWriter::new(file))
+    }
+
+    /// Write rows to any writer, starting with the header.
+    pub fn writer(mut output: impl Write + Send + 'static) -> std::io::Result<Self> {
+        writeln!(output, "{HEADER}")?;
+        Ok(Self {
+            output: Box::new(output),
+            flush_interval: Duration::from_secs(1),
+            last_flush: Instant::now(),
+            scenario: 0,
+            metrics: HashMap::new(),
+        })
+    }
+
+    /// How often rows are written, 1s by default.
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.last_flush = Instant::now();
+        let time = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, false);
+        let mut metrics: Vec<_> = std::mem::take(&mut self.metrics).into_iter().collect();
+        metrics.sort_unstable_by_key(|(ids, _)| *ids);
+        for ((scenario, executor), metrics) in metrics {
+            for (key, value) in metrics {
+                let attributes = key
+                    .attributes
+                    .iter()
+                    .map(|(name, value)| format!("{name}={value}"))
+                    .collect::<Vec<_>>()
+                    .join(";");
+                let fields = fields(&value);
+                for (field, value) in &fields {
+                    let metric = if fields.len() == 1 {
+                        key.name.to_string()
+                    } else {
+                        format!("{}.{field}", key.name)
+                    };
+                    writeln!(
+                        self.output,
+                        "{time},{scenario},{executor},{},{},{value}",
+                        escape(&metric),
+                        escape(&attributes)
+                    )?;
+                }
+            }
+        }
+        self.output.flush()
+    }
+}
+
+impl MetricSink for CsvSink {
+    fn handle(&mut self, message: &Message) {
+        match message {
+            Message::ScenarioChanged { scenario_id } => self.scenario = *scenario_id,
+            Message::ExecutorUpdate { id, metrics, .. } => {
+                self.metrics.insert((self.scenario, *id), metrics.clone());
+            }
+            _ => (),
+        }
+        if self.last_flush.elapsed() >= self.flush_interval {
+            let _ = self.flush();
+        }
+    }
+
+    fn finish(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Quote a cell if it contains a separator, quote or line break.
+fn escape(value: &str) -> std::borrow::Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\"")).into()
+    } else {
+        value.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::CsvSink;
+    use crate::tracing::{
+        message::Message,
+        sink::MetricSink,
+        task_event::{metrics::MetricType, metrics::MetricValue, MetricSetKey, Value},
+    };
+
+    #[test]
+    fn write_rows() {
+        let path = std::env::temp_dir().join(format!("rusher-csv-{}.csv", std::process::id()));
+        let mut sink = CsvSink::file(&path).unwrap().flush_interval(Duration::MAX);
+
+        let key = MetricSetKey {
+            name: "requests",
+            metric_type: MetricType::Rate,
+            attributes: vec![
+                ("route", Value::String("/a,b".to_string())),
+                ("method", Value::String("GET".to_string())),
+            ],
+        };
+        sink.handle(&Message::ScenarioChanged { scenario_id: 1 });
+        sink.handle(&Message::ExecutorUpdate {
+            id: 0,
+            users: 1,
+            max_users: 1,
+            total_iteration: None,
+            total_duration: None,
+            stage: None,
+            stage_duration: None,
+            stages: None,
+            metrics: vec![(key, MetricValue::Rate((2., 1.5)))],
+            window_metrics: vec![],
+            interval: Duration::ZERO,
+            deltas: vec![],
+        });
+        sink.finish();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<_> = content
+            .lines()
+            .map(|x| x.split_once(',').map_or(x, |(_, rest)| rest))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "scenario,executor,metric,attributes,value",
+                "1,0,requests.rate,\"route=/a,b;method=GET\",2",
+                "1,0,requests.window,\"route=/a,b;method=GET\",1.5",
+            ]
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Sink sending metrics to Graphite over the plaintext protocol.
+//!
+//! Current values of every metric are sent once per flush interval, each field of a metric under
+//! its own path built from the prefix, the index of scenario and executor, the metric name and its
+//! attributes, such as `rusher.0.1.http_req_duration.method.GET.p95`.
+//!
+//! ```no_run
+//! use rusher::tracing::sink::graphite::GraphiteSink;
+//!
+//! let sink = GraphiteSink::new("127.0.0.1:2003").unwrap();
+//! ```
+
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::Write as _,
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+
+use super::{fields, MetricSink};
+use crate::tracing::{
+    message::Message,
+    task_event::{metrics::MetricValue, MetricSetKey},
+};
+
+/// [`MetricSink`] for Graphite carbon receivers.
+#[derive(Debug)]
+pub struct GraphiteSink {
+    addr: Vec<SocketAddr>,
+    /// Connection to the receiver, opened again on the next flush after a failed write.
+    stream: Option<TcpStream>,
+    prefix: String,
+    flush_interval: Duration,
+    last_flush: Instant,
+    scenario: usize,
+    /// Latest values of every executor, keyed by scenario and executor.
+    metrics: HashMap<(usize, usize), Vec<(MetricSetKey, MetricValue)>>,
+}
+
+impl GraphiteSink {
+    /// Send metrics to the receiver listening at `addr`.
+    pub fn new(addr: impl ToSocketAddrs) -> std::io::Result<Self> {
+        let addr: Vec<_> = addr.to_socket_addrs()?.collect();
+        let stream = TcpStream::connect(&addr[..])?;
+        Ok(Self {
+            addr,
+            stream: Some(stream),
+            prefix: crate::CRATE_NAME.to_string(),
+            flush_interval: Duration::from_secs(1),
+            last_flush: Instant::now(),
+            scenario: 0,
+            metrics: HashMap::new(),
+        })
+    }
+
+    /// First component of every path, `rusher` by default. An empty prefix is left out.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// How often metrics are sent, 1s by default.
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    fn flush(&mut self) {
+        self.last_flush = Instant::now();
+        let timestamp = chrono::Utc::now().timestamp();
+        let mut body = String::new();
+        for ((scenario, executor), metrics) in std::mem::take(&mut self.metrics) {
+            for (key, value) in metrics {
+                let mut path = String::new();
+                if !self.prefix.is_empty() {
+                    path.push_str(&self.prefix);
+                    path.push('.');
+                }
+                let _ = write!(path, "{scenario}.{executor}.{}", sanitize(key.name));
+                for (name, value) in &key.attributes {
+                    let _ = write!(path, ".{}.{}", sanitize(name), sanitize(&value.to_string()));
+                }
+                let fields = fields(&value);
+                for (field, value) in &fields {
+                    // Metrics with a single value are sent under the metric path itself.
+                    if fields.len() == 1 {
+                        let _ = writeln!(body, "{path} {value} {timestamp}");
+                    } else {
+                        let _ = writeln!(body, "{path}.{field} {value} {timestamp}");
+                    }
+                }
+            }
+        }
+        if body.is_empty() {
+            return;
+        }
+
+        if self.stream.is_none() {
+            self.stream = TcpStream::connect(&self.addr[..]).ok();
+        }
+        if let Some(stream) = &mut self.stream {
+            if stream.write_all(body.as_bytes()).is_err() {
+                self.stream = None;
+            }
+        }
+    }
+}
+
+impl MetricSink for GraphiteSink {
+    fn handle(&mut self, message: &Message) {
+        match message {
+            Message::ScenarioChanged { scenario_id } => self.scenario = *scenario_id,
+            Message::ExecutorUpdate { id, metrics, .. } => {
+                self.metrics.insert((self.scenario, *id), metrics.clone());
+            }
+            _ => (),
+        }
+        if self.last_flush.elapsed() >= self.flush_interval {
+            self.flush();
+        }
+    }
+
+    fn finish(&mut self) {
+        self.flush();
+    }
+}
+
+/// Replace characters that separate or break up components of a path.
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|x| match x {
+            '.' | '/' | ':' => '_',
+            x if x.is_whitespace() => '_',
+            x => x,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Read, net::TcpListener, time::Duration};
+
+    use super::GraphiteSink;
+    use crate::tracing::{
+        message::Message,
+        sink::MetricSink,
+        task_event::{metrics::MetricType, metrics::MetricValue, MetricSetKey, Value},
+    };
+
+    #[test]
+    fn send_paths() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut sink = GraphiteSink::new(listener.local_addr().unwrap())
+            .unwrap()
+            .flush_interval(Duration::MAX);
+
+        let key = MetricSetKey {
+            name: "latency",
+            metric_type: MetricType::Histogram,
+            attributes: vec![("route", Value::String("/a".to_string()))],
+        };
+        sink.handle(&Message::ScenarioChanged { scenario_id: 1 });
+        sink.handle(&Message::ExecutorUpdate {
+            id: 0,
+            users: 1,
+            max_users: 1,
+            total_iteration: None,
+            total_duration: None,
+            stage: None,
+            stage_duration: None,
+            stages: None,
+            metrics: vec![(
+                key,
+                MetricValue::Histogram(((1., 2., 3., 4.), 10., (0.5, 5.))),
+            )],
+            window_metrics: vec![],
+            interval: Duration::ZERO,
+            deltas: vec![],
+        });
+        sink.finish();
+        drop(sink);
+
+        let mut content = String::new();
+        let (mut stream, _) = listener.accept().unwrap();
+        stream.read_to_string(&mut content).unwrap();
+        let lines: Vec<_> = content
+            .lines()
+            .map(|x| x.rsplit_once(' ').unwrap().0)
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "rusher.1.0.latency.route._a.p50 1",
+                "rusher.1.0.latency.route._a.p90 2",
+                "rusher.1.0.latency.route._a.p95 3",
+                "rusher.1.0.latency.route._a.p99 4",
+                "rusher.1.0.latency.route._a.sum 10",
+                "rusher.1.0.latency.route._a.min 0.5",
+                "rusher.1.0.latency.route._a.max 5",
+            ]
+        );
+    }
+}
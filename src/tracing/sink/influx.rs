@@ -0,0 +1,279 @@
+//! Sink writing metrics in InfluxDB line protocol.
+//!
+//! Each metric becomes a measurement of the same name, tagged with `run_id`, `scenario`, `executor`
+//! and its attributes. Current values of every metric are written once per flush interval, while
+//! the duration of every iteration is written as it happens to the `iteration_duration` measurement.
+//!
+//! Points go either to a file, or with the `reqwest` feature to the write API of an InfluxDB v2 server.
+//!
+//! ```no_run
+//! use rusher::tracing::sink::influx::InfluxSink;
+//!
+//! let sink = InfluxSink::file("metrics.lp").unwrap().run_id("nightly");
+//! ```
+
+use std::{
+    fmt::Write as _,
+    io::Write as _,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+
+use super::{fields, millis, Field, MetricSink};
+use crate::tracing::{
+    message::Message,
+    task_event::{metrics::MetricValue, MetricSetKey},
+};
+
+/// Measurement of the duration of every iteration.
+pub const ITERATION_DURATION: &str = "iteration_duration";
+
+enum Output {
+    File(std::io::BufWriter<std::fs::File>),
+    #[cfg(feature = "reqwest")]
+    Http {
+        client: reqwest::Client,
+        url: String,
+        token: String,
+    },
+}
+
+/// [`MetricSink`] writing InfluxDB line protocol.
+pub struct InfluxSink {
+    output: Output,
+    run_id: String,
+    flush_interval: Duration,
+    last_flush: Instant,
+    scenario: usize,
+    /// Latest values of every executor, keyed by scenario and executor.
+    metrics: std::collections::HashMap<(usize, usize), Vec<(MetricSetKey, MetricValue)>>,
+    buffer: String,
+}
+
+impl InfluxSink {
+    fn new(output: Output) -> Self {
+        Self {
+            output,
+            run_id: Utc::now().format("%Y%m%dT%H%M%S").to_string(),
+            flush_interval: Duration::from_secs(1),
+            last_flush: Instant::now(),
+            scenario: 0,
+            metrics: Default::default(),
+            buffer: String::new(),
+        }
+    }
+
+    /// Write points to a file at `path`, replacing its content.
+    pub fn file(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = std::fs::File::create(path)?;
+        Ok(Self::new(Output::File(std::io::BufWriter::new(file))))
+    }
+
+    /// Send points to the write API of the InfluxDB v2 server at `url`, such as `http://localhost:8086`.
+    #[cfg(feature = "reqwest")]
+    pub fn http(url: &str, org: &str, bucket: &str, token: impl Into<String>) -> Self {
+        let url = format!(
+            "{}/api/v2/write?org={org}&bucket={bucket}&precision=ns",
+            url.trim_end_matches('/')
+        );
+        Self::new(Output::Http {
+            client: reqwest::Client::new(),
+            url,
+            token: token.into(),
+        })
+    }
+
+    /// Value of the `run_id` tag, which tells points of separate runs apart.
+    /// Defaults to the time the sink was created, such as `20240601T120000`.
+    pub fn run_id(mut self, run_id: impl Into<String>) -> Self {
+        self.run_id = run_id.into();
+        self
+    }
+
+    /// How often current values are written, 1s by default.
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// Append a point to the buffer.
+    fn point(
+        &mut self,
+        (scenario, executor): (usize, usize),
+        key: Option<&MetricSetKey>,
+        measurement: &str,
+        fields: &[(&str, Field)],
+        timestamp: i64,
+    ) {
+        if fields.is_empty() {
+            return;
+        }
+        let buffer = &mut self.buffer;
+        let _ = write!(
+            buffer,
+            "{},run_id={},scenario={scenario},executor={executor}",
+            escape(measurement),
+            escape(&self.run_id)
+        );
+        for (name, value) in key.map_or(&[][..], |key| &key.attributes[..]) {
+            let _ = write!(buffer, ",{}={}", escape(name), escape(&value.to_string()));
+        }
+        for (index, (name, value)) in fields.iter().enumerate() {
+            let separator = if index == 0 { ' ' } else { ',' };
+            let _ = write!(
+                buffer,
+                "{separator}{}={}",
+                escape(name),
+                field_value(*value)
+            );
+        }
+        let _ = writeln!(buffer, " {timestamp}");
+    }
+
+    fn flush(&mut self) {
+        self.last_flush = Instant::now();
+        let timestamp = now();
+        for (ids, metrics) in std::mem::take(&mut self.metrics) {
+            for (key, value) in &metrics {
+                self.point(ids, Some(key), key.name, &fields(value), timestamp);
+            }
+        }
+        if self.buffer.is_empty() {
+            return;
+        }
+
+        let body = std::mem::take(&mut self.buffer);
+        match &mut self.output {
+            Output::File(file) => {
+                let _ = file.write_all(body.as_bytes()).and_then(|_| file.flush());
+            }
+            #[cfg(feature = "reqwest")]
+            Output::Http { client, url, token } => {
+                // Sinks run on a blocking thread of the runtime.
+                let request = client
+                    .post(url.as_str())
+                    .header("Authorization", format!("Token {token}"))
+                    .body(body)
+                    .send();
+                let _ = tokio::runtime::Handle::current().block_on(request);
+            }
+        }
+    }
+}
+
+impl MetricSink for InfluxSink {
+    fn handle(&mut self, message: &Message) {
+        match message {
+            Message::ScenarioChanged { scenario_id } => self.scenario = *scenario_id,
+            Message::TaskTime {
+                scenario_id,
+                execution_id,
+                duration,
+            } => self.point(
+                (*scenario_id, *execution_id),
+                None,
+                ITERATION_DURATION,
+                &[("value", Field::Float(millis(*duration)))],
+                now(),
+            ),
+            Message::ExecutorUpdate { id, metrics, .. } => {
+                self.metrics.insert((self.scenario, *id), metrics.clone());
+            }
+            _ => (),
+        }
+        if self.last_flush.elapsed() >= self.flush_interval {
+            self.flush();
+        }
+    }
+
+    fn finish(&mut self) {
+        self.flush();
+    }
+}
+
+/// Field value with the suffix marking integers.
+fn field_value(field: Field) -> String {
+    match field {
+        Field::Integer(x) => format!("{x}i"),
+        x => x.to_string(),
+    }
+}
+
+fn now() -> i64 {
+    Utc::now().timestamp_nanos_opt().unwrap_or_default()
+}
+
+/// Escape commas, equal signs and spaces of measurements, tag keys and tag values.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for x in value.chars() {
+        if matches!(x, ',' | '=' | ' ') {
+            escaped.push('\\');
+        }
+        escaped.push(x);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::InfluxSink;
+    use crate::tracing::{
+        message::Message,
+        sink::MetricSink,
+        task_event::{metrics::MetricType, metrics::MetricValue, MetricSetKey, Value},
+    };
+
+    #[test]
+    fn write_points() {
+        let path = std::env::temp_dir().join(format!("rusher-influx-{}.lp", std::process::id()));
+        let mut sink = InfluxSink::file(&path)
+            .unwrap()
+            .run_id("test run")
+            .flush_interval(Duration::MAX);
+
+        let key = MetricSetKey {
+            name: "http_status",
+            metric_type: MetricType::Counter,
+            attributes: vec![("route", Value::String("/a b".to_string()))],
+        };
+        sink.handle(&Message::ScenarioChanged { scenario_id: 1 });
+        sink.handle(&Message::ExecutorUpdate {
+            id: 0,
+            users: 1,
+            max_users: 1,
+            total_iteration: None,
+            total_duration: None,
+            stage: None,
+            stage_duration: None,
+            stages: None,
+            metrics: vec![(key, MetricValue::Counter(3))],
+            window_metrics: vec![],
+            interval: Duration::ZERO,
+            deltas: vec![],
+        });
+        sink.handle(&Message::TaskTime {
+            execution_id: 0,
+            scenario_id: 1,
+            duration: Duration::from_millis(5),
+        });
+        sink.finish();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<_> = content
+            .lines()
+            .map(|x| x.rsplit_once(' ').unwrap().0)
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "iteration_duration,run_id=test\\ run,scenario=1,executor=0 value=5",
+                "http_status,run_id=test\\ run,scenario=1,executor=0,route=/a\\ b value=3i",
+            ]
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Sink appending a JSON line for every executor update, to follow a run with `tail -f` or
+//! feed a custom live dashboard without the `web` feature.
+//!
+//! Each line holds the content of a [`Message::ExecutorUpdate`] along with the time and the
+//! names of the scenario and executor. Every metric is an object with its name, attributes and
+//! the fields of its value, durations are in milliseconds.
+//!
+//! ```json
+//! {"execution_id":0,"executor":"Once","max_users":1,"metrics":[{"attributes":{"result":"ok"},"name":"iterations_total","value":1}],"scenario":"checkout","scenario_id":0,"time":"2024-06-01T12:00:00.000+00:00","users":1,"window":[]}
+//! ```
+
+use std::io::Write;
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Map, Value};
+
+use super::{fields, millis, Field, MetricSink};
+use crate::tracing::{
+    message::Message,
+    task_event::{metrics::MetricValue, MetricSetKey},
+};
+
+/// [`MetricSink`] appending executor updates as newline delimited JSON.
+pub struct LiveSink {
+    output: Box<dyn Write + Send>,
+    scenarios: Vec<(String, Vec<String>)>,
+    scenario: usize,
+}
+
+impl LiveSink {
+    /// Append records to the file at `path`, which is created if missing.
+    pub fn file(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        Ok(Self::writer(std::io::BufWriter::new(file)))
+    }
+
+    /// Write records to any writer.
+    pub fn writer(output: impl Write + Send + 'static) -> Self {
+        Self {
+            output: Box::new(output),
+            scenarios: Vec::new(),
+            scenario: 0,
+        }
+    }
+
+    fn write(&mut self, record: &Value) -> std::io::Result<()> {
+        serde_json::to_writer(&mut self.output, record)?;
+        self.output.write_all(b"\n")?;
+        // Readers follow the file as it grows.
+        self.output.flush()
+    }
+}
+
+impl MetricSink for LiveSink {
+    fn start(&mut self, scenarios: &[(String, Vec<String>)]) {
+        self.scenarios = scenarios.to_vec();
+    }
+
+    fn handle(&mut self, message: &Message) {
+        match message {
+            Message::ScenarioChanged { scenario_id } => self.scenario = *scenario_id,
+            Message::ExecutorUpdate {
+                id,
+                users,
+                max_users,
+                total_iteration,
+                total_duration,
+                stage,
+                stage_duration,
+                stages,
+                metrics,
+                window_metrics,
+                ..
+            } => {
+                let names = self.scenarios.get(self.scenario);
+                let mut record = json!({
+                    "time": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, false),
+                    "scenario": names.map(|x| &x.0),
+                    "executor": names.and_then(|x| x.1.get(*id)),
+                    "scenario_id": self.scenario,
+                    "execution_id": id,
+                    "users": users,
+                    "max_users": max_users,
+                });
+                let optional = [
+                    ("total_iterations", total_iteration.map(Value::from)),
+                    (
+                        "total_duration_ms",
+                        total_duration.map(|x| millis(x).into()),
+                    ),
+                    ("stage", stage.map(Value::from)),
+                    (
+                        "stage_duration_ms",
+                        stage_duration.map(|x| millis(x).into()),
+                    ),
+                    ("stages", stages.map(Value::from)),
+                ];
+                for (name, value) in optional {
+                    if let Some(value) = value {
+                        record[name] = value;
+                    }
+                }
+                record["metrics"] = metrics.iter().map(metric).collect();
+                if !window_metrics.is_empty() {
+                    record["window"] = window_metrics.iter().map(metric).collect();
+                }
+                let _ = self.write(&record);
+            }
+            _ => (),
+        }
+    }
+
+    fn finish(&mut self) {
+        let _ = self.output.flush();
+    }
+}
+
+fn metric((key, value): &(MetricSetKey, MetricValue)) -> Value {
+    let attributes: Map<_, _> = key
+        .attributes
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string().into()))
+        .collect();
+    let mut metric = json!({"name": key.name, "attributes": attributes});
+    for (name, field) in fields(value) {
+        metric[name] = match field {
+            Field::Float(x) => x.into(),
+            Field::Integer(x) => x.into(),
+        };
+    }
+    metric
+}
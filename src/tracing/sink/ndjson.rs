@@ -0,0 +1,60 @@
+//! Sink writing a JSON line for every iteration.
+//!
+//! Each line is an [`IterationRecord`] with the start time, scenario and executor, duration in
+//! milliseconds, result, error and the groups completed within the iteration, for analysis of
+//! single iterations with tools such as pandas or DuckDB.
+//!
+//! ```json
+//! {"time":"2024-06-01T12:00:00.000+00:00","scenario_id":0,"execution_id":1,"duration_ms":12.5,"result":"ok","groups":[{"name":"login","duration_ms":4.2}]}
+//! ```
+
+use std::io::Write;
+
+use super::MetricSink;
+use crate::tracing::message::{IterationRecord, Message};
+
+/// [`MetricSink`] writing [`IterationRecord`]s as newline delimited JSON.
+pub struct NdjsonSink {
+    output: Box<dyn Write + Send>,
+}
+
+impl NdjsonSink {
+    /// Write records to a file at `path`, replacing its content.
+    pub fn file(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = std::fs::File::create(path)?;
+        Ok(Self::writer(std::io::BufWriter::new(file)))
+    }
+
+    /// Write records to stdout, which should not be combined with the terminal UI.
+    pub fn stdout() -> Self {
+        Self::writer(std::io::BufWriter::new(std::io::stdout()))
+    }
+
+    /// Write records to any writer.
+    pub fn writer(output: impl Write + Send + 'static) -> Self {
+        Self {
+            output: Box::new(output),
+        }
+    }
+
+    fn write(&mut self, record: &IterationRecord) -> std::io::Result<()> {
+        serde_json::to_writer(&mut self.output, record)?;
+        self.output.write_all(b"\n")
+    }
+}
+
+impl MetricSink for NdjsonSink {
+    fn handle(&mut self, message: &Message) {
+        if let Message::Iteration(record) = message {
+            let _ = self.write(record);
+        }
+    }
+
+    fn finish(&mut self) {
+        let _ = self.output.flush();
+    }
+
+    fn iterations(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,176 @@
+//! Sink pushing the messages of a run to a remote collector over a WebSocket, so that workers
+//! behind NAT or firewalls feed a shared live view without any inbound port.
+//!
+//! The sink connects out to the collector and sends every message as a JSON text frame, in the
+//! same format as the `/ws` endpoint of the web server, leaving out the timing of every
+//! iteration. The first frame after connecting names the worker and every scenario and executor,
+//! which the ids of later messages index:
+//!
+//! ```json
+//! {"Hello":{"worker":"eu-west-1","scenarios":[["checkout",["Once"]]]}}
+//! ```
+//!
+//! When the connection drops, it is opened again after a delay growing up to 30s and the hello
+//! is followed by the messages that started the current scenario and its executors. Messages
+//! sent meanwhile are lost, updates of executors carry cumulative metrics so the view catches up
+//! with the next one.
+//!
+//! ```no_run
+//! use rusher::tracing::sink::push::PushSink;
+//!
+//! let sink = PushSink::new("wss://dashboard.example.com/ingest")
+//!     .worker("eu-west-1")
+//!     .header("Authorization", "Bearer token");
+//! ```
+
+use std::{
+    error::Error,
+    net::TcpStream,
+    time::{Duration, Instant},
+};
+
+use serde_json::json;
+use tungstenite::{
+    client::IntoClientRequest,
+    http::{HeaderName, HeaderValue},
+    stream::MaybeTlsStream,
+    WebSocket,
+};
+
+use super::MetricSink;
+use crate::tracing::message::Message;
+
+/// Longest wait between two attempts to connect.
+const MAX_RETRY: Duration = Duration::from_secs(30);
+/// Time a frame may take to be written before the connection is considered lost.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// [`MetricSink`] sending messages to a WebSocket collector.
+pub struct PushSink {
+    url: String,
+    headers: Vec<(String, String)>,
+    worker: Option<String>,
+    socket: Option<WebSocket<MaybeTlsStream<TcpStream>>>,
+    /// Delay before the next attempt to connect, doubled after every failure.
+    retry: Duration,
+    retry_at: Option<Instant>,
+    scenarios: Vec<(String, Vec<String>)>,
+    /// Messages that started the current scenario and its executors, sent again on reconnect.
+    replay: Vec<Message>,
+}
+
+impl PushSink {
+    /// Push messages to the collector at `url`, a `ws://` or `wss://` address.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            headers: Vec::new(),
+            worker: None,
+            socket: None,
+            retry: Duration::from_secs(1),
+            retry_at: None,
+            scenarios: Vec::new(),
+            replay: Vec::new(),
+        }
+    }
+
+    /// Name of this worker sent in the hello, to tell workers apart on the collector.
+    pub fn worker(mut self, name: impl Into<String>) -> Self {
+        self.worker = Some(name.into());
+        self
+    }
+
+    /// Header added to the handshake, such as an authorization token.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    fn connect(&self) -> Result<WebSocket<MaybeTlsStream<TcpStream>>, Box<dyn Error>> {
+        let mut request = self.url.as_str().into_client_request()?;
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())?;
+            request
+                .headers_mut()
+                .append(name, HeaderValue::from_str(value)?);
+        }
+        let (mut socket, _) = tungstenite::connect(request)?;
+        let stream = match socket.get_mut() {
+            MaybeTlsStream::Plain(stream) => Some(&*stream),
+            MaybeTlsStream::Rustls(stream) => Some(stream.get_ref()),
+            _ => None,
+        };
+        if let Some(stream) = stream {
+            stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        }
+        let hello = json!({
+            "Hello": {
+                "worker": self.worker,
+                "scenarios": self.scenarios,
+            }
+        });
+        socket.send(tungstenite::Message::Text(hello.to_string()))?;
+        for message in &self.replay {
+            socket.send(frame(message))?;
+        }
+        Ok(socket)
+    }
+
+    /// Connection to the collector, opened again once the delay after a failure is over.
+    fn socket(&mut self) -> Option<&mut WebSocket<MaybeTlsStream<TcpStream>>> {
+        if self.socket.is_none() && self.retry_at.is_none_or(|x| x <= Instant::now()) {
+            match self.connect() {
+                Ok(socket) => {
+                    self.socket = Some(socket);
+                    self.retry = Duration::from_secs(1);
+                    self.retry_at = None;
+                }
+                Err(_) => {
+                    self.retry_at = Some(Instant::now() + self.retry);
+                    self.retry = (self.retry * 2).min(MAX_RETRY);
+                }
+            }
+        }
+        self.socket.as_mut()
+    }
+}
+
+fn frame(message: &Message) -> tungstenite::Message {
+    tungstenite::Message::Text(serde_json::to_string(message).unwrap_or_default())
+}
+
+impl MetricSink for PushSink {
+    fn start(&mut self, scenarios: &[(String, Vec<String>)]) {
+        self.scenarios = scenarios.to_vec();
+        self.socket();
+    }
+
+    fn handle(&mut self, message: &Message) {
+        match message {
+            // Sent for every iteration, too many to push.
+            Message::TaskTime { .. } | Message::Iteration(_) => return,
+            Message::ScenarioChanged { .. } => self.replay = vec![message.clone()],
+            Message::ExecutorStart { .. } => self.replay.push(message.clone()),
+            _ => (),
+        }
+        let connected = self.socket.is_some();
+        let replayed = matches!(
+            message,
+            Message::ScenarioChanged { .. } | Message::ExecutorStart { .. }
+        );
+        let Some(socket) = self.socket() else {
+            return;
+        };
+        // A new connection already sent the message along with the replay.
+        if (connected || !replayed) && socket.send(frame(message)).is_err() {
+            self.socket = None;
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(mut socket) = self.socket.take() {
+            let _ = socket.close(None);
+            let _ = socket.flush();
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! Sink sending metrics to a StatsD agent over UDP.
+//!
+//! Counters are sent as the amount they grew by since the previous flush, and gauges, rates and
+//! percentiles of histograms over the last [`WINDOW`](crate::tracing::task_event::metrics::WINDOW)
+//! as gauges. The duration of every iteration is sent as a `iteration_duration` timing.
+//!
+//! Metrics are tagged with the index of their scenario and executor, along with their attributes.
+//! Agents that support the DogStatsD extension receive them as tags, others as components of the name
+//! such as `rusher.0.1.http_status.code.200`.
+//!
+//! ```no_run
+//! use rusher::tracing::sink::statsd::StatsdSink;
+//!
+//! let sink = StatsdSink::new("127.0.0.1:8125").unwrap().dogstatsd(true);
+//! ```
+
+use std::{
+    collections::HashMap,
+    net::{ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use super::{millis, MetricSink};
+use crate::tracing::{
+    message::Message,
+    task_event::{
+        metrics::{ratio, MetricType, MetricValue},
+        MetricSetKey,
+    },
+};
+
+/// Largest datagram sent, small enough to not be fragmented on common networks.
+const MAX_DATAGRAM: usize = 1432;
+
+/// Name of the timing sent for every iteration.
+pub const ITERATION_DURATION: &str = "iteration_duration";
+
+/// [`MetricSink`] for StatsD and DogStatsD agents.
+#[derive(Debug)]
+pub struct StatsdSink {
+    socket: UdpSocket,
+    prefix: String,
+    tags: bool,
+    flush_interval: Duration,
+    last_flush: Instant,
+    scenario: usize,
+    /// Growth of counters since the previous flush, keyed by scenario, executor and metric.
+    counters: HashMap<(usize, usize, MetricSetKey), u64>,
+    /// Latest window value of every metric.
+    gauges: HashMap<(usize, usize, MetricSetKey), MetricValue>,
+    lines: Vec<String>,
+}
+
+impl StatsdSink {
+    /// Send metrics to the agent listening at `addr`.
+    pub fn new(addr: impl ToSocketAddrs) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.connect(addr)?;
+        Ok(Self {
+            socket,
+            prefix: crate::CRATE_NAME.to_string(),
+            tags: false,
+            flush_interval: Duration::from_secs(1),
+            last_flush: Instant::now(),
+            scenario: 0,
+            counters: HashMap::new(),
+            gauges: HashMap::new(),
+            lines: Vec::new(),
+        })
+    }
+
+    /// Prefix of every metric name, `rusher` by default. An empty prefix leaves names as they are.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Send scenario, executor and attributes as DogStatsD tags instead of in the metric name.
+    pub fn dogstatsd(mut self, enable: bool) -> Self {
+        self.tags = enable;
+        self
+    }
+
+    /// How often counters and gauges are sent, 1s by default.
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// Format a single line of `name` with the given value and type.
+    fn line(
+        &self,
+        (scenario, executor): (usize, usize),
+        name: &str,
+        key: Option<&MetricSetKey>,
+        value: impl std::fmt::Display,
+        ty: &str,
+    ) -> String {
+        let attributes = key.map_or(&[][..], |key| &key.attributes[..]);
+        let mut line = String::new();
+        if !self.prefix.is_empty() {
+            line.push_str(&self.prefix);
+            line.push('.');
+        }
+        if self.tags {
+            line.push_str(&sanitize(name));
+            line.push_str(&format!(
+                ":{value}|{ty}|#scenario:{scenario},executor:{executor}"
+            ));
+            for (key, value) in attributes {
+                line.push_str(&format!(
+                    ",{}:{}",
+                    sanitize(key),
+                    sanitize(&value.to_string())
+                ));
+            }
+        } else {
+            line.push_str(&format!("{scenario}.{executor}.{}", sanitize(name)));
+            for (key, value) in attributes {
+                line.push_str(&format!(
+                    ".{}.{}",
+                    sanitize(key),
+                    sanitize(&value.to_string())
+                ));
+            }
+            line.push_str(&format!(":{value}|{ty}"));
+        }
+        line
+    }
+
+    fn flush(&mut self) {
+        self.last_flush = Instant::now();
+        let mut lines = std::mem::take(&mut self.lines);
+
+        let counters: Vec<_> = self.counters.drain().collect();
+        for ((scenario, executor, key), delta) in counters {
+            let name = match key.metric_type {
+                MetricType::Counter | MetricType::Rate | MetricType::Bytes => key.name.to_string(),
+                _ => format!("{}.count", key.name),
+            };
+            lines.push(self.line((scenario, executor), &name, Some(&key), delta, "c"));
+        }
+
+        for ((scenario, executor, key), value) in &self.gauges {
+            for (suffix, value) in gauge_values(value) {
+                let name = match suffix {
+                    Some(suffix) => format!("{}.{suffix}", key.name),
+                    None => key.name.to_string(),
+                };
+                lines.push(self.line((*scenario, *executor), &name, Some(key), value, "g"));
+            }
+        }
+        self.gauges.clear();
+
+        let mut datagram = String::new();
+        for line in lines {
+            if !datagram.is_empty() && datagram.len() + line.len() + 1 > MAX_DATAGRAM {
+                let _ = self.socket.send(datagram.as_bytes());
+                datagram.clear();
+            }
+            if !datagram.is_empty() {
+                datagram.push('\n');
+            }
+            datagram.push_str(&line);
+        }
+        if !datagram.is_empty() {
+            let _ = self.socket.send(datagram.as_bytes());
+        }
+    }
+}
+
+impl MetricSink for StatsdSink {
+    fn handle(&mut self, message: &Message) {
+        match message {
+            Message::ScenarioChanged { scenario_id } => self.scenario = *scenario_id,
+            Message::TaskTime {
+                scenario_id,
+                execution_id,
+                duration,
+            } => {
+                let line = self.line(
+                    (*scenario_id, *execution_id),
+                    ITERATION_DURATION,
+                    None,
+                    millis(*duration),
+                    "ms",
+                );
+                self.lines.push(line);
+            }
+            Message::ExecutorUpdate {
+                id,
+                window_metrics,
+                deltas,
+                ..
+            } => {
+                for (key, delta) in deltas {
+                    *self
+                        .counters
+                        .entry((self.scenario, *id, key.clone()))
+                        .or_default() += delta;
+                }
+                for (key, value) in window_metrics {
+                    self.gauges
+                        .insert((self.scenario, *id, key.clone()), *value);
+                }
+            }
+            _ => (),
+        }
+        if self.last_flush.elapsed() >= self.flush_interval {
+            self.flush();
+        }
+    }
+
+    fn finish(&mut self) {
+        self.flush();
+    }
+}
+
+/// Values of a metric sent as gauges, with the suffix appended to its name.
+fn gauge_values(value: &MetricValue) -> Vec<(Option<&'static str>, f64)> {
+    const PERCENTILES: [&str; 4] = ["p50", "p90", "p95", "p99"];
+    match *value {
+        MetricValue::Counter(_) => vec![],
+        MetricValue::GaugeF64(x) => vec![(None, x)],
+        MetricValue::GaugeI64(x) => vec![(None, x as f64)],
+        MetricValue::GaugeU64(x) => vec![(None, x as f64)],
+        MetricValue::GaugeDuration(x) => vec![(None, millis(x))],
+        MetricValue::Histogram(((p50, p90, p95, p99), ..)) => PERCENTILES
+            .map(Some)
+            .into_iter()
+            .zip([p50, p90, p95, p99])
+            .collect(),
+        MetricValue::DurationHistogram(((p50, p90, p95, p99), ..)) => PERCENTILES
+            .map(Some)
+            .into_iter()
+            .zip([p50, p90, p95, p99].map(millis))
+            .collect(),
+        MetricValue::Trend(x) => {
+            let (p50, p90, p95, p99) = x.percentiles;
+            let mut values = vec![(Some("mean"), x.mean)];
+            values.extend(PERCENTILES.map(Some).into_iter().zip([p50, p90, p95, p99]));
+            values
+        }
+        MetricValue::DurationTrend(x) => {
+            let (p50, p90, p95, p99) = x.percentiles;
+            let mut values = vec![(Some("mean"), millis(x.mean))];
+            values.extend(
+                PERCENTILES
+                    .map(Some)
+                    .into_iter()
+                    .zip([p50, p90, p95, p99].map(millis)),
+            );
+            values
+        }
+        MetricValue::Rate((_, window)) | MetricValue::Bytes((_, (_, window))) => {
+            vec![(Some("rate"), window)]
+        }
+        MetricValue::Ratio((passed, total)) => vec![(None, ratio(passed, total))],
+    }
+}
+
+/// Replace characters with a meaning in the StatsD line protocol.
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|x| match x {
+            ':' | '|' | '@' | '#' | ',' | '.' | '/' => '_',
+            x if x.is_whitespace() => '_',
+            x => x,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::UdpSocket, time::Duration};
+
+    use super::StatsdSink;
+    use crate::tracing::{
+        message::Message,
+        sink::MetricSink,
+        task_event::{metrics::MetricType, metrics::MetricValue, MetricSetKey, Value},
+    };
+
+    #[test]
+    fn send_lines() {
+        let agent = UdpSocket::bind("127.0.0.1:0").unwrap();
+        agent
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut sink = StatsdSink::new(agent.local_addr().unwrap())
+            .unwrap()
+            .dogstatsd(true)
+            .flush_interval(Duration::MAX);
+
+        let key = MetricSetKey {
+            name: "http_status",
+            metric_type: MetricType::Counter,
+            attributes: vec![("code", Value::UnsignedNumber(200))],
+        };
+        sink.handle(&Message::ScenarioChanged { scenario_id: 1 });
+        for _ in 0..2 {
+            sink.handle(&Message::ExecutorUpdate {
+                id: 0,
+                users: 1,
+                max_users: 1,
+                total_iteration: None,
+                total_duration: None,
+                stage: None,
+                stage_duration: None,
+                stages: None,
+                metrics: vec![],
+                window_metrics: vec![(key.clone(), MetricValue::Counter(1))],
+                interval: Duration::ZERO,
+                deltas: vec![(key.clone(), 3)],
+            });
+        }
+        sink.handle(&Message::TaskTime {
+            execution_id: 0,
+            scenario_id: 1,
+            duration: Duration::from_millis(5),
+        });
+        sink.finish();
+
+        let mut buf = [0; 1500];
+        let len = agent.recv(&mut buf).unwrap();
+        let mut lines: Vec<_> = std::str::from_utf8(&buf[..len]).unwrap().lines().collect();
+        lines.sort();
+        assert_eq!(
+            lines,
+            [
+                "rusher.http_status:6|c|#scenario:1,executor:0,code:200",
+                "rusher.iteration_duration:5|ms|#scenario:1,executor:0",
+            ]
+        );
+    }
+}
@@ -1,5 +1,13 @@
-use std::time::{Duration, Instant};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
 
+use chrono::{DateTime, Utc};
 use metrics::MetricType;
 use ordered_float::OrderedFloat;
 use tracing::span::Id;
@@ -17,8 +25,7 @@ use tracing::span::Id;
 /// For any events within a span, attributes and name of the span are added as extra label for grouping the events.
 ///
 /// Each span inherits all the attributes of the parent and each span is tracked for its duration and tracked in a histogram.
-
-/// Type to capture arbritary spans
+/// An attribute set on an inner span replaces the one of the same name inherited from a parent.
 pub mod metrics;
 
 pub type Attribute = (&'static str, Value);
@@ -32,29 +39,395 @@ pub struct MetricSetKey {
     pub attributes: Vec<Attribute>,
 }
 
-#[derive(Debug, Default)]
+/// Attribute value of the series that collects attribute sets over the cardinality limit.
+pub const OVERFLOW_ATTRIBUTE: &str = "_other";
+
+/// Limits applied to metrics collected from user tasks.
+#[derive(Debug, Clone, Copy)]
+pub struct MetricsConfig {
+    cardinality_limit: usize,
+    history_resolution: Duration,
+    history_retention: Duration,
+    history_budget: Option<usize>,
+    digest: metrics::DigestConfig,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            cardinality_limit: 1000,
+            history_resolution: Duration::from_secs(1),
+            history_retention: Duration::from_secs(60 * 60),
+            history_budget: None,
+            digest: metrics::DigestConfig::default(),
+        }
+    }
+}
+
+impl MetricsConfig {
+    /// Maximum number of distinct attribute sets tracked per metric name and type, 1000 by default.
+    /// Events with new attribute sets past the limit are recorded in a single series
+    /// with every attribute set to [`OVERFLOW_ATTRIBUTE`].
+    pub fn cardinality_limit(mut self, limit: usize) -> Self {
+        self.cardinality_limit = limit;
+        self
+    }
+
+    /// Interval between samples of metric history, 1s by default.
+    pub fn history_resolution(mut self, resolution: Duration) -> Self {
+        self.history_resolution = resolution;
+        self
+    }
+
+    /// How long samples of metric history are kept, 1h by default.
+    /// [`Duration::ZERO`] disables history.
+    pub fn history_retention(mut self, retention: Duration) -> Self {
+        self.history_retention = retention;
+        self
+    }
+
+    /// Approximate memory in bytes the history samples of a metric set may use, unbounded by
+    /// default. Once over budget, older samples are downsampled by dropping every other one of
+    /// them, so long runs keep their full span at a coarser resolution while recent samples stay
+    /// intact. Only the history is bounded, the memory of the metrics themselves follows from
+    /// [`cardinality_limit`](Self::cardinality_limit), [`digest_buffer_size`](Self::digest_buffer_size)
+    /// and [`digest_compression`](Self::digest_compression).
+    pub fn history_budget(mut self, bytes: usize) -> Self {
+        self.history_budget = Some(bytes);
+        self
+    }
+
+    /// Number of raw values a histogram buffers before folding them into its digest, 4096 by default.
+    /// Percentiles are exact until the buffer is first folded.
+    pub fn digest_buffer_size(mut self, size: usize) -> Self {
+        self.digest.buffer_size = size.max(1);
+        self
+    }
+
+    /// Maximum number of centroids kept by histogram digests, 100 by default.
+    /// Higher values improve accuracy of tail percentiles at the cost of memory.
+    pub fn digest_compression(mut self, compression: usize) -> Self {
+        self.digest.compression = compression.max(1);
+        self
+    }
+}
+
+/// Value of a metric at a point in time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Sample {
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "super::message::serialize_to_rfc3339_opts")
+    )]
+    pub time: DateTime<Utc>,
+    pub value: metrics::MetricValue,
+}
+
+#[derive(Debug)]
 pub struct MetricSet {
     inner: dashmap::DashMap<MetricSetKey, metrics::Metric>,
+    /// Number of series tracked for each metric name and type.
+    series: dashmap::DashMap<(&'static str, MetricType), usize>,
+    config: MetricsConfig,
+    start: Instant,
+    /// Nanos since `start` at which the next history sample is due.
+    next_sample: AtomicU64,
+    history: Mutex<HashMap<MetricSetKey, VecDeque<Sample>>>,
+}
+
+impl Default for MetricSet {
+    fn default() -> Self {
+        Self::new(MetricsConfig::default())
+    }
 }
 
 impl MetricSet {
-    pub fn update(&self, event: TaskEvent) {
-        let metric = self.inner.get(&event.key);
+    pub fn new(config: MetricsConfig) -> Self {
+        Self {
+            inner: Default::default(),
+            series: Default::default(),
+            config,
+            start: Instant::now(),
+            next_sample: AtomicU64::new(0),
+            history: Default::default(),
+        }
+    }
 
-        if let Some(metric) = metric {
-            metric.update(event.value);
-        } else {
-            let v = metrics::Metric::new(event.key.metric_type, &event.value);
-            v.update(event.value);
-            self.inner.insert(event.key, v);
+    /// Record an event. Returns the metric name when this event is the first one
+    /// to go over the cardinality limit of the metric.
+    pub fn update(&self, event: TaskEvent) -> Option<&'static str> {
+        self.update_corrected(event, None)
+    }
+
+    /// Record an event from an executor that expects an iteration every `expected_interval`.
+    /// Durations of [`ITERATION_DURATION`](super::ITERATION_DURATION) longer than the interval
+    /// are back-filled with the samples missed while waiting on the slow iteration, and tagged
+    /// with a `corrected` attribute. At most 1024 samples are back-filled for one iteration,
+    /// spread evenly over longer stalls. Other metrics are recorded as they are.
+    pub fn update_corrected(
+        &self,
+        mut event: TaskEvent,
+        expected_interval: Option<Duration>,
+    ) -> Option<&'static str> {
+        let corrected = event.key.name == super::ITERATION_DURATION
+            && matches!(event.value, Value::Duration(_));
+        let expected_interval = expected_interval.filter(|x| corrected && !x.is_zero());
+        if expected_interval.is_some() {
+            event
+                .key
+                .attributes
+                .push(("corrected", Value::String("true".to_string())));
+        }
+
+        let overflow = self.record(event, expected_interval);
+        self.sample_history();
+        overflow
+    }
+
+    fn record(
+        &self,
+        mut event: TaskEvent,
+        expected_interval: Option<Duration>,
+    ) -> Option<&'static str> {
+        if let Some(metric) = self.inner.get(&event.key) {
+            observe(&metric, event.value, expected_interval);
+            return None;
+        }
+
+        // Series of a metric are only created while holding its count, so that concurrent
+        // executors can not both take the last slot or create the same series twice.
+        let mut overflow = None;
+        let mut series = self
+            .series
+            .entry((event.key.name, event.key.metric_type))
+            .or_default();
+        if !self.inner.contains_key(&event.key) {
+            if *series >= self.config.cardinality_limit {
+                for (_, value) in event.key.attributes.iter_mut() {
+                    *value = Value::String(OVERFLOW_ATTRIBUTE.to_string());
+                }
+                if !self.inner.contains_key(&event.key) {
+                    overflow = Some(event.key.name);
+                }
+            } else {
+                *series += 1;
+            }
+        }
+
+        let TaskEvent { key, value } = event;
+        let metric_type = key.metric_type;
+        let metric = self.inner.entry(key).or_insert_with(|| {
+            metrics::Metric::new(metric_type, &value, self.start, self.config.digest)
+        });
+        observe(&metric, value, expected_interval);
+        overflow
+    }
+
+    /// Append current values to the history once every resolution interval.
+    /// Samples are taken on updates, so idle metrics have gaps in their history.
+    fn sample_history(&self) {
+        let MetricsConfig {
+            history_resolution: resolution,
+            history_retention: retention,
+            history_budget: budget,
+            ..
+        } = self.config;
+        if retention.is_zero() {
+            return;
+        }
+
+        let elapsed = self.start.elapsed().as_nanos() as u64;
+        let due = self.next_sample.load(Ordering::Relaxed);
+        if elapsed < due {
+            return;
+        }
+        // Only one of the concurrent updates takes the sample.
+        let next = elapsed + resolution.as_nanos() as u64;
+        if self
+            .next_sample
+            .compare_exchange(due, next, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+
+        let time = Utc::now();
+        let oldest = time - retention;
+        let mut history = self.history.lock().unwrap();
+        for (key, value) in self.entries() {
+            let samples = history.entry(key).or_default();
+            while samples.front().is_some_and(|x| x.time < oldest) {
+                samples.pop_front();
+            }
+            samples.push_back(Sample { time, value });
+        }
+        if let Some(budget) = budget {
+            downsample(&mut history, budget / std::mem::size_of::<Sample>());
         }
     }
 
+    /// Samples of every metric over the retention period, oldest first.
+    pub fn history(&self) -> Vec<(MetricSetKey, Vec<Sample>)> {
+        self.history
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(key, samples)| (key.clone(), samples.iter().copied().collect()))
+            .collect()
+    }
+
     pub fn entries(&self) -> impl Iterator<Item = (MetricSetKey, metrics::MetricValue)> + '_ {
         self.inner
             .iter()
             .map(|x| (x.key().clone(), x.value().value()))
     }
+
+    /// Totals of counters and rates, and number of values observed by histograms and trends.
+    pub fn counts(&self) -> impl Iterator<Item = (MetricSetKey, u64)> + '_ {
+        self.inner
+            .iter()
+            .filter_map(|x| x.value().count().map(|count| (x.key().clone(), count)))
+    }
+
+    /// Values of every metric computed over the last [`WINDOW`](metrics::WINDOW) only.
+    pub fn window_entries(
+        &self,
+    ) -> impl Iterator<Item = (MetricSetKey, metrics::MetricValue)> + '_ {
+        self.inner
+            .iter()
+            .map(|x| (x.key().clone(), x.value().window_value()))
+    }
+
+    /// Empty set of a run that already went on for `elapsed`, so that per second rates of
+    /// restored metrics are spread over the whole run.
+    #[cfg(feature = "serde")]
+    pub(crate) fn resumed(config: MetricsConfig, elapsed: Duration) -> Self {
+        let mut set = Self::new(config);
+        set.start = set.start.checked_sub(elapsed).unwrap_or(set.start);
+        set
+    }
+
+    /// State of every metric in this set, see [`restore`](Self::restore).
+    #[cfg(feature = "serde")]
+    pub(crate) fn states(&self) -> impl Iterator<Item = (MetricSetKey, metrics::MetricState)> + '_ {
+        self.inner
+            .iter()
+            .map(|x| (x.key().clone(), x.value().state()))
+    }
+
+    /// Restore a metric from a previous run, without counting it towards the window.
+    /// Restored series count towards the cardinality limit of their metric.
+    #[cfg(feature = "serde")]
+    pub(crate) fn restore(&self, key: MetricSetKey, state: metrics::MetricState) {
+        let (metric_type, value) = state.kind();
+        let mut series = self.series.entry((key.name, metric_type)).or_default();
+        let metric = self.inner.entry(key).or_insert_with(|| {
+            *series += 1;
+            metrics::Metric::new(metric_type, &value, self.start, self.config.digest)
+        });
+        drop(series);
+        metric.restore(state);
+    }
+
+    /// Mergeable digests of every histogram in this set.
+    pub fn digests(&self) -> impl Iterator<Item = (MetricSetKey, metrics::HistogramDigest)> + '_ {
+        self.inner
+            .iter()
+            .filter_map(|x| x.value().digest().map(|digest| (x.key().clone(), digest)))
+    }
+
+    /// Combine metrics with the same key across sets, such as the executors of a scenario.
+    /// Counters, rates, bytes and ratios are summed while histograms and trends merge their
+    /// digests, so percentiles are those of all values observed. Gauges are left out.
+    pub fn rollup<'a>(
+        sets: impl IntoIterator<Item = &'a MetricSet>,
+    ) -> Vec<(MetricSetKey, metrics::MetricValue)> {
+        let mut rollups: HashMap<MetricSetKey, metrics::Rollup> = HashMap::new();
+        for set in sets {
+            for entry in set.inner.iter() {
+                let Some(rollup) = entry.value().rollup() else {
+                    continue;
+                };
+                let merged = match rollups.remove(entry.key()) {
+                    Some(existing) => existing.merge(rollup),
+                    None => rollup,
+                };
+                rollups.insert(entry.key().clone(), merged);
+            }
+        }
+        rollups
+            .into_iter()
+            .map(|(key, rollup)| (key, rollup.value()))
+            .collect()
+    }
+
+    /// Merge a digest collected elsewhere (another worker or run) into the histogram for `key`.
+    pub fn merge_digest(&self, key: MetricSetKey, digest: metrics::HistogramDigest) {
+        if let Some(metric) = self.inner.get(&key) {
+            metric.merge_digest(digest);
+            return;
+        }
+        let value = if digest.is_duration() {
+            Value::Duration(Duration::ZERO)
+        } else {
+            Value::Float(OrderedFloat(0.))
+        };
+        let metric = metrics::Metric::new(
+            MetricType::Histogram,
+            &value,
+            self.start,
+            self.config.digest,
+        );
+        metric.merge_digest(digest);
+        self.inner.insert(key, metric);
+    }
+}
+
+/// Thin out the older half of every series until at most `limit` samples are kept in total.
+fn downsample(history: &mut HashMap<MetricSetKey, VecDeque<Sample>>, limit: usize) {
+    let mut total: usize = history.values().map(VecDeque::len).sum();
+    while total > limit {
+        let before = total;
+        for samples in history.values_mut() {
+            let older = samples.len() / 2;
+            let mut index = 0;
+            samples.retain(|_| {
+                index += 1;
+                index > older || index % 2 == 0
+            });
+        }
+        total = history.values().map(VecDeque::len).sum();
+        // Series down to a single sample can not be thinned, drop their oldest samples instead.
+        if total == before {
+            for samples in history.values_mut() {
+                samples.pop_front();
+            }
+            history.retain(|_, samples| !samples.is_empty());
+            total = history.values().map(VecDeque::len).sum();
+        }
+    }
+}
+
+/// Most samples back-filled for a single call, so that a stall many intervals long does not
+/// hold up the executor recording it.
+const MAX_BACKFILL: u64 = 1024;
+
+/// Update a metric, back-filling durations missed while waiting on a call longer than `expected_interval`.
+/// When more than [`MAX_BACKFILL`] samples were missed, the samples back-filled are spread evenly
+/// over the missed durations instead, which keeps their distribution but not their count.
+fn observe(metric: &metrics::Metric, value: Value, expected_interval: Option<Duration>) {
+    if let (Some(interval), Value::Duration(duration)) = (expected_interval, &value) {
+        let mut missed = duration.saturating_sub(interval);
+        let count = (missed.as_nanos() / interval.as_nanos()) as u64;
+        let stride = u32::try_from(count.div_ceil(MAX_BACKFILL)).unwrap_or(u32::MAX);
+        let step = interval.saturating_mul(stride.max(1));
+        while missed >= interval {
+            metric.update(Value::Duration(missed));
+            missed = missed.saturating_sub(step);
+        }
+    }
+    metric.update(value);
 }
 
 /// Represents scalar values that are allowed to be in a user eventErrorVisitor's attribute set.
@@ -68,6 +441,8 @@ pub enum Value {
     #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_ordered_float"))]
     Float(OrderedFloat<f64>),
     Duration(Duration),
+    Bytes(u64),
+    Bool(bool),
 }
 
 impl From<Duration> for Value {
@@ -84,6 +459,8 @@ impl std::fmt::Display for Value {
             Value::UnsignedNumber(x) => write!(f, "{}", x),
             Value::Float(x) => write!(f, "{}", x),
             Value::Duration(x) => write!(f, "{:?}", x),
+            Value::Bytes(x) => write!(f, "{}", metrics::format_bytes(*x as f64)),
+            Value::Bool(x) => write!(f, "{}", x),
         }
     }
 }
@@ -109,6 +486,10 @@ impl TaskEvent {
             value,
         }
     }
+
+    pub fn key(&self) -> &MetricSetKey {
+        &self.key
+    }
 }
 
 impl tracing::field::Visit for TaskEvent {
@@ -136,6 +517,9 @@ impl tracing::field::Visit for TaskEvent {
 
     fn record_u64(&mut self, field: &tracing::field::Field, value: u64) {
         match field.name() {
+            "value" if self.key.metric_type == MetricType::Bytes => {
+                self.value = Value::Bytes(value)
+            }
             "value" => self.value = Value::UnsignedNumber(value),
             _ => self
                 .key
@@ -144,6 +528,16 @@ impl tracing::field::Visit for TaskEvent {
         }
     }
 
+    fn record_bool(&mut self, field: &tracing::field::Field, value: bool) {
+        match field.name() {
+            "value" => self.value = Value::Bool(value),
+            _ => self
+                .key
+                .attributes
+                .push((field.name(), Value::String(value.to_string()))),
+        }
+    }
+
     fn record_f64(&mut self, field: &tracing::field::Field, value: f64) {
         match field.name() {
             "value" => self.value = Value::Float(OrderedFloat(value)),
@@ -212,3 +606,212 @@ fn serialize_ordered_float<S: serde::Serializer>(
 ) -> Result<S::Ok, S::Error> {
     serde::Serialize::serialize(&x.0, s)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{metrics::MetricType, MetricSet, MetricsConfig, TaskEvent, Value};
+
+    #[test]
+    fn cardinality_overflow() {
+        let set = MetricSet::new(MetricsConfig::default().cardinality_limit(2));
+        let event = |id: u64| {
+            TaskEvent::new(
+                "requests",
+                MetricType::Counter,
+                vec![("id", Value::UnsignedNumber(id))],
+                Value::UnsignedNumber(1),
+            )
+        };
+
+        assert_eq!(set.update(event(0)), None);
+        assert_eq!(set.update(event(1)), None);
+        assert_eq!(set.update(event(2)), Some("requests"));
+        assert_eq!(set.update(event(3)), None);
+        assert_eq!(set.update(event(0)), None);
+
+        let mut entries: Vec<_> = set
+            .entries()
+            .map(|(key, value)| (key.attributes[0].1.to_string(), value.to_string()))
+            .collect();
+        entries.sort();
+        assert_eq!(
+            entries,
+            [("0", "2"), ("1", "1"), ("_other", "2")].map(|(x, y)| (x.to_string(), y.to_string()))
+        );
+    }
+
+    #[test]
+    fn cardinality_per_metric_type() {
+        let set = MetricSet::new(MetricsConfig::default().cardinality_limit(4));
+        std::thread::scope(|scope| {
+            for thread in 0..4u64 {
+                let set = &set;
+                scope.spawn(move || {
+                    for id in 0..8 {
+                        for (metric_type, value) in [
+                            (MetricType::Counter, Value::UnsignedNumber(1)),
+                            (MetricType::Gauge, Value::Number(1)),
+                        ] {
+                            let attributes = vec![("id", Value::UnsignedNumber(thread * 8 + id))];
+                            set.update(TaskEvent::new("requests", metric_type, attributes, value));
+                        }
+                    }
+                });
+            }
+        });
+
+        for metric_type in [MetricType::Counter, MetricType::Gauge] {
+            let series: Vec<_> = set
+                .entries()
+                .filter(|(key, _)| key.metric_type == metric_type)
+                .map(|(key, _)| key.attributes[0].1.to_string())
+                .collect();
+            assert_eq!(series.len(), 5, "{metric_type:?}");
+            assert!(series.iter().any(|x| x == "_other"));
+        }
+    }
+
+    #[test]
+    fn history_samples() {
+        let config = MetricsConfig::default().history_resolution(std::time::Duration::ZERO);
+        let set = MetricSet::new(config);
+        let event = || {
+            TaskEvent::new(
+                "requests",
+                MetricType::Counter,
+                vec![],
+                Value::UnsignedNumber(1),
+            )
+        };
+        for _ in 0..3 {
+            set.update(event());
+        }
+
+        let history = set.history();
+        assert_eq!(history.len(), 1);
+        let values: Vec<_> = history[0].1.iter().map(|x| x.value.to_string()).collect();
+        assert_eq!(values, ["1", "2", "3"]);
+
+        let disabled = MetricSet::new(config.history_retention(std::time::Duration::ZERO));
+        disabled.update(event());
+        assert!(disabled.history().is_empty());
+
+        let budget = 10 * std::mem::size_of::<super::Sample>();
+        let bounded = MetricSet::new(config.history_budget(budget));
+        for _ in 0..100 {
+            bounded.update(event());
+        }
+        let history = bounded.history();
+        let values: Vec<_> = history[0].1.iter().map(|x| x.value.to_string()).collect();
+        assert!(values.len() <= 10);
+        assert_eq!(values.last().unwrap(), "100");
+    }
+
+    #[test]
+    fn rollup_across_sets() {
+        use super::metrics::MetricValue;
+
+        let sets = [MetricSet::default(), MetricSet::default()];
+        for (i, set) in sets.iter().enumerate() {
+            let event = |ty, value| TaskEvent::new("latency", ty, vec![], value);
+            set.update(event(MetricType::Counter, Value::UnsignedNumber(2)));
+            set.update(event(MetricType::Gauge, Value::Number(1)));
+            for x in 0..100 {
+                let value = (i * 100 + x) as f64;
+                set.update(event(MetricType::Histogram, Value::Float(value.into())));
+            }
+        }
+
+        let rollup: std::collections::HashMap<_, _> = MetricSet::rollup(&sets)
+            .into_iter()
+            .map(|(key, value)| (key.metric_type, value))
+            .collect();
+        assert_eq!(rollup.len(), 2);
+        assert_eq!(rollup[&MetricType::Counter], MetricValue::Counter(4));
+        let MetricValue::Histogram(((p50, ..), sum, (min, max))) = rollup[&MetricType::Histogram]
+        else {
+            panic!("expected a histogram");
+        };
+        assert!((p50 - 100.).abs() < 5.);
+        assert_eq!(sum, (0..200).sum::<u64>() as f64);
+        assert_eq!((min, max), (0., 199.));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn restore_states() {
+        use super::metrics::MetricState;
+        use std::time::Duration;
+
+        let set = MetricSet::default();
+        let event = |ty, value| TaskEvent::new("requests", ty, vec![], value);
+        set.update(event(MetricType::Counter, Value::UnsignedNumber(2)));
+        set.update(event(MetricType::Gauge, Value::Float(1.5.into())));
+        set.update(event(MetricType::Histogram, Value::Float(3.0.into())));
+        set.update(event(MetricType::Rate, Value::UnsignedNumber(4)));
+        set.update(event(
+            MetricType::Trend,
+            Value::Duration(Duration::from_millis(5)),
+        ));
+        set.update(event(MetricType::Bytes, Value::Bytes(6)));
+        set.update(event(MetricType::Ratio, Value::Bool(true)));
+
+        let states: Vec<MetricState> = set.states().map(|(_, state)| state).collect();
+        let states: Vec<MetricState> =
+            serde_json::from_slice(&serde_json::to_vec(&states).unwrap()).unwrap();
+        let restored = MetricSet::default();
+        for state in states {
+            let (metric_type, value) = state.kind();
+            restored.restore(
+                TaskEvent::new("requests", metric_type, vec![], value).key,
+                state,
+            );
+        }
+
+        let sorted = |set: &MetricSet| {
+            let mut entries: Vec<_> = set
+                .entries()
+                .filter(|(key, _)| !matches!(key.metric_type, MetricType::Rate | MetricType::Bytes))
+                .map(|(key, value)| (key.metric_type.to_string(), value.to_string()))
+                .collect();
+            let mut counts: Vec<_> = set.counts().map(|(key, x)| (key.metric_type, x)).collect();
+            entries.sort();
+            counts.sort_by_key(|(x, _)| x.to_string());
+            (entries, counts)
+        };
+        assert_eq!(sorted(&restored), sorted(&set));
+    }
+
+    #[test]
+    fn omission_correction() {
+        use crate::tracing::ITERATION_DURATION;
+        use std::time::Duration;
+
+        let set = MetricSet::default();
+        let ms = Duration::from_millis;
+        let event = |name, value| {
+            TaskEvent::new(name, MetricType::Histogram, vec![], Value::Duration(value))
+        };
+        set.update_corrected(event(ITERATION_DURATION, ms(50)), Some(ms(10)));
+        set.update_corrected(event(ITERATION_DURATION, ms(5)), Some(ms(10)));
+        set.update_corrected(event("call", ms(50)), Some(ms(10)));
+
+        let counts: std::collections::HashMap<_, _> = set
+            .counts()
+            .map(|(key, count)| ((key.name, key.attributes), count))
+            .collect();
+        // 50ms iteration back-fills 40, 30, 20 and 10ms samples, other durations are left as is.
+        let corrected = vec![("corrected", Value::String("true".to_string()))];
+        assert_eq!(counts[&(ITERATION_DURATION, corrected)], 6);
+        assert_eq!(counts[&("call", vec![])], 1);
+
+        // An hour long stall is back-filled with a bounded number of samples.
+        let set = MetricSet::default();
+        set.update_corrected(
+            event(ITERATION_DURATION, Duration::from_secs(3600)),
+            Some(ms(1)),
+        );
+        let (_, count) = set.counts().next().unwrap();
+        assert!(count > super::MAX_BACKFILL / 2 && count <= super::MAX_BACKFILL + 1);
+    }
+}
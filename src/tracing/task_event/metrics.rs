@@ -1,10 +1,12 @@
 use std::{
+    collections::VecDeque,
+    fmt::Debug,
     str::FromStr,
     sync::{
         atomic::{AtomicU64, Ordering},
         Mutex,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use atomic::Atomic;
@@ -19,6 +21,10 @@ pub enum MetricType {
     Counter,
     Gauge,
     Histogram,
+    Rate,
+    Trend,
+    Bytes,
+    Ratio,
 }
 
 #[allow(clippy::to_string_trait_impl)]
@@ -28,6 +34,10 @@ impl ToString for MetricType {
             MetricType::Counter => "counter".to_string(),
             MetricType::Gauge => "gauge".to_string(),
             MetricType::Histogram => "histogram".to_string(),
+            MetricType::Rate => "rate".to_string(),
+            MetricType::Trend => "trend".to_string(),
+            MetricType::Bytes => "bytes".to_string(),
+            MetricType::Ratio => "ratio".to_string(),
         }
     }
 }
@@ -40,6 +50,10 @@ impl FromStr for MetricType {
             "counter" => Ok(Self::Counter),
             "gauge" => Ok(Self::Gauge),
             "histogram" => Ok(Self::Histogram),
+            "rate" => Ok(Self::Rate),
+            "trend" => Ok(Self::Trend),
+            "bytes" => Ok(Self::Bytes),
+            "ratio" => Ok(Self::Ratio),
             _ => Err(()),
         }
     }
@@ -54,9 +68,46 @@ pub enum MetricValue {
     GaugeI64(i64),
     GaugeU64(u64),
     GaugeDuration(Duration),
-    /// histogram values ((p50, p90, p95, p99), sum)
-    Histogram(((f64, f64, f64, f64), f64)),
-    DurationHistogram(((Duration, Duration, Duration, Duration), Duration)),
+    /// histogram values ((p50, p90, p95, p99), sum, (min, max))
+    Histogram(((f64, f64, f64, f64), f64, (f64, f64))),
+    DurationHistogram(
+        (
+            (Duration, Duration, Duration, Duration),
+            Duration,
+            (Duration, Duration),
+        ),
+    ),
+    /// rate values per second (over the run, over the last [`WINDOW`])
+    Rate((f64, f64)),
+    Trend(TrendValue<f64>),
+    DurationTrend(TrendValue<Duration>),
+    /// bytes transferred and throughput in bytes per second (over the run, over the last [`WINDOW`])
+    Bytes((u64, (f64, f64))),
+    /// (number of `true` values, number of values)
+    Ratio((u64, u64)),
+}
+
+/// Summary statistics of a trend metric.
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TrendValue<T> {
+    pub count: u64,
+    pub mean: T,
+    pub stddev: T,
+    pub min: T,
+    pub max: T,
+    /// (p50, p90, p95, p99)
+    pub percentiles: (T, T, T, T),
+}
+
+impl<T: Debug> std::fmt::Display for TrendValue<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "count={} mean={:.2?} stddev={:.2?} min={:.2?} max={:.2?} p95={:.2?}",
+            self.count, self.mean, self.stddev, self.min, self.max, self.percentiles.2
+        )
+    }
 }
 
 #[allow(clippy::to_string_trait_impl)]
@@ -70,10 +121,52 @@ impl ToString for MetricValue {
             MetricValue::GaugeDuration(x) => format!("{:.2?}", x),
             MetricValue::Histogram(x) => format!("{:.2?}", x),
             MetricValue::DurationHistogram(x) => format!("{:.2?}", x),
+            MetricValue::Rate((total, window)) => format!("{:.2}/s ({:.2}/s)", total, window),
+            MetricValue::Trend(x) => x.to_string(),
+            MetricValue::DurationTrend(x) => x.to_string(),
+            MetricValue::Bytes((total, (rate, window))) => format!(
+                "{} {}/s ({}/s)",
+                format_bytes(*total as f64),
+                format_bytes(*rate),
+                format_bytes(*window)
+            ),
+            MetricValue::Ratio((passed, total)) => {
+                format!(
+                    "{:.2}% ({}/{})",
+                    ratio(*passed, *total) * 100.,
+                    passed,
+                    total
+                )
+            }
         }
     }
 }
 
+/// Fraction of `true` values, 0 when nothing was recorded.
+pub fn ratio(passed: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.
+    } else {
+        passed as f64 / total as f64
+    }
+}
+
+/// Format a byte amount with binary unit prefixes, e.g. `1.50 MiB`.
+pub fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024. && unit < UNITS.len() - 1 {
+        value /= 1024.;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.2} {}", value, UNITS[unit])
+    }
+}
+
 impl MetricValue {
     pub fn min_gauge<'a>(&'a self, other: &'a Self) -> &'a Self {
         match (self, other) {
@@ -162,20 +255,42 @@ pub(crate) enum Metric {
     GaugeDuration((Gauge<u64>, Gauge<u32>)),
     Histogram(Histogram),
     Duration(Histogram),
+    Rate(Rate),
+    Trend(Trend),
+    DurationTrend(Trend),
+    Bytes((Counter, Rate)),
+    Ratio((Counter, Counter)),
 }
 
 impl Metric {
-    pub fn new(ty: MetricType, value: &Value) -> Self {
+    /// Create a metric for the value type. `start` is when the owning metric set was
+    /// created, rates and windows are computed relative to it.
+    pub fn new(ty: MetricType, value: &Value, start: Instant, digest: DigestConfig) -> Self {
         match (ty, value) {
-            (MetricType::Counter, Value::UnsignedNumber(_)) => Self::Counter(Counter::new()),
+            (MetricType::Counter, Value::UnsignedNumber(_)) => Self::Counter(Counter::new(start)),
             (MetricType::Gauge, Value::Float(_)) => Self::GaugeF64(Gauge::new()),
             (MetricType::Gauge, Value::Number(_)) => Self::GaugeI64(Gauge::new()),
             (MetricType::Gauge, Value::UnsignedNumber(_)) => Self::GaugeU64(Gauge::new()),
             (MetricType::Gauge, Value::Duration(_)) => {
                 Self::GaugeDuration((Gauge::new(), Gauge::new()))
             }
-            (MetricType::Histogram, Value::Float(_)) => Self::Histogram(Histogram::new()),
-            (MetricType::Histogram, Value::Duration(_)) => Self::Duration(Histogram::new()),
+            (MetricType::Histogram, Value::Float(_)) => {
+                Self::Histogram(Histogram::new(start, digest))
+            }
+            (MetricType::Histogram, Value::Duration(_)) => {
+                Self::Duration(Histogram::new(start, digest))
+            }
+            (MetricType::Rate, Value::UnsignedNumber(_)) => Self::Rate(Rate::new(start)),
+            (MetricType::Trend, Value::Float(_)) => Self::Trend(Trend::new(start, digest)),
+            (MetricType::Trend, Value::Duration(_)) => {
+                Self::DurationTrend(Trend::new(start, digest))
+            }
+            (MetricType::Bytes, Value::Bytes(_)) => {
+                Self::Bytes((Counter::new(start), Rate::new(start)))
+            }
+            (MetricType::Ratio, Value::Bool(_)) => {
+                Self::Ratio((Counter::new(start), Counter::new(start)))
+            }
             _ => panic!("Unsupported value type for metric"),
         }
     }
@@ -195,6 +310,17 @@ impl Metric {
                 let val = f.as_nanos() as u64;
                 x.observe(val as f64)
             }
+            (Metric::Rate(x), Value::UnsignedNumber(val)) => x.add(val),
+            (Metric::Trend(x), Value::Float(val)) => x.observe(val.0),
+            (Metric::DurationTrend(x), Value::Duration(f)) => x.observe(f.as_nanos() as f64),
+            (Metric::Bytes((total, rate)), Value::Bytes(val)) => {
+                total.add(val);
+                rate.add(val)
+            }
+            (Metric::Ratio((passed, total)), Value::Bool(val)) => {
+                passed.add(val as u64);
+                total.add(1)
+            }
             _ => {}
         }
     }
@@ -208,47 +334,543 @@ impl Metric {
             Metric::GaugeDuration(x) => {
                 MetricValue::GaugeDuration(Duration::new(x.0.get(), x.1.get()))
             }
-            Metric::Histogram(x) => MetricValue::Histogram((x.get_percentiles(), x.get_sum())),
+            Metric::Histogram(x) => {
+                MetricValue::Histogram((x.get_percentiles(), x.get_sum(), x.get_min_max()))
+            }
             Metric::Duration(x) => {
-                let f = |f: f64| -> u64 {
-                    if f.is_nan() {
-                        return 0;
-                    }
-                    unsafe { f.to_int_unchecked() }
-                };
-                let (p50, p90, p95, p99) = x.get_percentiles();
-                let p50 = Duration::from_nanos(f(p50));
-                let p90 = Duration::from_nanos(f(p90));
-                let p95 = Duration::from_nanos(f(p95));
-                let p99 = Duration::from_nanos(f(p99));
-                MetricValue::DurationHistogram((
-                    (p50, p90, p95, p99),
-                    Duration::from_nanos(f(x.get_sum())),
-                ))
+                duration_histogram(x.get_percentiles(), x.get_sum(), x.get_min_max())
             }
+            Metric::Rate(x) => MetricValue::Rate(x.get()),
+            Metric::Trend(x) => MetricValue::Trend(x.get()),
+            Metric::DurationTrend(x) => MetricValue::DurationTrend(duration_trend(x.get())),
+            Metric::Bytes((total, rate)) => MetricValue::Bytes((total.get(), rate.get())),
+            Metric::Ratio((passed, total)) => MetricValue::Ratio((passed.get(), total.get())),
+        }
+    }
+
+    /// Value of the metric computed only over the last [`WINDOW`].
+    /// Gauges and rates already reflect current behavior and are returned as is.
+    pub fn window_value(&self) -> MetricValue {
+        match self {
+            Metric::Counter(x) => MetricValue::Counter(x.get_window()),
+            Metric::Histogram(x) => {
+                let (percentiles, stats) = x.get_window();
+                MetricValue::Histogram((percentiles, stats.sum(), stats.min_max()))
+            }
+            Metric::Duration(x) => {
+                let (percentiles, stats) = x.get_window();
+                duration_histogram(percentiles, stats.sum(), stats.min_max())
+            }
+            Metric::Trend(x) => MetricValue::Trend(x.get_window()),
+            Metric::DurationTrend(x) => MetricValue::DurationTrend(duration_trend(x.get_window())),
+            Metric::Bytes((total, rate)) => MetricValue::Bytes((total.get_window(), rate.get())),
+            Metric::Ratio((passed, total)) => {
+                MetricValue::Ratio((passed.get_window(), total.get_window()))
+            }
+            _ => self.value(),
+        }
+    }
+
+    /// Total of a counter, rate or bytes metric, or number of values recorded by a ratio,
+    /// histogram or trend.
+    /// None for gauges.
+    pub(crate) fn count(&self) -> Option<u64> {
+        match self {
+            Metric::Counter(x) => Some(x.get()),
+            Metric::Rate(x) => Some(x.total.load(Ordering::Relaxed)),
+            Metric::Bytes((x, _)) => Some(x.get()),
+            Metric::Ratio((_, x)) => Some(x.get()),
+            Metric::Histogram(x) | Metric::Duration(x) => Some(x.count()),
+            Metric::Trend(x) | Metric::DurationTrend(x) => Some(x.histogram.count()),
+            _ => None,
+        }
+    }
+
+    /// Digest of a histogram metric, None for other metric types.
+    pub(crate) fn digest(&self) -> Option<HistogramDigest> {
+        match self {
+            Metric::Histogram(x) => Some(x.digest(false)),
+            Metric::Duration(x) => Some(x.digest(true)),
+            _ => None,
+        }
+    }
+
+    /// State of the metric that can be combined with the same metric of other executors.
+    /// None for gauges, as the latest value of separate executors has no meaningful combination.
+    pub(crate) fn rollup(&self) -> Option<Rollup> {
+        match self {
+            Metric::Counter(x) => Some(Rollup::Counter(x.get())),
+            Metric::Rate(x) => Some(Rollup::Rate(x.get())),
+            Metric::Bytes((total, rate)) => Some(Rollup::Bytes((total.get(), rate.get()))),
+            Metric::Ratio((passed, total)) => Some(Rollup::Ratio((passed.get(), total.get()))),
+            Metric::Histogram(x) => Some(Rollup::Histogram(x.digest(false))),
+            Metric::Duration(x) => Some(Rollup::Histogram(x.digest(true))),
+            Metric::Trend(x) => Some(Rollup::Trend((
+                x.histogram.digest(false),
+                *x.stats.lock().unwrap(),
+            ))),
+            Metric::DurationTrend(x) => Some(Rollup::Trend((
+                x.histogram.digest(true),
+                *x.stats.lock().unwrap(),
+            ))),
+            _ => None,
+        }
+    }
+
+    /// Merge a digest into a histogram metric. Ignored for other metric types.
+    pub(crate) fn merge_digest(&self, digest: HistogramDigest) {
+        match self {
+            Metric::Histogram(x) | Metric::Duration(x) => x.merge(digest),
+            _ => {}
+        }
+    }
+
+    /// State of the metric to be restored after resuming a run.
+    #[cfg(feature = "serde")]
+    pub(crate) fn state(&self) -> MetricState {
+        match self {
+            Metric::Counter(x) => MetricState::Counter(x.get()),
+            Metric::GaugeF64(x) => MetricState::GaugeF64(x.get()),
+            Metric::GaugeI64(x) => MetricState::GaugeI64(x.get()),
+            Metric::GaugeU64(x) => MetricState::GaugeU64(x.get()),
+            Metric::GaugeDuration(x) => {
+                MetricState::GaugeDuration(Duration::new(x.0.get(), x.1.get()))
+            }
+            Metric::Histogram(x) => MetricState::Histogram(x.digest(false)),
+            Metric::Duration(x) => MetricState::Histogram(x.digest(true)),
+            Metric::Rate(x) => MetricState::Rate(x.total.load(Ordering::Relaxed)),
+            Metric::Trend(x) => {
+                MetricState::Trend((x.histogram.digest(false), *x.stats.lock().unwrap()))
+            }
+            Metric::DurationTrend(x) => {
+                MetricState::Trend((x.histogram.digest(true), *x.stats.lock().unwrap()))
+            }
+            Metric::Bytes((total, _)) => MetricState::Bytes(total.get()),
+            Metric::Ratio((passed, total)) => MetricState::Ratio((passed.get(), total.get())),
+        }
+    }
+
+    /// Add the state of a previous run to the metric, without counting it towards the window.
+    /// Gauges take the restored value. A state of another metric type is ignored.
+    #[cfg(feature = "serde")]
+    pub(crate) fn restore(&self, state: MetricState) {
+        match (self, state) {
+            (Metric::Counter(x), MetricState::Counter(value)) => {
+                x.value.fetch_add(value, Ordering::Relaxed);
+            }
+            (Metric::GaugeF64(x), MetricState::GaugeF64(value)) => x.set(value),
+            (Metric::GaugeI64(x), MetricState::GaugeI64(value)) => x.set(value),
+            (Metric::GaugeU64(x), MetricState::GaugeU64(value)) => x.set(value),
+            (Metric::GaugeDuration((sec, nanos)), MetricState::GaugeDuration(value)) => {
+                sec.set(value.as_secs());
+                nanos.set(value.subsec_nanos())
+            }
+            (Metric::Histogram(x) | Metric::Duration(x), MetricState::Histogram(digest)) => {
+                x.merge(digest)
+            }
+            (Metric::Rate(x), MetricState::Rate(value)) => {
+                x.total.fetch_add(value, Ordering::Relaxed);
+            }
+            (Metric::Trend(x) | Metric::DurationTrend(x), MetricState::Trend((digest, stats))) => {
+                x.histogram.merge(digest);
+                let mut current = x.stats.lock().unwrap();
+                *current = current.merge(&stats);
+            }
+            (Metric::Bytes((total, rate)), MetricState::Bytes(value)) => {
+                total.value.fetch_add(value, Ordering::Relaxed);
+                rate.total.fetch_add(value, Ordering::Relaxed);
+            }
+            (Metric::Ratio((passed, total)), MetricState::Ratio((x, y))) => {
+                passed.value.fetch_add(x, Ordering::Relaxed);
+                total.value.fetch_add(y, Ordering::Relaxed);
+            }
+            _ => {}
         }
     }
 }
 
+/// Cumulative state of a metric, written to checkpoints so that every metric continues
+/// from where it was when a run is resumed. Rates and bytes keep their total, the per
+/// second rate is derived again from the elapsed time of the run.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub(crate) enum MetricState {
+    Counter(u64),
+    GaugeF64(f64),
+    GaugeI64(i64),
+    GaugeU64(u64),
+    GaugeDuration(Duration),
+    Histogram(HistogramDigest),
+    Rate(u64),
+    Trend((HistogramDigest, Stats)),
+    Bytes(u64),
+    Ratio((u64, u64)),
+}
+
+#[cfg(feature = "serde")]
+impl MetricState {
+    /// Type of the metric and a value of the kind it records, to create the metric from.
+    pub(crate) fn kind(&self) -> (MetricType, Value) {
+        let number = |digest: &HistogramDigest| {
+            if digest.is_duration() {
+                Value::Duration(Duration::ZERO)
+            } else {
+                Value::Float(OrderedFloat(0.))
+            }
+        };
+        match self {
+            MetricState::Counter(_) => (MetricType::Counter, Value::UnsignedNumber(0)),
+            MetricState::GaugeF64(_) => (MetricType::Gauge, Value::Float(OrderedFloat(0.))),
+            MetricState::GaugeI64(_) => (MetricType::Gauge, Value::Number(0)),
+            MetricState::GaugeU64(_) => (MetricType::Gauge, Value::UnsignedNumber(0)),
+            MetricState::GaugeDuration(_) => (MetricType::Gauge, Value::Duration(Duration::ZERO)),
+            MetricState::Histogram(digest) => (MetricType::Histogram, number(digest)),
+            MetricState::Rate(_) => (MetricType::Rate, Value::UnsignedNumber(0)),
+            MetricState::Trend((digest, _)) => (MetricType::Trend, number(digest)),
+            MetricState::Bytes(_) => (MetricType::Bytes, Value::Bytes(0)),
+            MetricState::Ratio(_) => (MetricType::Ratio, Value::Bool(false)),
+        }
+    }
+}
+
+/// A metric combined across executors. Totals and rates are summed, digests and stats merged.
+#[derive(Debug, Clone)]
+pub(crate) enum Rollup {
+    Counter(u64),
+    Rate((f64, f64)),
+    Bytes((u64, (f64, f64))),
+    Ratio((u64, u64)),
+    Histogram(HistogramDigest),
+    Trend((HistogramDigest, Stats)),
+}
+
+impl Rollup {
+    /// Combine with the same metric of another executor. Mismatched metric types keep `self`.
+    pub(crate) fn merge(self, other: Rollup) -> Rollup {
+        fn add((a, b): (f64, f64), (c, d): (f64, f64)) -> (f64, f64) {
+            (a + c, b + d)
+        }
+
+        match (self, other) {
+            (Rollup::Counter(x), Rollup::Counter(y)) => Rollup::Counter(x + y),
+            (Rollup::Rate(x), Rollup::Rate(y)) => Rollup::Rate(add(x, y)),
+            (Rollup::Bytes((x, xr)), Rollup::Bytes((y, yr))) => Rollup::Bytes((x + y, add(xr, yr))),
+            (Rollup::Ratio((xp, xt)), Rollup::Ratio((yp, yt))) => Rollup::Ratio((xp + yp, xt + yt)),
+            (Rollup::Histogram(x), Rollup::Histogram(y)) => {
+                Rollup::Histogram(HistogramDigest::merge([x, y]))
+            }
+            (Rollup::Trend((x, xs)), Rollup::Trend((y, ys))) => {
+                Rollup::Trend((HistogramDigest::merge([x, y]), xs.merge(&ys)))
+            }
+            (x, _) => x,
+        }
+    }
+
+    pub(crate) fn value(&self) -> MetricValue {
+        match self {
+            Rollup::Counter(x) => MetricValue::Counter(*x),
+            Rollup::Rate(x) => MetricValue::Rate(*x),
+            Rollup::Bytes(x) => MetricValue::Bytes(*x),
+            Rollup::Ratio(x) => MetricValue::Ratio(*x),
+            Rollup::Histogram(x) => x.value(),
+            Rollup::Trend((digest, stats)) => {
+                let trend = stats.trend(digest.percentiles());
+                if digest.is_duration() {
+                    MetricValue::DurationTrend(duration_trend(trend))
+                } else {
+                    MetricValue::Trend(trend)
+                }
+            }
+        }
+    }
+}
+
+/// Span of the rolling window view of metrics.
+pub const WINDOW: Duration = Duration::from_secs(10);
+
+/// Per second buckets of a metric, only seconds within [`WINDOW`] are kept.
+#[derive(Debug)]
+pub(crate) struct Buckets<T> {
+    start: Instant,
+    inner: Mutex<VecDeque<(u64, T)>>,
+}
+
+impl<T: Default> Buckets<T> {
+    pub(crate) fn new(start: Instant) -> Self {
+        Self {
+            start,
+            inner: Mutex::default(),
+        }
+    }
+
+    /// Update the bucket of the current second.
+    pub(crate) fn update(&self, f: impl FnOnce(&mut T)) {
+        let second = self.start.elapsed().as_secs();
+        let mut inner = self.inner.lock().unwrap();
+        if inner.back().is_none_or(|(x, _)| *x != second) {
+            inner.push_back((second, T::default()));
+        }
+        f(&mut inner.back_mut().unwrap().1);
+        while inner
+            .front()
+            .is_some_and(|(x, _)| *x + WINDOW.as_secs() <= second)
+        {
+            inner.pop_front();
+        }
+    }
+
+    /// Fold buckets within the window. Also returns the seconds covered by the window,
+    /// which is the current partial second and the full seconds before it.
+    pub(crate) fn fold<R>(&self, init: R, mut f: impl FnMut(R, &T) -> R) -> (R, f64) {
+        let elapsed = self.start.elapsed();
+        let from = (elapsed.as_secs() + 1).saturating_sub(WINDOW.as_secs());
+        let span = elapsed.as_secs_f64() - from as f64;
+        let res = self
+            .inner
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(x, _)| *x >= from)
+            .fold(init, |acc, (_, x)| f(acc, x));
+        (res, span)
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct Counter {
     pub(crate) value: AtomicU64,
+    window: Buckets<u64>,
 }
 
 impl Counter {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(start: Instant) -> Self {
         Counter {
             value: AtomicU64::new(0),
+            window: Buckets::new(start),
         }
     }
 
     pub(crate) fn add(&self, amount: u64) {
         self.value.fetch_add(amount, Ordering::Relaxed);
+        self.window.update(|x| *x += amount);
     }
 
     pub(crate) fn get(&self) -> u64 {
         self.value.load(Ordering::Relaxed)
     }
+
+    /// Amount added within the window.
+    pub(crate) fn get_window(&self) -> u64 {
+        self.window.fold(0, |acc, x| acc + x).0
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Rate {
+    start: Instant,
+    total: AtomicU64,
+    window: Buckets<u64>,
+}
+
+impl Rate {
+    pub(crate) fn new(start: Instant) -> Self {
+        Rate {
+            start,
+            total: AtomicU64::new(0),
+            window: Buckets::new(start),
+        }
+    }
+
+    pub(crate) fn add(&self, amount: u64) {
+        self.total.fetch_add(amount, Ordering::Relaxed);
+        self.window.update(|x| *x += amount);
+    }
+
+    /// Per second rate over the run and over the rolling window.
+    pub(crate) fn get(&self) -> (f64, f64) {
+        let secs = self.start.elapsed().as_secs_f64();
+        let total = if secs > 0. {
+            self.total.load(Ordering::Relaxed) as f64 / secs
+        } else {
+            0.
+        };
+
+        let (amount, span) = self.window.fold(0, |acc, x| acc + x);
+        let window = if span > 0. { amount as f64 / span } else { 0. };
+
+        (total, window)
+    }
+}
+
+/// Count, mean, variance, min and max of observed values.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Stats {
+    count: u64,
+    mean: f64,
+    /// sum of squared differences from the mean
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            mean: 0.,
+            m2: 0.,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl Stats {
+    // Welford's online algorithm, stable for long runs unlike sum of squares.
+    fn observe(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    fn merge(self, other: &Stats) -> Stats {
+        if other.count == 0 {
+            return self;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        Stats {
+            count,
+            mean: self.mean + delta * other.count as f64 / count as f64,
+            m2: self.m2
+                + other.m2
+                + delta * delta * (self.count as f64 * other.count as f64) / count as f64,
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    fn sum(&self) -> f64 {
+        self.mean * self.count as f64
+    }
+
+    /// (min, max), zero when nothing was observed.
+    fn min_max(&self) -> (f64, f64) {
+        if self.count == 0 {
+            (0., 0.)
+        } else {
+            (self.min, self.max)
+        }
+    }
+
+    fn trend(&self, percentiles: (f64, f64, f64, f64)) -> TrendValue<f64> {
+        if self.count == 0 {
+            return TrendValue {
+                count: 0,
+                mean: 0.,
+                stddev: 0.,
+                min: 0.,
+                max: 0.,
+                percentiles: (0., 0., 0., 0.),
+            };
+        }
+        TrendValue {
+            count: self.count,
+            mean: self.mean,
+            stddev: (self.m2 / self.count as f64).sqrt(),
+            min: self.min,
+            max: self.max,
+            percentiles,
+        }
+    }
+}
+
+/// Memory and accuracy trade off of histogram digests.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DigestConfig {
+    /// Raw values buffered before they are folded into the digest.
+    pub buffer_size: usize,
+    /// Maximum number of centroids kept by the digest.
+    pub compression: usize,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            buffer_size: 4096,
+            compression: 100,
+        }
+    }
+}
+
+impl DigestConfig {
+    fn digest(&self) -> TDigest {
+        TDigest::new_with_size(self.compression)
+    }
+}
+
+/// Values observed within one bucket of a histogram window.
+#[derive(Debug, Default)]
+pub(crate) struct Samples {
+    digest: Option<TDigest>,
+    buffer: Vec<f64>,
+    stats: Stats,
+}
+
+impl Samples {
+    fn observe(&mut self, value: f64, config: DigestConfig) {
+        self.stats.observe(value);
+        self.buffer.push(value);
+        if self.buffer.len() >= config.buffer_size {
+            let values = std::mem::take(&mut self.buffer);
+            let digest = self.digest.take().unwrap_or_else(|| config.digest());
+            self.digest = Some(digest.merge_unsorted(values));
+        }
+    }
+
+    fn digest(&self, config: DigestConfig) -> TDigest {
+        let digest = self.digest.clone().unwrap_or_else(|| config.digest());
+        if self.buffer.is_empty() {
+            digest
+        } else {
+            digest.merge_unsorted(self.buffer.clone())
+        }
+    }
+}
+
+/// Histogram that also tracks mean, standard deviation, min and max of observed values.
+#[derive(Debug)]
+pub(crate) struct Trend {
+    histogram: Histogram,
+    stats: Mutex<Stats>,
+}
+
+impl Trend {
+    pub(crate) fn new(start: Instant, config: DigestConfig) -> Self {
+        Trend {
+            histogram: Histogram::new(start, config),
+            stats: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn observe(&self, value: f64) {
+        self.histogram.observe(value);
+        self.stats.lock().unwrap().observe(value);
+    }
+
+    pub(crate) fn get(&self) -> TrendValue<f64> {
+        let stats = *self.stats.lock().unwrap();
+        stats.trend(self.histogram.get_percentiles())
+    }
+
+    pub(crate) fn get_window(&self) -> TrendValue<f64> {
+        let (percentiles, stats) = self.histogram.get_window();
+        stats.trend(percentiles)
+    }
 }
 
 #[derive(Debug)]
@@ -272,33 +894,60 @@ impl<T: bytemuck::NoUninit + Default> Gauge<T> {
     }
 }
 
+/// Digest, values not folded into it yet, sum and (min, max) of all values.
+type HistogramState = (Option<TDigest>, Vec<OrderedFloat<f64>>, f64, (f64, f64));
+
 #[derive(Debug)]
 pub(crate) struct Histogram {
-    inner: Mutex<(Option<TDigest>, Vec<OrderedFloat<f64>>, f64)>,
+    inner: Mutex<HistogramState>,
+    window: Buckets<Samples>,
+    config: DigestConfig,
 }
 
 impl Histogram {
-    fn new() -> Self {
+    fn new(start: Instant, config: DigestConfig) -> Self {
         Self {
-            inner: Mutex::new((None, Vec::default(), 0.)),
+            inner: Mutex::new((None, Vec::default(), 0., (f64::INFINITY, f64::NEG_INFINITY))),
+            window: Buckets::new(start),
+            config,
         }
     }
 
     fn observe(&self, value: f64) {
         let mut inner = self.inner.lock().unwrap();
         inner.1.push(OrderedFloat(value));
-        if inner.1.len() >= 4096 {
+        if inner.1.len() >= self.config.buffer_size {
             let values = std::mem::take(&mut inner.1);
             let values = values.into_iter().map(|x| x.0).collect();
-            if let Some(tdigest) = inner.0.as_mut() {
-                tdigest.merge_unsorted(values);
-            } else {
-                let tdigest = TDigest::default();
-                tdigest.merge_unsorted(values);
-                inner.0 = Some(tdigest)
-            }
+            let tdigest = inner.0.take().unwrap_or_else(|| self.config.digest());
+            inner.0 = Some(tdigest.merge_unsorted(values));
         }
         inner.2 += value;
+        inner.3 = (inner.3 .0.min(value), inner.3 .1.max(value));
+        drop(inner);
+        self.window.update(|x| x.observe(value, self.config));
+    }
+
+    /// Percentiles and stats of values observed within the window.
+    fn get_window(&self) -> ((f64, f64, f64, f64), Stats) {
+        let ((digests, stats), _) =
+            self.window
+                .fold((Vec::new(), Stats::default()), |(mut digests, stats), x| {
+                    digests.push(x.digest(self.config));
+                    (digests, stats.merge(&x.stats))
+                });
+        let digest = TDigest::merge_digests(digests);
+        let percentiles = if digest.is_empty() {
+            (0., 0., 0., 0.)
+        } else {
+            (
+                digest.estimate_quantile(0.5),
+                digest.estimate_quantile(0.9),
+                digest.estimate_quantile(0.95),
+                digest.estimate_quantile(0.99),
+            )
+        };
+        (percentiles, stats)
     }
 
     fn get_percentile(&self, u: usize, l: usize) -> f64 {
@@ -306,6 +955,8 @@ impl Histogram {
         if let Some(tdigest) = &lock.0 {
             let quantile = u as f64 / l as f64;
             tdigest.estimate_quantile(quantile)
+        } else if lock.1.is_empty() {
+            0.
         } else {
             let index = (lock.1.len() * u) / l;
             lock.1.sort_unstable();
@@ -322,7 +973,269 @@ impl Histogram {
         )
     }
 
+    fn count(&self) -> u64 {
+        let inner = self.inner.lock().unwrap();
+        let folded = inner.0.as_ref().map_or(0., |x| x.count());
+        folded as u64 + inner.1.len() as u64
+    }
+
     fn get_sum(&self) -> f64 {
         self.inner.lock().unwrap().2
     }
+
+    /// (min, max) of all values, zero when nothing was observed.
+    fn get_min_max(&self) -> (f64, f64) {
+        let (min, max) = self.inner.lock().unwrap().3;
+        if min > max {
+            (0., 0.)
+        } else {
+            (min, max)
+        }
+    }
+
+    /// Copy of the digest including buffered values. The buffer is left in place, so exact
+    /// percentiles are kept until it fills up.
+    fn digest(&self, duration: bool) -> HistogramDigest {
+        let inner = self.inner.lock().unwrap();
+        let digest = inner.0.clone().unwrap_or_else(|| self.config.digest());
+        let digest = if inner.1.is_empty() {
+            digest
+        } else {
+            digest.merge_unsorted(inner.1.iter().map(|x| x.0).collect())
+        };
+        HistogramDigest {
+            digest,
+            sum: inner.2,
+            duration,
+        }
+    }
+
+    fn merge(&self, other: HistogramDigest) {
+        let mut inner = self.inner.lock().unwrap();
+        let values = std::mem::take(&mut inner.1);
+        let values = values.into_iter().map(|x| x.0).collect();
+        let tdigest = inner
+            .0
+            .take()
+            .unwrap_or_else(|| self.config.digest())
+            .merge_unsorted(values);
+        if other.count() > 0 {
+            inner.3 = (
+                inner.3 .0.min(other.digest.min()),
+                inner.3 .1.max(other.digest.max()),
+            );
+        }
+        inner.0 = Some(TDigest::merge_digests(vec![tdigest, other.digest]));
+        inner.2 += other.sum;
+    }
+}
+
+/// Mergeable state of a histogram metric.
+///
+/// Percentiles of separate runs or worker processes can not be averaged into
+/// a meaningful value. Digests however can be merged, producing accurate global
+/// percentiles. With `serde` feature enabled a digest can be serialized to be
+/// shipped across processes.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HistogramDigest {
+    digest: TDigest,
+    sum: f64,
+    /// values are durations stored as nanos.
+    duration: bool,
+}
+
+impl HistogramDigest {
+    /// Merge all digests into one. Digests of float and duration histograms should not be mixed.
+    pub fn merge(digests: impl IntoIterator<Item = HistogramDigest>) -> Self {
+        let mut sum = 0.;
+        let mut duration = false;
+        let digests = digests
+            .into_iter()
+            .map(|x| {
+                sum += x.sum;
+                duration |= x.duration;
+                x.digest
+            })
+            .collect();
+        Self {
+            digest: TDigest::merge_digests(digests),
+            sum,
+            duration,
+        }
+    }
+
+    /// Number of values observed.
+    pub fn count(&self) -> u64 {
+        self.digest.count() as u64
+    }
+
+    /// Sum of all the values observed.
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Returns true if values are durations stored as nanos.
+    pub fn is_duration(&self) -> bool {
+        self.duration
+    }
+
+    /// Estimate value at quantile `q` where `q` is between 0 and 1.
+    pub fn quantile(&self, q: f64) -> f64 {
+        self.digest.estimate_quantile(q)
+    }
+
+    fn percentiles(&self) -> (f64, f64, f64, f64) {
+        if self.digest.is_empty() {
+            return (0., 0., 0., 0.);
+        }
+        (
+            self.quantile(0.5),
+            self.quantile(0.9),
+            self.quantile(0.95),
+            self.quantile(0.99),
+        )
+    }
+
+    /// Smallest and largest value observed, zero when nothing was observed.
+    pub fn min_max(&self) -> (f64, f64) {
+        if self.count() == 0 {
+            (0., 0.)
+        } else {
+            (self.digest.min(), self.digest.max())
+        }
+    }
+
+    /// Convert to a metric value with same percentiles as a live histogram.
+    pub fn value(&self) -> MetricValue {
+        let percentiles = self.percentiles();
+        if self.duration {
+            duration_histogram(percentiles, self.sum, self.min_max())
+        } else {
+            MetricValue::Histogram((percentiles, self.sum, self.min_max()))
+        }
+    }
+}
+
+fn duration_histogram(
+    (p50, p90, p95, p99): (f64, f64, f64, f64),
+    sum: f64,
+    (min, max): (f64, f64),
+) -> MetricValue {
+    MetricValue::DurationHistogram((
+        (
+            nanos_to_duration(p50),
+            nanos_to_duration(p90),
+            nanos_to_duration(p95),
+            nanos_to_duration(p99),
+        ),
+        nanos_to_duration(sum),
+        (nanos_to_duration(min), nanos_to_duration(max)),
+    ))
+}
+
+fn duration_trend(value: TrendValue<f64>) -> TrendValue<Duration> {
+    let (p50, p90, p95, p99) = value.percentiles;
+    TrendValue {
+        count: value.count,
+        mean: nanos_to_duration(value.mean),
+        stddev: nanos_to_duration(value.stddev),
+        min: nanos_to_duration(value.min),
+        max: nanos_to_duration(value.max),
+        percentiles: (
+            nanos_to_duration(p50),
+            nanos_to_duration(p90),
+            nanos_to_duration(p95),
+            nanos_to_duration(p99),
+        ),
+    }
+}
+
+/// Float casts saturate, negative and NaN values become zero.
+fn nanos_to_duration(f: f64) -> Duration {
+    Duration::from_nanos(f as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{format_bytes, DigestConfig, Histogram, HistogramDigest, Rate, Stats, Trend};
+
+    #[test]
+    fn merged_digest_percentiles() {
+        let first = Histogram::new(Instant::now(), DigestConfig::default());
+        let second = Histogram::new(Instant::now(), DigestConfig::default());
+        for x in 0..5000 {
+            first.observe(x as f64);
+            second.observe((x + 5000) as f64);
+        }
+
+        let merged = HistogramDigest::merge([first.digest(false), second.digest(false)]);
+        // Taking a digest does not fold the buffer, values below the buffer size stay exact.
+        assert_eq!(first.inner.lock().unwrap().1.len(), 904);
+        assert_eq!(merged.count(), 10000);
+        assert_eq!(merged.sum(), (0..10000).sum::<u64>() as f64);
+        assert!((merged.quantile(0.5) - 5000.).abs() < 100.);
+        assert!((merged.quantile(0.99) - 9900.).abs() < 100.);
+
+        first.merge(second.digest(false));
+        assert!((first.get_percentile(1, 2) - 5000.).abs() < 100.);
+    }
+
+    #[test]
+    fn rate_over_run_and_window() {
+        let start = Instant::now() - Duration::from_secs(20);
+        let rate = Rate::new(start);
+        rate.add(150);
+        rate.add(50);
+
+        let (total, window) = rate.get();
+        // 200 over the 20 seconds of the run, all of it within the last ~9 seconds of the window.
+        assert!((total - 10.).abs() < 0.1);
+        assert!(window > 20. && window < 23.);
+    }
+
+    #[test]
+    fn human_readable_bytes() {
+        assert_eq!(format_bytes(512.), "512 B");
+        assert_eq!(format_bytes(1536.), "1.50 KiB");
+        assert_eq!(format_bytes(3. * 1024. * 1024. * 1024.), "3.00 GiB");
+    }
+
+    #[test]
+    fn trend_stats() {
+        let trend = Trend::new(Instant::now(), DigestConfig::default());
+        for x in [2., 4., 4., 4., 5., 5., 7., 9.] {
+            trend.observe(x);
+        }
+
+        let value = trend.get();
+        assert_eq!(value.count, 8);
+        assert_eq!(value.mean, 5.);
+        assert_eq!(value.stddev, 2.);
+        assert_eq!(value.min, 2.);
+        assert_eq!(value.max, 9.);
+    }
+
+    #[test]
+    fn merged_stats() {
+        let mut all = Stats::default();
+        let mut first = Stats::default();
+        let mut second = Stats::default();
+        for x in 0..100 {
+            all.observe(x as f64);
+            if x < 30 {
+                first.observe(x as f64)
+            } else {
+                second.observe(x as f64)
+            }
+        }
+
+        let merged = first.merge(&second);
+        assert_eq!(merged.count, all.count);
+        assert!((merged.mean - all.mean).abs() < 1e-9);
+        assert!((merged.m2 - all.m2).abs() < 1e-6);
+        assert_eq!((merged.min, merged.max), (0., 99.));
+    }
 }
@@ -1,6 +1,18 @@
+pub mod choice;
+pub mod retry;
+pub mod sequence;
+pub mod think_time;
+pub mod timeout;
+
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
 use futures::Future;
+use tracing::Instrument;
 
-use crate::{data::RuntimeDataStore, error::Error, UserResult};
+use crate::{data::RuntimeDataStore, error::Error, UserResult, USER_TASK};
 
 /// The `User` trait defines the fundamental component of this library.
 /// A `User` represents a state coupled with an asynchronous function that can be executed asynchronously.
@@ -16,7 +28,31 @@ use crate::{data::RuntimeDataStore, error::Error, UserResult};
 /// ### Note
 /// A concrete implementation of the `User` trait can have references to data from a [RuntimeDataStore]  
 pub trait User: Send {
+    /// Run one iteration of the user. An error fails the iteration, which is counted while the
+    /// scenario goes on, only an [`Error::TerminationError`] ends the scenario.
     fn call(&mut self) -> impl std::future::Future<Output = UserResult> + std::marker::Send;
+
+    /// Called once before the first iteration of the user, when the executor schedules it rather
+    /// than when it is built, such as to log in or open a session. It does not count toward the
+    /// duration of the iteration. Does nothing by default.
+    fn start(&mut self) -> impl std::future::Future<Output = ()> + std::marker::Send {
+        async {}
+    }
+
+    /// Called between two iterations of the user by executors running it in a loop, outside of
+    /// the duration of the iterations. Arrival rate executors schedule iterations on their own
+    /// and never call it. Does nothing by default, see [`UserExt::with_think_time`].
+    fn think(&mut self) -> impl std::future::Future<Output = ()> + std::marker::Send {
+        async {}
+    }
+
+    /// Called once the executor is done with the user, when its scenario ends or the run is cut
+    /// short, to clean up connections, sessions and temporary resources created by the builder.
+    /// Ramping user executors also stop the users they no longer run when scaled down, and
+    /// [`start`](Self::start) them again if they are scaled back up. Does nothing by default.
+    fn stop(&mut self) -> impl std::future::Future<Output = ()> + std::marker::Send {
+        async {}
+    }
 }
 
 impl<F, Fut> User for F
@@ -29,6 +65,135 @@ where
     }
 }
 
+/// Synchronous counterpart of [`User`] for clients without an async API, such as legacy
+/// database drivers or C libraries behind FFI. Wrapped in [`Blocking`], every call runs on the
+/// blocking thread pool of tokio, so that it does not stall the executors.
+///
+/// Metrics and groups recorded within a call are attributed to its iteration as they are for
+/// an async user.
+///
+/// ```no_run
+/// # struct Connection;
+/// # impl Connection { fn query(&mut self, _: &str) {} }
+/// use rusher::prelude::*;
+/// use rusher::user::{Blocking, BlockingUser};
+///
+/// struct DbUser {
+///     conn: Connection,
+/// }
+///
+/// impl BlockingUser for DbUser {
+///     fn call(&mut self) -> UserResult {
+///         self.conn.query("SELECT 1");
+///         Ok(())
+///     }
+/// }
+///
+/// async fn user_builder(_: &RuntimeDataStore) -> impl User {
+///     Blocking::new(DbUser { conn: Connection })
+/// }
+/// ```
+pub trait BlockingUser: Send + 'static {
+    fn call(&mut self) -> UserResult;
+
+    /// See [`User::start`].
+    fn start(&mut self) {}
+
+    /// See [`User::stop`].
+    fn stop(&mut self) {}
+}
+
+/// [`User`] running a [`BlockingUser`] on the blocking thread pool.
+///
+/// A call cut short at the end of an executor keeps running on its thread until it returns,
+/// the next use of the user waits for it.
+pub struct Blocking<U> {
+    user: Arc<Mutex<U>>,
+}
+
+impl<U: BlockingUser> Blocking<U> {
+    pub fn new(user: U) -> Self {
+        Self {
+            user: Arc::new(Mutex::new(user)),
+        }
+    }
+
+    /// Run `f` with the user on a blocking thread, within the span of the caller.
+    async fn run<T: Send + 'static>(&self, f: impl FnOnce(&mut U) -> T + Send + 'static) -> T {
+        let user = self.user.clone();
+        let span = tracing::Span::current();
+        let task = tokio::task::spawn_blocking(move || span.in_scope(|| f(&mut lock(&user))));
+        match task.await {
+            Ok(res) => res,
+            Err(err) => std::panic::resume_unwind(err.into_panic()),
+        }
+    }
+}
+
+/// Lock `user`, a call that panicked does not keep it from being used again.
+fn lock<U>(user: &Mutex<U>) -> MutexGuard<'_, U> {
+    user.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+impl<U: BlockingUser> User for Blocking<U> {
+    async fn call(&mut self) -> UserResult {
+        self.run(U::call).await
+    }
+
+    async fn start(&mut self) {
+        self.run(U::start).await
+    }
+
+    async fn stop(&mut self) {
+        self.run(U::stop).await
+    }
+}
+
+/// Combinators of every [`User`].
+pub trait UserExt: User + Sized {
+    /// Call the user up to `retries` more times when an iteration fails, see [`Retry`](retry::Retry).
+    fn with_retries(self, retries: usize) -> retry::Retry<Self> {
+        retry::Retry::new(self, retries)
+    }
+
+    /// Fail calls of the user taking longer than `timeout`, see [`Timeout`](timeout::Timeout).
+    fn with_timeout(self, timeout: Duration) -> timeout::Timeout<Self> {
+        timeout::Timeout::new(self, timeout)
+    }
+
+    /// Pause between iterations of the user for a time drawn from `think_time`, see
+    /// [`Thinking`](think_time::Thinking).
+    fn with_think_time(self, think_time: think_time::ThinkTime) -> think_time::Thinking<Self> {
+        think_time::Thinking::new(self, think_time)
+    }
+}
+
+impl<U: User> UserExt for U {}
+
+/// Run a step of a user flow inside a named group.
+/// Duration of the group is recorded in the [`GROUP_DURATION`](crate::tracing::GROUP_DURATION) histogram
+/// with the name in a [`GROUP`](crate::tracing::GROUP) attribute, and metrics emitted within it carry the same attribute.
+/// Nested groups inherit the name of their parent, joined with `::`.
+///
+/// ```no_run
+/// # async fn login() {}
+/// # async fn add_to_cart() {}
+/// # async fn call() {
+/// use rusher::user::group;
+///
+/// group("checkout", async {
+///     group("login", login()).await; // group="checkout::login"
+///     group("cart", add_to_cart()).await;
+/// })
+/// .await;
+/// # }
+/// ```
+pub async fn group<F: Future>(name: &str, future: F) -> F::Output {
+    let span =
+        tracing::span!(target: USER_TASK, tracing::Level::INFO, "group_duration", group = name);
+    future.instrument(span).await
+}
+
 /// Builds a user instance asynchronously.
 /// The type implementing this should also implement Sync as this is shared across runtime executors.
 /// Runtime executors given the type and configuration can request more user in middle of execution.  
@@ -61,7 +226,7 @@ where
 mod tests {
     use crate::{
         data::RuntimeDataStore,
-        user::{AsyncUserBuilder, User},
+        user::{AsyncUserBuilder, Blocking, BlockingUser, User},
         UserResult,
     };
 
@@ -88,4 +253,34 @@ mod tests {
 
         let _ = futures::executor::block_on(AsyncUserBuilder::build(&user_builder, &store));
     }
+
+    struct CountingUser {
+        calls: usize,
+        stopped: bool,
+    }
+
+    impl BlockingUser for CountingUser {
+        fn call(&mut self) -> UserResult {
+            self.calls += 1;
+            Ok(())
+        }
+
+        fn stop(&mut self) {
+            self.stopped = true;
+        }
+    }
+
+    #[tokio::test]
+    async fn blocking_user() {
+        let mut user = Blocking::new(CountingUser {
+            calls: 0,
+            stopped: false,
+        });
+        user.call().await.unwrap();
+        user.call().await.unwrap();
+        user.stop().await;
+        let user = user.user.lock().unwrap();
+        assert_eq!(user.calls, 2);
+        assert!(user.stopped);
+    }
 }
@@ -0,0 +1,171 @@
+//! Traffic mix within a single executor, each iteration running one of several behaviors picked
+//! at random according to their weights.
+//!
+//! ```no_run
+//! use rusher::prelude::*;
+//! use rusher::user::choice::WeightedChoice;
+//!
+//! async fn user_builder(_: &RuntimeDataStore) -> impl User {
+//!     let browse = || async { Ok(()) };
+//!     let checkout = || async { Ok(()) };
+//!     WeightedChoice::new()
+//!         .behavior("browse", 9, browse)
+//!         .behavior("checkout", 1, checkout)
+//! }
+//! ```
+
+use std::{borrow::Cow, pin::Pin};
+
+use futures::Future;
+use rand::Rng;
+use tracing::Instrument;
+
+use crate::{error::Error, UserResult, USER_TASK};
+
+use super::User;
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Object safe [`User`], so that behaviors of different types can be mixed.
+trait DynUser: Send {
+    fn call(&mut self) -> BoxFuture<'_, UserResult>;
+    fn start(&mut self) -> BoxFuture<'_, ()>;
+    fn think(&mut self) -> BoxFuture<'_, ()>;
+    fn stop(&mut self) -> BoxFuture<'_, ()>;
+}
+
+impl<U: User> DynUser for U {
+    fn call(&mut self) -> BoxFuture<'_, UserResult> {
+        Box::pin(User::call(self))
+    }
+
+    fn start(&mut self) -> BoxFuture<'_, ()> {
+        Box::pin(User::start(self))
+    }
+
+    fn think(&mut self) -> BoxFuture<'_, ()> {
+        Box::pin(User::think(self))
+    }
+
+    fn stop(&mut self) -> BoxFuture<'_, ()> {
+        Box::pin(User::stop(self))
+    }
+}
+
+struct Behavior<'a> {
+    name: Cow<'static, str>,
+    weight: u32,
+    user: Box<dyn DynUser + 'a>,
+}
+
+/// [`User`] running one of its behaviors in every iteration, each picked with a probability
+/// proportional to its weight.
+///
+/// The call of the behavior is recorded in the
+/// [`BEHAVIOR_DURATION`](crate::tracing::BEHAVIOR_DURATION) histogram and metrics emitted
+/// within it carry its name in a [`BEHAVIOR`](crate::tracing::BEHAVIOR) attribute. Every
+/// behavior is started and stopped along with the user, [`User::think`] is forwarded to the
+/// behavior of the last iteration.
+#[derive(Default)]
+pub struct WeightedChoice<'a> {
+    behaviors: Vec<Behavior<'a>>,
+    /// Index of the behavior of the last iteration.
+    last: Option<usize>,
+}
+
+impl<'a> WeightedChoice<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a behavior named `name` picked `weight` times out of the sum of all weights.
+    pub fn behavior(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        weight: u32,
+        user: impl User + 'a,
+    ) -> Self {
+        self.behaviors.push(Behavior {
+            name: name.into(),
+            weight,
+            user: Box::new(user),
+        });
+        self
+    }
+
+    fn choose(&self, rng: &mut impl Rng) -> Option<usize> {
+        let total: u32 = self.behaviors.iter().map(|x| x.weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut pick = rng.gen_range(0..total);
+        self.behaviors
+            .iter()
+            .position(|x| match pick.checked_sub(x.weight) {
+                Some(rest) => {
+                    pick = rest;
+                    false
+                }
+                None => true,
+            })
+    }
+}
+
+impl<'a> User for WeightedChoice<'a> {
+    async fn call(&mut self) -> UserResult {
+        let Some(index) = self.choose(&mut rand::thread_rng()) else {
+            return Err(Error::new("no behavior with a weight to choose from"));
+        };
+        self.last = Some(index);
+        let behavior = &mut self.behaviors[index];
+        let span = tracing::span!(target: USER_TASK, tracing::Level::INFO, "behavior_duration", behavior = behavior.name.as_ref());
+        behavior.user.call().instrument(span).await
+    }
+
+    async fn start(&mut self) {
+        futures::future::join_all(self.behaviors.iter_mut().map(|x| x.user.start())).await;
+    }
+
+    async fn think(&mut self) {
+        if let Some(index) = self.last {
+            self.behaviors[index].user.think().await
+        }
+    }
+
+    async fn stop(&mut self) {
+        futures::future::join_all(self.behaviors.iter_mut().map(|x| x.user.stop())).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WeightedChoice;
+    use crate::user::User;
+
+    #[tokio::test]
+    async fn choose_by_weight() {
+        let (mut first, mut second, mut never) = (0, 0, 0);
+        let mut user = WeightedChoice::new()
+            .behavior("first", 3, || {
+                first += 1;
+                async { Ok(()) }
+            })
+            .behavior("never", 0, || {
+                never += 1;
+                async { Ok(()) }
+            })
+            .behavior("second", 1, || {
+                second += 1;
+                async { Ok(()) }
+            });
+        for _ in 0..4000 {
+            user.call().await.unwrap();
+        }
+        drop(user);
+        assert_eq!(never, 0);
+        assert!((2700..3300).contains(&first), "{first}");
+        assert_eq!(first + second, 4000);
+
+        assert!(WeightedChoice::new().call().await.is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! Retry failed iterations of a user, so that flaky transport errors do not dominate the
+//! failure rate of runs where retries are acceptable.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use rusher::prelude::*;
+//! use rusher::user::{retry::Backoff, UserExt};
+//!
+//! async fn user_builder(_: &RuntimeDataStore) -> impl User {
+//!     let user = || async { Ok(()) };
+//!     user.with_retries(3)
+//!         .backoff(Backoff::exponential(Duration::from_millis(100)))
+//!         .retry_if(|err| err.class() == "timeout")
+//! }
+//! ```
+
+use std::time::Duration;
+
+use tracing::{event, Level};
+
+use crate::{error::Error, UserResult, USER_TASK};
+
+use super::User;
+
+/// Delay before each retry of a failed iteration.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Backoff {
+    /// Retry right away.
+    #[default]
+    None,
+    /// Wait the same time before every retry.
+    Fixed(Duration),
+    /// Wait `initial` before the first retry, doubled for every following one up to `max`.
+    Exponential { initial: Duration, max: Duration },
+}
+
+impl Backoff {
+    /// Exponential backoff starting at `initial`, up to 10s.
+    pub fn exponential(initial: Duration) -> Self {
+        Self::Exponential {
+            initial,
+            max: Duration::from_secs(10),
+        }
+    }
+
+    /// Delay before the `retry`th retry, starting at 1.
+    fn delay(&self, retry: usize) -> Duration {
+        match *self {
+            Backoff::None => Duration::ZERO,
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { initial, max } => {
+                let factor = 1u32.checked_shl(retry as u32 - 1).unwrap_or(u32::MAX);
+                initial.saturating_mul(factor).min(max)
+            }
+        }
+    }
+}
+
+/// [`User`] calling the wrapped user again when an iteration fails, created with
+/// [`UserExt::with_retries`](super::UserExt::with_retries).
+///
+/// Only the outcome of the last attempt is counted as the result of the iteration, whose
+/// duration spans every attempt and the backoff between them. Each retry is added to the
+/// [`RETRIES_TOTAL`](crate::tracing::RETRIES_TOTAL) counter. Termination errors are never
+/// retried.
+pub struct Retry<U> {
+    user: U,
+    retries: usize,
+    backoff: Backoff,
+    retry_if: Option<fn(&Error) -> bool>,
+}
+
+impl<U> Retry<U> {
+    pub(super) fn new(user: U, retries: usize) -> Self {
+        Self {
+            user,
+            retries,
+            backoff: Backoff::None,
+            retry_if: None,
+        }
+    }
+
+    /// Wait according to `backoff` before every retry.
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Only retry errors for which `retry_if` returns true, such as errors of a given
+    /// [`class`](Error::class).
+    pub fn retry_if(mut self, retry_if: fn(&Error) -> bool) -> Self {
+        self.retry_if = Some(retry_if);
+        self
+    }
+
+    fn retries(&self, err: &Error) -> bool {
+        !err.is_termination_err() && self.retry_if.is_none_or(|x| x(err))
+    }
+}
+
+impl<U: User> User for Retry<U> {
+    async fn call(&mut self) -> UserResult {
+        let mut retry = 0;
+        loop {
+            match self.user.call().await {
+                Err(err) if retry < self.retries && self.retries(&err) => {
+                    retry += 1;
+                    event!(name: "retries_total.counter", target: USER_TASK, Level::INFO, value = 1u64, class = err.class());
+                    tokio::time::sleep(self.backoff.delay(retry)).await;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    async fn start(&mut self) {
+        self.user.start().await
+    }
+
+    async fn think(&mut self) {
+        self.user.think().await
+    }
+
+    async fn stop(&mut self) {
+        self.user.stop().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use super::Backoff;
+    use crate::{error::Error, prelude::*, user::UserExt};
+
+    #[tokio::test]
+    async fn retry_failed_iterations() {
+        let mut calls = 0;
+        let mut user = (|| {
+            calls += 1;
+            let res = if calls < 3 {
+                Err(Error::new("reset").tagged("io"))
+            } else {
+                Ok(())
+            };
+            async move { res }
+        })
+        .with_retries(2);
+        assert!(user.call().await.is_ok());
+
+        let mut user = (|| async { Err(Error::termination("stop")) }).with_retries(2);
+        assert!(user.call().await.unwrap_err().is_termination_err());
+
+        let mut user = (|| async { Err(Error::new("bad request")) })
+            .with_retries(2)
+            .retry_if(|err| err.class() == "io");
+        assert!(user.call().await.is_err());
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let backoff = Backoff::exponential(Duration::from_millis(100));
+        assert_eq!(backoff.delay(1), Duration::from_millis(100));
+        assert_eq!(backoff.delay(3), Duration::from_millis(400));
+        assert_eq!(backoff.delay(40), Duration::from_secs(10));
+    }
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    async fn user_builder(_: &RuntimeDataStore) -> impl User {
+        (|| async {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Err(Error::new("reset"))
+        })
+        .with_retries(1)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn exhausted_retries_do_not_stop_executor() {
+        let execution = Execution::builder()
+            .with_user_builder(user_builder)
+            .with_executor(Executor::Constant {
+                users: 1,
+                duration: Duration::from_millis(200),
+            });
+        let runner = Runner::new(vec![Scenario::new("scenario", execution)]).print_summary(false);
+        assert!(runner.run().await.is_ok());
+        assert!(CALLS.load(Ordering::Relaxed) > 5);
+    }
+}
@@ -0,0 +1,166 @@
+//! User flow made of named steps sharing a state, such as login, browse and checkout, each
+//! step timed in a [`group`](super::group) of its name.
+//!
+//! ```no_run
+//! use rusher::prelude::*;
+//! use rusher::user::sequence::{Sequence, StepFailure};
+//!
+//! #[derive(Default)]
+//! struct Session {
+//!     token: Option<String>,
+//! }
+//!
+//! async fn login(session: &mut Session) -> UserResult {
+//!     session.token = Some("token".to_string());
+//!     Ok(())
+//! }
+//!
+//! async fn browse(session: &mut Session) -> UserResult {
+//!     Ok(())
+//! }
+//!
+//! async fn checkout(session: &mut Session) -> UserResult {
+//!     Ok(())
+//! }
+//!
+//! async fn user_builder(_: &RuntimeDataStore) -> impl User {
+//!     Sequence::new(Session::default())
+//!         .step("login", login)
+//!         .step("browse", browse)
+//!         .step("checkout", checkout)
+//!         .on_failure(StepFailure::Abort)
+//! }
+//! ```
+
+use std::{borrow::Cow, pin::Pin};
+
+use futures::Future;
+
+use crate::UserResult;
+
+use super::{group, User};
+
+/// Step of a [`Sequence`], implemented by async functions taking the state of the sequence
+/// such as `async fn login(session: &mut Session) -> UserResult`.
+pub trait Step<S>: Send {
+    fn call<'a>(
+        &'a mut self,
+        state: &'a mut S,
+    ) -> Pin<Box<dyn Future<Output = UserResult> + Send + 'a>>;
+}
+
+impl<S, F> Step<S> for F
+where
+    F: for<'a> async_fn_traits::AsyncFnMut1<&'a mut S, Output = UserResult> + Send,
+    for<'a> <F as async_fn_traits::AsyncFnMut1<&'a mut S>>::OutputFuture: Send,
+{
+    fn call<'a>(
+        &'a mut self,
+        state: &'a mut S,
+    ) -> Pin<Box<dyn Future<Output = UserResult> + Send + 'a>> {
+        Box::pin(self(state))
+    }
+}
+
+/// What a [`Sequence`] does when one of its steps fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StepFailure {
+    /// Skip the following steps, the iteration fails with the error of the step.
+    #[default]
+    Abort,
+    /// Run the following steps anyway, the iteration fails with the error of the first step
+    /// that failed. Termination errors still abort.
+    Continue,
+}
+
+/// [`User`] running its steps one after the other in every iteration.
+///
+/// Each step runs in a [`group`] named after it, so its duration is recorded in the
+/// [`GROUP_DURATION`](crate::tracing::GROUP_DURATION) histogram.
+pub struct Sequence<S> {
+    state: S,
+    steps: Vec<(Cow<'static, str>, Box<dyn Step<S>>)>,
+    on_failure: StepFailure,
+}
+
+impl<S: Send> Sequence<S> {
+    /// Sequence without steps, sharing `state` between them.
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            steps: Vec::new(),
+            on_failure: StepFailure::default(),
+        }
+    }
+
+    /// Add a step named `name` run after the ones added before.
+    pub fn step(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        step: impl Step<S> + 'static,
+    ) -> Self {
+        self.steps.push((name.into(), Box::new(step)));
+        self
+    }
+
+    /// What to do when a step fails, [`StepFailure::Abort`] by default.
+    pub fn on_failure(mut self, on_failure: StepFailure) -> Self {
+        self.on_failure = on_failure;
+        self
+    }
+}
+
+impl<S: Send> User for Sequence<S> {
+    async fn call(&mut self) -> UserResult {
+        let mut failed = None;
+        for (name, step) in &mut self.steps {
+            match group(name, step.call(&mut self.state)).await {
+                Ok(()) => (),
+                Err(err)
+                    if self.on_failure == StepFailure::Continue && !err.is_termination_err() =>
+                {
+                    failed.get_or_insert(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        failed.map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sequence, StepFailure};
+    use crate::{error::Error, user::User, UserResult};
+
+    async fn login(steps: &mut Vec<&'static str>) -> UserResult {
+        steps.push("login");
+        Ok(())
+    }
+
+    async fn browse(steps: &mut Vec<&'static str>) -> UserResult {
+        steps.push("browse");
+        Err(Error::new("not found"))
+    }
+
+    async fn checkout(steps: &mut Vec<&'static str>) -> UserResult {
+        steps.push("checkout");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn step_failure() {
+        for (on_failure, steps) in [
+            (StepFailure::Abort, vec!["login", "browse"]),
+            (StepFailure::Continue, vec!["login", "browse", "checkout"]),
+        ] {
+            let mut user = Sequence::new(Vec::new())
+                .step("login", login)
+                .step("browse", browse)
+                .step("checkout", checkout)
+                .on_failure(on_failure);
+            assert!(user.call().await.is_err());
+            assert_eq!(user.state, steps);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Pause between the iterations of a user, to simulate the time a person takes to read a page
+//! or fill a form in a closed model without sleeping within calls, which would add to the
+//! duration of iterations.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use rusher::prelude::*;
+//! use rusher::user::{think_time::ThinkTime, UserExt};
+//!
+//! async fn user_builder(_: &RuntimeDataStore) -> impl User {
+//!     let user = || async { Ok(()) };
+//!     user.with_think_time(ThinkTime::uniform(Duration::from_secs(1)..Duration::from_secs(3)))
+//! }
+//! ```
+
+use std::{ops::Range, time::Duration};
+
+use rand::Rng;
+
+use crate::UserResult;
+
+use super::User;
+
+/// Distribution the time between two iterations is drawn from.
+#[derive(Debug, Clone)]
+pub enum ThinkTime {
+    /// Always the same time.
+    Constant(Duration),
+    /// Any time within the range, equally likely.
+    Uniform(Range<Duration>),
+    /// Time between events occurring at random with the given mean, mostly short pauses with
+    /// a few long ones.
+    Exponential { mean: Duration },
+}
+
+impl ThinkTime {
+    pub fn constant(duration: Duration) -> Self {
+        Self::Constant(duration)
+    }
+
+    pub fn uniform(range: Range<Duration>) -> Self {
+        Self::Uniform(range)
+    }
+
+    pub fn exponential(mean: Duration) -> Self {
+        Self::Exponential { mean }
+    }
+
+    fn sample(&self, rng: &mut impl Rng) -> Duration {
+        match self {
+            ThinkTime::Constant(duration) => *duration,
+            ThinkTime::Uniform(range) if range.is_empty() => range.start,
+            ThinkTime::Uniform(range) => rng.gen_range(range.clone()),
+            ThinkTime::Exponential { mean } => {
+                // Inverse of the cumulative distribution, 1 - x is never 0.
+                let x: f64 = rng.gen();
+                mean.mul_f64(-(1. - x).ln())
+            }
+        }
+    }
+}
+
+/// [`User`] pausing between its iterations, created with
+/// [`UserExt::with_think_time`](super::UserExt::with_think_time).
+///
+/// The pause is taken in [`User::think`], so it only applies to executors running users in a
+/// loop and is not part of the duration of iterations.
+pub struct Thinking<U> {
+    user: U,
+    think_time: ThinkTime,
+}
+
+impl<U> Thinking<U> {
+    pub(super) fn new(user: U, think_time: ThinkTime) -> Self {
+        Self { user, think_time }
+    }
+}
+
+impl<U: User> User for Thinking<U> {
+    async fn call(&mut self) -> UserResult {
+        self.user.call().await
+    }
+
+    async fn start(&mut self) {
+        self.user.start().await
+    }
+
+    async fn think(&mut self) {
+        let think_time = self.think_time.sample(&mut rand::thread_rng());
+        self.user.think().await;
+        tokio::time::sleep(think_time).await
+    }
+
+    async fn stop(&mut self) {
+        self.user.stop().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::ThinkTime;
+
+    #[test]
+    fn sample_think_time() {
+        let mut rng = rand::thread_rng();
+        let secs = Duration::from_secs;
+        for _ in 0..100 {
+            let uniform = ThinkTime::uniform(secs(1)..secs(3)).sample(&mut rng);
+            assert!(secs(1) <= uniform && uniform < secs(3));
+        }
+        assert_eq!(
+            ThinkTime::uniform(secs(2)..secs(2)).sample(&mut rng),
+            secs(2)
+        );
+        let total: Duration = (0..10_000)
+            .map(|_| ThinkTime::exponential(secs(1)).sample(&mut rng))
+            .sum();
+        let mean = total / 10_000;
+        let millis = Duration::from_millis;
+        assert!(millis(900) < mean && mean < millis(1100), "{mean:?}");
+    }
+}
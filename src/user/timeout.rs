@@ -0,0 +1,125 @@
+//! Fail calls of a user taking too long, so that a hung connection ends its iteration with an
+//! error instead of holding the user for the rest of the executor.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use rusher::prelude::*;
+//! use rusher::user::UserExt;
+//!
+//! async fn user_builder(_: &RuntimeDataStore) -> impl User {
+//!     let user = || async { Ok(()) };
+//!     user.with_timeout(Duration::from_secs(5))
+//! }
+//! ```
+//!
+//! Wrapped in [`Retry`](super::retry::Retry), every attempt gets the whole timeout and attempts
+//! timing out are retried like any other error.
+//!
+//! ```no_run
+//! # use std::time::Duration;
+//! # use rusher::prelude::*;
+//! # use rusher::user::UserExt;
+//! # async fn user_builder(_: &RuntimeDataStore) -> impl User {
+//! # let user = || async { Ok(()) };
+//! user.with_timeout(Duration::from_secs(5)).with_retries(2)
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use tracing::{event, Level};
+
+use crate::{error::Error, UserResult, USER_TASK};
+
+use super::User;
+
+/// [`User`] failing calls of the wrapped user that take longer than a timeout, created with
+/// [`UserExt::with_timeout`](super::UserExt::with_timeout).
+///
+/// A call timing out is dropped where it was waiting and fails with [`Error::Timeout`], it is
+/// added to the [`TIMEOUTS_TOTAL`](crate::tracing::TIMEOUTS_TOTAL) counter. The user is kept
+/// for the next iteration, so state left halfway by the dropped call should be tolerated.
+pub struct Timeout<U> {
+    user: U,
+    timeout: Duration,
+}
+
+impl<U> Timeout<U> {
+    pub(super) fn new(user: U, timeout: Duration) -> Self {
+        Self { user, timeout }
+    }
+}
+
+impl<U: User> User for Timeout<U> {
+    async fn call(&mut self) -> UserResult {
+        match tokio::time::timeout(self.timeout, self.user.call()).await {
+            Ok(res) => res,
+            Err(_) => {
+                event!(name: "timeouts_total.counter", target: USER_TASK, Level::INFO, value = 1u64);
+                Err(Error::Timeout(self.timeout))
+            }
+        }
+    }
+
+    async fn start(&mut self) {
+        self.user.start().await
+    }
+
+    async fn think(&mut self) {
+        self.user.think().await
+    }
+
+    async fn stop(&mut self) {
+        self.user.stop().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use crate::{error::Error, prelude::*, user::UserExt};
+
+    #[tokio::test]
+    async fn time_out_calls() {
+        let mut user = (|| async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok(())
+        })
+        .with_timeout(Duration::from_millis(10));
+        let err = user.call().await.unwrap_err();
+        assert!(matches!(err, Error::Timeout(_)));
+        assert_eq!(err.class(), "timeout");
+
+        let mut user = (|| async { Ok(()) }).with_timeout(Duration::from_millis(10));
+        assert!(user.call().await.is_ok());
+    }
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    async fn user_builder(_: &RuntimeDataStore) -> impl User {
+        (|| async {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok(())
+        })
+        .with_timeout(Duration::from_millis(10))
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn timeouts_do_not_stop_executor() {
+        let execution = Execution::builder()
+            .with_user_builder(user_builder)
+            .with_executor(Executor::Constant {
+                users: 1,
+                duration: Duration::from_millis(200),
+            });
+        let runner = Runner::new(vec![Scenario::new("scenario", execution)]).print_summary(false);
+        assert!(runner.run().await.is_ok());
+        assert!(CALLS.load(Ordering::Relaxed) > 5);
+    }
+}